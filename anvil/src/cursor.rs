@@ -10,6 +10,7 @@ static FALLBACK_CURSOR_DATA: &[u8] = include_bytes!("../resources/cursor.rgba");
 
 pub struct Cursor {
     icons: Vec<Image>,
+    theme: String,
     size: u32,
 }
 
@@ -23,7 +24,11 @@ impl Cursor {
             .and_then(|s| s.parse().ok())
             .unwrap_or(24);
 
-        let theme = CursorTheme::load(&name);
+        Cursor::load_theme(&name, size)
+    }
+
+    pub fn load_theme(name: &str, size: u32) -> Cursor {
+        let theme = CursorTheme::load(name);
         let icons = load_icon(&theme)
             .map_err(|err| warn!("Unable to load xcursor: {}, using fallback cursor", err))
             .unwrap_or_else(|_| {
@@ -39,7 +44,19 @@ impl Cursor {
                 }]
             });
 
-        Cursor { icons, size }
+        Cursor {
+            icons,
+            theme: name.to_string(),
+            size,
+        }
+    }
+
+    pub fn theme(&self) -> &str {
+        &self.theme
+    }
+
+    pub fn size(&self) -> u32 {
+        self.size
     }
 
     pub fn get_image(&self, scale: u32, time: Duration) -> Image {
@@ -1,4 +1,4 @@
-use std::{convert::TryInto, process::Command, sync::atomic::Ordering};
+use std::{convert::TryInto, ops::RangeInclusive, process::Command, sync::atomic::Ordering};
 
 use crate::{focus::PointerFocusTarget, shell::FullscreenSurface, AnvilState};
 
@@ -14,7 +14,7 @@ use smithay::{
     },
     desktop::{layer_map_for_output, WindowSurfaceType},
     input::{
-        keyboard::{keysyms as xkb, FilterResult, Keysym, ModifiersState},
+        keyboard::{self, keysyms as xkb, FilterResult, Keysym, ModifiersState},
         pointer::{AxisFrame, ButtonEvent, MotionEvent},
     },
     output::Scale,
@@ -47,7 +47,7 @@ use smithay::{
             GestureSwipeUpdateEvent as _, PointerMotionEvent, ProximityState, TabletToolButtonEvent,
             TabletToolEvent, TabletToolProximityEvent, TabletToolTipEvent, TabletToolTipState, TouchEvent,
         },
-        session::Session,
+        session::{vt_for_keysym, Session},
     },
     input::{
         pointer::{
@@ -59,6 +59,7 @@ use smithay::{
     },
     reexports::wayland_server::DisplayHandle,
     wayland::{
+        input_timestamps::InputTimestampsSeat,
         pointer_constraints::{with_pointer_constraint, PointerConstraint},
         seat::WaylandFocus,
         tablet_manager::{TabletDescriptor, TabletSeatTrait},
@@ -573,6 +574,22 @@ impl<BackendData: Backend> AnvilState<BackendData> {
 #[cfg(feature = "udev")]
 impl AnvilState<UdevData> {
     pub fn process_input_event<B: InputBackend>(&mut self, dh: &DisplayHandle, event: InputEvent<B>) {
+        // libinput provides microsecond timestamps, pass them on to clients asking for them
+        let time = match &event {
+            InputEvent::Keyboard { event } => Some(Event::time(event)),
+            InputEvent::PointerMotion { event } => Some(Event::time(event)),
+            InputEvent::PointerMotionAbsolute { event } => Some(Event::time(event)),
+            InputEvent::PointerButton { event } => Some(Event::time(event)),
+            InputEvent::PointerAxis { event } => Some(Event::time(event)),
+            InputEvent::TouchDown { event } => Some(Event::time(event)),
+            InputEvent::TouchUp { event } => Some(Event::time(event)),
+            InputEvent::TouchMotion { event } => Some(Event::time(event)),
+            _ => None,
+        };
+        if let Some(time) = time {
+            self.seat.input_timestamps().set_time(time);
+        }
+
         match event {
             InputEvent::Keyboard { event, .. } => match self.keyboard_key_to_action::<B>(event) {
                 #[cfg(feature = "udev")]
@@ -716,6 +733,17 @@ impl AnvilState<UdevData> {
                     self.backend_data.set_debug_flags(debug_flags);
                 }
 
+                KeyAction::CycleCursorSize => {
+                    let cursor = self.backend_data.cursor();
+                    let theme = cursor.theme().to_string();
+                    let size = match cursor.size() {
+                        size if size < 32 => 32,
+                        size if size < 48 => 48,
+                        _ => 24,
+                    };
+                    self.set_cursor_theme(&theme, size);
+                }
+
                 action => match action {
                     KeyAction::None
                     | KeyAction::Quit
@@ -1182,12 +1210,7 @@ impl AnvilState<UdevData> {
         let output = output?;
         let output_geometry = self.space.output_geometry(output)?;
 
-        let transform = output.current_transform();
-        let size = transform.invert().transform_size(output_geometry.size);
-        Some(
-            transform.transform_point_in(evt.position_transformed(size), &size.to_f64())
-                + output_geometry.loc.to_f64(),
-        )
+        Some(evt.position_in_output(output_geometry, output.current_transform()))
     }
 
     fn on_touch_down<B: InputBackend>(&mut self, evt: B::TouchDownEvent) {
@@ -1306,40 +1329,184 @@ enum KeyAction {
     TogglePreview,
     RotateOutput,
     ToggleTint,
+    CycleCursorSize,
     ToggleDecorations,
     /// Do nothing more
     None,
 }
 
-fn process_keyboard_shortcut(modifiers: ModifiersState, keysym: Keysym) -> Option<KeyAction> {
-    if modifiers.ctrl && modifiers.alt && keysym == Keysym::BackSpace || modifiers.logo && keysym == Keysym::q
-    {
-        // ctrl+alt+backspace = quit
-        // logo + q = quit
-        Some(KeyAction::Quit)
-    } else if (xkb::KEY_XF86Switch_VT_1..=xkb::KEY_XF86Switch_VT_12).contains(&keysym.raw()) {
-        // VTSwitch
-        Some(KeyAction::VtSwitch(
-            (keysym.raw() - xkb::KEY_XF86Switch_VT_1 + 1) as i32,
-        ))
-    } else if modifiers.logo && keysym == Keysym::Return {
-        // run terminal
-        Some(KeyAction::Run("weston-terminal".into()))
-    } else if modifiers.logo && (xkb::KEY_1..=xkb::KEY_9).contains(&keysym.raw()) {
-        Some(KeyAction::Screen((keysym.raw() - xkb::KEY_1) as usize))
-    } else if modifiers.logo && modifiers.shift && keysym == Keysym::M {
-        Some(KeyAction::ScaleDown)
-    } else if modifiers.logo && modifiers.shift && keysym == Keysym::P {
-        Some(KeyAction::ScaleUp)
-    } else if modifiers.logo && modifiers.shift && keysym == Keysym::W {
-        Some(KeyAction::TogglePreview)
-    } else if modifiers.logo && modifiers.shift && keysym == Keysym::R {
-        Some(KeyAction::RotateOutput)
-    } else if modifiers.logo && modifiers.shift && keysym == Keysym::T {
-        Some(KeyAction::ToggleTint)
-    } else if modifiers.logo && modifiers.shift && keysym == Keysym::D {
-        Some(KeyAction::ToggleDecorations)
-    } else {
-        None
+/// Modifiers required by a [`KeyboardShortcut`], other modifiers are ignored
+#[derive(Debug, Clone, Copy)]
+struct ShortcutModifiers {
+    ctrl: bool,
+    alt: bool,
+    shift: bool,
+    logo: bool,
+}
+
+impl ShortcutModifiers {
+    const NONE: Self = ShortcutModifiers {
+        ctrl: false,
+        alt: false,
+        shift: false,
+        logo: false,
+    };
+    const CTRL_ALT: Self = ShortcutModifiers {
+        ctrl: true,
+        alt: true,
+        ..Self::NONE
+    };
+    const LOGO: Self = ShortcutModifiers {
+        logo: true,
+        ..Self::NONE
+    };
+    const LOGO_SHIFT: Self = ShortcutModifiers {
+        logo: true,
+        shift: true,
+        ..Self::NONE
+    };
+
+    fn matches(&self, modifiers: &ModifiersState) -> bool {
+        (!self.ctrl || modifiers.ctrl)
+            && (!self.alt || modifiers.alt)
+            && (!self.shift || modifiers.shift)
+            && (!self.logo || modifiers.logo)
     }
 }
+
+/// A keyboard shortcut handled by anvil
+#[derive(Debug)]
+pub struct KeyboardShortcut {
+    modifiers: ShortcutModifiers,
+    /// Keysyms triggering the shortcut
+    keysyms: RangeInclusive<u32>,
+    /// Action for the pressed keysym, `None` lets the key through to the client
+    action: fn(ModifiersState, Keysym) -> Option<KeyAction>,
+    /// What the shortcut does
+    pub description: &'static str,
+}
+
+impl KeyboardShortcut {
+    /// Human-readable key combination triggering the shortcut, e.g. `Logo+Shift+M`
+    pub fn keys(&self) -> String {
+        let mut keys = String::new();
+        for (pressed, name) in [
+            (self.modifiers.ctrl, "Ctrl+"),
+            (self.modifiers.alt, "Alt+"),
+            (self.modifiers.logo, "Logo+"),
+            (self.modifiers.shift, "Shift+"),
+        ] {
+            if pressed {
+                keys.push_str(name);
+            }
+        }
+
+        let name = |raw: u32| keyboard::xkb::keysym_get_name(Keysym::new(raw));
+        keys.push_str(&name(*self.keysyms.start()));
+        if self.keysyms.start() != self.keysyms.end() {
+            keys.push_str("..");
+            keys.push_str(&name(*self.keysyms.end()));
+        }
+        keys
+    }
+}
+
+/// Keyboard shortcuts handled by anvil, the first matching one is triggered
+pub static KEYBOARD_SHORTCUTS: &[KeyboardShortcut] = &[
+    KeyboardShortcut {
+        modifiers: ShortcutModifiers::CTRL_ALT,
+        keysyms: xkb::KEY_BackSpace..=xkb::KEY_BackSpace,
+        action: |_, _| Some(KeyAction::Quit),
+        description: "Quit anvil",
+    },
+    KeyboardShortcut {
+        modifiers: ShortcutModifiers::LOGO,
+        keysyms: xkb::KEY_q..=xkb::KEY_q,
+        action: |_, _| Some(KeyAction::Quit),
+        description: "Quit anvil",
+    },
+    KeyboardShortcut {
+        modifiers: ShortcutModifiers::NONE,
+        keysyms: xkb::KEY_XF86Switch_VT_1..=xkb::KEY_XF86Switch_VT_12,
+        action: vt_switch,
+        description: "Switch to virtual terminal 1..12 (tty-udev only)",
+    },
+    KeyboardShortcut {
+        modifiers: ShortcutModifiers::CTRL_ALT,
+        keysyms: xkb::KEY_F1..=xkb::KEY_F12,
+        action: vt_switch,
+        description: "Switch to virtual terminal 1..12 (tty-udev only)",
+    },
+    KeyboardShortcut {
+        modifiers: ShortcutModifiers::LOGO,
+        keysyms: xkb::KEY_Return..=xkb::KEY_Return,
+        action: |_, _| Some(KeyAction::Run("weston-terminal".into())),
+        description: "Run weston-terminal",
+    },
+    KeyboardShortcut {
+        modifiers: ShortcutModifiers::LOGO,
+        keysyms: xkb::KEY_1..=xkb::KEY_9,
+        action: |_, keysym| Some(KeyAction::Screen((keysym.raw() - xkb::KEY_1) as usize)),
+        description: "Move the pointer to output 1..9",
+    },
+    KeyboardShortcut {
+        modifiers: ShortcutModifiers::LOGO_SHIFT,
+        keysyms: xkb::KEY_M..=xkb::KEY_M,
+        action: |_, _| Some(KeyAction::ScaleDown),
+        description: "Decrease the scale of the current output",
+    },
+    KeyboardShortcut {
+        modifiers: ShortcutModifiers::LOGO_SHIFT,
+        keysyms: xkb::KEY_P..=xkb::KEY_P,
+        action: |_, _| Some(KeyAction::ScaleUp),
+        description: "Increase the scale of the current output",
+    },
+    KeyboardShortcut {
+        modifiers: ShortcutModifiers::LOGO_SHIFT,
+        keysyms: xkb::KEY_W..=xkb::KEY_W,
+        action: |_, _| Some(KeyAction::TogglePreview),
+        description: "Toggle window previews",
+    },
+    KeyboardShortcut {
+        modifiers: ShortcutModifiers::LOGO_SHIFT,
+        keysyms: xkb::KEY_R..=xkb::KEY_R,
+        action: |_, _| Some(KeyAction::RotateOutput),
+        description: "Rotate the current output",
+    },
+    KeyboardShortcut {
+        modifiers: ShortcutModifiers::LOGO_SHIFT,
+        keysyms: xkb::KEY_T..=xkb::KEY_T,
+        action: |_, _| Some(KeyAction::ToggleTint),
+        description: "Toggle the debug tint (tty-udev only)",
+    },
+    KeyboardShortcut {
+        modifiers: ShortcutModifiers::LOGO_SHIFT,
+        keysyms: xkb::KEY_C..=xkb::KEY_C,
+        action: |_, _| Some(KeyAction::CycleCursorSize),
+        description: "Cycle the cursor size (tty-udev only)",
+    },
+    KeyboardShortcut {
+        modifiers: ShortcutModifiers::LOGO_SHIFT,
+        keysyms: xkb::KEY_D..=xkb::KEY_D,
+        action: |_, _| Some(KeyAction::ToggleDecorations),
+        description: "Toggle server-side decorations",
+    },
+];
+
+#[cfg(feature = "udev")]
+fn vt_switch(modifiers: ModifiersState, keysym: Keysym) -> Option<KeyAction> {
+    vt_for_keysym(&modifiers, keysym).map(KeyAction::VtSwitch)
+}
+
+// vt switching is only possible with a session, let other backends forward the keys
+#[cfg(not(feature = "udev"))]
+fn vt_switch(_modifiers: ModifiersState, _keysym: Keysym) -> Option<KeyAction> {
+    None
+}
+
+fn process_keyboard_shortcut(modifiers: ModifiersState, keysym: Keysym) -> Option<KeyAction> {
+    KEYBOARD_SHORTCUTS
+        .iter()
+        .filter(|shortcut| shortcut.modifiers.matches(&modifiers) && shortcut.keysyms.contains(&keysym.raw()))
+        .find_map(|shortcut| (shortcut.action)(modifiers, keysym))
+}
@@ -49,6 +49,14 @@ fn main() {
             tracing::info!("Starting anvil with x11 backend");
             anvil::x11::run_x11();
         }
+        Some("--shortcuts") => {
+            #[allow(clippy::disallowed_macros)]
+            {
+                for shortcut in anvil::input_handler::KEYBOARD_SHORTCUTS {
+                    println!("{:<30} {}", shortcut.keys(), shortcut.description);
+                }
+            }
+        }
         Some(other) => {
             tracing::error!("Unknown backend: {}", other);
         }
@@ -61,6 +69,8 @@ fn main() {
                 for b in POSSIBLE_BACKENDS {
                     println!("\t{}", b);
                 }
+                println!();
+                println!("Use `anvil --shortcuts` to list the available keyboard shortcuts.");
             }
         }
     }
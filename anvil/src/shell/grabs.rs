@@ -419,6 +419,8 @@ impl<BackendData: Backend> PointerGrab<AnvilState<BackendData>> for PointerResiz
             }
             #[cfg(feature = "xwayland")]
             WindowSurface::X11(x11) => {
+                // respect terminal cell increments, aspect ratios and the like
+                self.last_window_size = x11.constrain_size(self.last_window_size);
                 let location = data.space.element_location(&self.window).unwrap();
                 x11.configure(Rectangle::from_loc_and_size(location, self.last_window_size))
                     .unwrap();
@@ -816,6 +818,8 @@ impl<BackendData: Backend> TouchGrab<AnvilState<BackendData>> for TouchResizeSur
             }
             #[cfg(feature = "xwayland")]
             WindowSurface::X11(x11) => {
+                // respect terminal cell increments, aspect ratios and the like
+                self.last_window_size = x11.constrain_size(self.last_window_size);
                 let location = data.space.element_location(&self.window).unwrap();
                 x11.configure(Rectangle::from_loc_and_size(location, self.last_window_size))
                     .unwrap();
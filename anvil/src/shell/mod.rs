@@ -7,7 +7,7 @@ use smithay::xwayland::XWaylandClientData;
 use smithay::wayland::drm_syncobj::DrmSyncobjCachedState;
 
 use smithay::{
-    backend::renderer::utils::on_commit_buffer_handler,
+    backend::renderer::utils::{on_commit_buffer_handler, with_renderer_surface_state},
     desktop::{
         layer_map_for_output, space::SpaceElement, LayerSurface, PopupKind, PopupManager, Space,
         WindowSurfaceType,
@@ -55,6 +55,8 @@ mod xdg;
 pub use self::element::*;
 pub use self::grabs::*;
 
+struct FirstFrameScheduled;
+
 fn fullscreen_output_geometry(
     wl_surface: &WlSurface,
     wl_output: Option<&wl_output::WlOutput>,
@@ -194,7 +196,18 @@ impl<BackendData: Backend> CompositorHandler for AnvilState<BackendData> {
 
                     if let Some(buffer_offset) = buffer_offset {
                         let current_loc = self.space.element_location(&window).unwrap();
-                        self.space.map_element(window, current_loc + buffer_offset, false);
+                        self.space
+                            .map_element(window.clone(), current_loc + buffer_offset, false);
+                    }
+
+                    // repaint newly mapped windows as soon as possible instead of waiting
+                    // for the next frame, as mapping is very noticeable to the user
+                    let has_buffer = with_renderer_surface_state(surface, |state| state.buffer().is_some())
+                        .unwrap_or(false);
+                    if has_buffer && window.0.user_data().insert_if_missing(|| FirstFrameScheduled) {
+                        for output in self.space.outputs_for_element(&window) {
+                            self.backend_data.window_mapped(&output);
+                        }
                     }
                 }
             }
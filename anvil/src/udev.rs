@@ -10,7 +10,7 @@ use crate::state::{DndIcon, SurfaceDmabufFeedback};
 use crate::{
     drawing::*,
     render::*,
-    shell::WindowElement,
+    shell::{FullscreenSurface, WindowElement},
     state::{post_repaint, take_presentation_feedback, AnvilState, Backend},
 };
 #[cfg(feature = "renderer_sync")]
@@ -28,8 +28,13 @@ use smithay::{
             Fourcc,
         },
         drm::{
-            compositor::DrmCompositor, CreateDrmNodeError, DrmAccessError, DrmDevice, DrmDeviceFd, DrmError,
-            DrmEvent, DrmEventMetadata, DrmNode, DrmSurface, GbmBufferedSurface, NodeType,
+            compositor::DrmCompositor,
+            connector::{
+                edid, is_non_desktop, panel_orientation, preferred_mode, privacy_screen_state,
+                set_broadcast_rgb, BroadcastRgb, ContentType, PrivacyScreenState,
+            },
+            primary_node, render_node, CreateDrmNodeError, DrmAccessError, DrmDevice, DrmDeviceFd, DrmError,
+            DrmEvent, DrmEventMetadata, DrmNode, DrmSurface, GbmBufferedSurface,
         },
         egl::{self, context::ContextPriority, EGLDevice, EGLDisplay},
         input::InputEvent,
@@ -62,10 +67,10 @@ use smithay::{
     reexports::{
         calloop::{
             timer::{TimeoutAction, Timer},
-            EventLoop, LoopHandle, RegistrationToken,
+            Dispatcher, EventLoop, LoopHandle, RegistrationToken,
         },
         drm::{
-            control::{connector, crtc, Device, ModeTypeFlags},
+            control::{connector, crtc, Mode},
             Device as _,
         },
         input::{DeviceCapability, Libinput},
@@ -79,6 +84,7 @@ use smithay::{
     utils::{Clock, DeviceFd, IsAlive, Logical, Monotonic, Physical, Point, Rectangle, Scale, Transform},
     wayland::{
         compositor,
+        content_type::ContentTypeSurfaceCachedState,
         dmabuf::{
             DmabufFeedback, DmabufFeedbackBuilder, DmabufGlobal, DmabufHandler, DmabufState, ImportNotifier,
         },
@@ -136,6 +142,8 @@ pub struct UdevData {
     pointer_image: crate::cursor::Cursor,
     debug_flags: DebugFlags,
     keyboards: Vec<smithay::reexports::input::Device>,
+    handle: LoopHandle<'static, AnvilState<UdevData>>,
+    idle_timers: HashMap<(DrmNode, crtc::Handle), RegistrationToken>,
 }
 
 impl UdevData {
@@ -154,6 +162,10 @@ impl UdevData {
     pub fn debug_flags(&self) -> DebugFlags {
         self.debug_flags
     }
+
+    pub fn cursor(&self) -> &crate::cursor::Cursor {
+        &self.pointer_image
+    }
 }
 
 impl DmabufHandler for AnvilState<UdevData> {
@@ -202,6 +214,20 @@ impl Backend for UdevData {
         }
     }
 
+    fn window_mapped(&mut self, output: &Output) {
+        let Some(id) = output.user_data().get::<UdevOutputId>() else {
+            return;
+        };
+
+        // only an idle output waits for a whole frame before checking for damage again,
+        // otherwise the next repaint is already due
+        if let Some(token) = self.idle_timers.remove(&(id.device_id, id.crtc)) {
+            self.handle.remove(token);
+            let (node, crtc) = (id.device_id, id.crtc);
+            self.handle.insert_idle(move |data| data.render(node, Some(crtc)));
+        }
+    }
+
     fn update_led_state(&mut self, led_state: LedState) {
         for keyboard in self.keyboards.iter_mut() {
             keyboard.led_update(led_state.into());
@@ -233,7 +259,7 @@ pub fn run_udev() {
     } else {
         primary_gpu(session.seat())
             .unwrap()
-            .and_then(|x| DrmNode::from_path(x).ok()?.node_with_type(NodeType::Render)?.ok())
+            .and_then(|x| render_node(&DrmNode::from_path(x).ok()?))
             .unwrap_or_else(|| {
                 all_gpus(session.seat())
                     .unwrap()
@@ -261,6 +287,8 @@ pub fn run_udev() {
         fps_texture: None,
         debug_flags: DebugFlags::empty(),
         keyboards: Vec::new(),
+        handle: event_loop.handle(),
+        idle_timers: HashMap::new(),
     };
     let mut state = AnvilState::init(display, event_loop.handle(), data, true);
 
@@ -282,14 +310,14 @@ pub fn run_udev() {
         state.backend_data.session.clone().into(),
     );
     libinput_context.udev_assign_seat(&state.seat_name).unwrap();
-    let libinput_backend = LibinputInputBackend::new(libinput_context.clone());
+    let libinput_backend = LibinputInputBackend::new(libinput_context);
 
     /*
      * Bind all our objects that get driven by the event loop
      */
-    event_loop
-        .handle()
-        .insert_source(libinput_backend, move |mut event, _, data| {
+    let libinput_dispatcher = Dispatcher::new(
+        libinput_backend,
+        move |mut event, _, data: &mut AnvilState<UdevData>| {
             let dh = data.backend_data.dh.clone();
             if let InputEvent::DeviceAdded { device } = &mut event {
                 if device.has_capability(DeviceCapability::Keyboard) {
@@ -305,7 +333,11 @@ pub fn run_udev() {
             }
 
             data.process_input_event(&dh, event)
-        })
+        },
+    );
+    event_loop
+        .handle()
+        .register_dispatcher(libinput_dispatcher.clone())
         .unwrap();
 
     let handle = event_loop.handle();
@@ -313,7 +345,9 @@ pub fn run_udev() {
         .handle()
         .insert_source(notifier, move |event, &mut (), data| match event {
             SessionEvent::PauseSession => {
-                libinput_context.suspend();
+                let _ = libinput_dispatcher
+                    .as_source_mut()
+                    .handle_session_event(&SessionEvent::PauseSession);
                 info!("pausing session");
 
                 for backend in data.backend_data.backends.values_mut() {
@@ -327,7 +361,10 @@ pub fn run_udev() {
             SessionEvent::ActivateSession => {
                 info!("resuming session");
 
-                if let Err(err) = libinput_context.resume() {
+                if let Err(err) = libinput_dispatcher
+                    .as_source_mut()
+                    .handle_session_event(&SessionEvent::ActivateSession)
+                {
                     error!("Failed to resume libinput context: {:?}", err);
                 }
                 for (node, backend) in data
@@ -349,9 +386,14 @@ pub fn run_udev() {
                     if let Some(lease_global) = backend.leasing_global.as_mut() {
                         lease_global.resume::<AnvilState<UdevData>>();
                     }
-                    for surface in backend.surfaces.values_mut() {
-                        if let Err(err) = surface.compositor.reset_state() {
-                            warn!("Failed to reset drm surface state: {}", err);
+                    // try to bring back the last frame (including the cursor) right away,
+                    // otherwise reset the surfaces and let the next frame do a full modeset.
+                    if let Err(err) = backend.drm.restore_state() {
+                        warn!("Failed to restore drm state: {}", err);
+                        for surface in backend.surfaces.values_mut() {
+                            if let Err(err) = surface.compositor.reset_state() {
+                                warn!("Failed to reset drm surface state: {}", err);
+                            }
                         }
                     }
                     handle.insert_idle(move |data| data.render(node, None));
@@ -438,12 +480,7 @@ pub fn run_udev() {
     });
 
     // Expose syncobj protocol if supported by primary GPU
-    if let Some(primary_node) = state
-        .backend_data
-        .primary_gpu
-        .node_with_type(NodeType::Primary)
-        .and_then(|x| x.ok())
-    {
+    if let Some(primary_node) = primary_node(&state.backend_data.primary_gpu) {
         if let Some(backend) = state.backend_data.backends.get(&primary_node) {
             let import_device = backend.drm.device_fd().clone();
             if supports_syncobj_eventfd(&import_device) {
@@ -630,6 +667,15 @@ impl SurfaceComposition {
         }
     }
 
+    fn use_mode(&mut self, mode: Mode) -> Result<(), SwapBuffersError> {
+        match self {
+            SurfaceComposition::Compositor(c) => c.use_mode(mode).map_err(Into::<SwapBuffersError>::into),
+            SurfaceComposition::Surface { surface, .. } => {
+                surface.use_mode(mode).map_err(Into::<SwapBuffersError>::into)
+            }
+        }
+    }
+
     fn reset_buffers(&mut self) {
         match self {
             SurfaceComposition::Compositor(c) => c.reset_buffers(),
@@ -760,6 +806,9 @@ struct SurfaceData {
     #[cfg(feature = "debug")]
     fps_element: Option<FpsElement<MultiTexture>>,
     dmabuf_feedback: Option<DrmSurfaceDmabufFeedback>,
+    connector: connector::Handle,
+    privacy_screen: Option<PrivacyScreenState>,
+    content_type: Option<ContentType>,
 }
 
 impl Drop for SurfaceData {
@@ -852,6 +901,34 @@ fn get_surface_dmabuf_feedback(
 }
 
 impl AnvilState<UdevData> {
+    /// Switch to another cursor theme or size without restarting
+    pub fn set_cursor_theme(&mut self, name: &str, size: u32) {
+        let cursor = crate::cursor::Cursor::load_theme(name, size);
+
+        #[cfg(feature = "xwayland")]
+        if let Some(xwm) = self.xwm.as_mut() {
+            let image = cursor.get_image(1, Duration::ZERO);
+            if let Err(err) = xwm.set_cursor(
+                &image.pixels_rgba,
+                smithay::utils::Size::from((image.width as u16, image.height as u16)),
+                Point::from((image.xhot as u16, image.yhot as u16)),
+            ) {
+                warn!(?err, "Failed to update xwayland default cursor");
+            }
+            // let X11 clients loading cursors themselves follow the new theme
+            if let Err(err) = xwm.set_display_settings(xwm.client_scale() as f64, name, size) {
+                warn!(?err, "Failed to update xwayland cursor settings");
+            }
+        }
+
+        // the cached buffers belong to the old theme, re-render the cursor on all outputs
+        self.backend_data.pointer_image = cursor;
+        self.backend_data.pointer_images.clear();
+        for output in self.space.outputs().cloned().collect::<Vec<_>>() {
+            self.backend_data.reset_buffers(&output);
+        }
+    }
+
     fn device_added(&mut self, node: DrmNode, path: &Path) -> Result<(), DeviceAddError> {
         // Try to open the device
         let fd = self
@@ -865,7 +942,10 @@ impl AnvilState<UdevData> {
 
         let fd = DrmDeviceFd::new(DeviceFd::from(fd));
 
-        let (drm, notifier) = DrmDevice::new(fd.clone(), true).map_err(DeviceAddError::DrmDevice)?;
+        // keep the contents of the previous drm master (e.g. plymouth) on screen until our first frame
+        let inherit_state = std::env::var("ANVIL_INHERIT_DRM_STATE").is_ok();
+        let (drm, notifier) =
+            DrmDevice::new(fd.clone(), !inherit_state).map_err(DeviceAddError::DrmDevice)?;
         let gbm = GbmDevice::new(fd).map_err(DeviceAddError::GbmDevice)?;
 
         let registration_token = self
@@ -938,23 +1018,7 @@ impl AnvilState<UdevData> {
         let output_name = format!("{}-{}", connector.interface().as_str(), connector.interface_id());
         info!(?crtc, "Trying to setup connector {}", output_name,);
 
-        let non_desktop = device
-            .drm
-            .get_properties(connector.handle())
-            .ok()
-            .and_then(|props| {
-                let (info, value) = props
-                    .into_iter()
-                    .filter_map(|(handle, value)| {
-                        let info = device.drm.get_property(handle).ok()?;
-
-                        Some((info, value))
-                    })
-                    .find(|(info, _)| info.name().to_str() == Ok("non-desktop"))?;
-
-                info.value_type().convert_value(value).as_boolean()
-            })
-            .unwrap_or(false);
+        let non_desktop = is_non_desktop(&device.drm, connector.handle()).unwrap_or(false);
 
         let display_info = display_info::for_connector(&device.drm, connector.handle());
 
@@ -979,13 +1043,17 @@ impl AnvilState<UdevData> {
                 );
             }
         } else {
-            let mode_id = connector
-                .modes()
-                .iter()
-                .position(|mode| mode.mode_type().contains(ModeTypeFlags::PREFERRED))
-                .unwrap_or(0);
-
-            let drm_mode = connector.modes()[mode_id];
+            let inherited_mode = device
+                .drm
+                .current_crtc_mode(connector.handle())
+                .ok()
+                .flatten()
+                .filter(|(current_crtc, mode)| *current_crtc == crtc && connector.modes().contains(mode))
+                .map(|(_, mode)| mode);
+            let Some(drm_mode) = inherited_mode.or_else(|| preferred_mode(&connector)) else {
+                warn!("Connector {} has no modes", output_name);
+                return;
+            };
             let wl_mode = WlMode::from(drm_mode);
 
             let surface = match device.drm.create_surface(crtc, drm_mode, &[connector.handle()]) {
@@ -1014,8 +1082,30 @@ impl AnvilState<UdevData> {
                 .fold(0, |acc, o| acc + self.space.output_geometry(o).unwrap().size.w);
             let position = (x, 0).into();
 
+            // compensate for panels mounted rotated inside the device
+            let transform = panel_orientation(&device.drm, connector.handle())
+                .ok()
+                .flatten()
+                .map(|orientation| orientation.transform());
+
+            // avoid crushed blacks on TVs, unless the user knows better
+            let broadcast_rgb = match std::env::var("ANVIL_BROADCAST_RGB").as_deref() {
+                Ok("full") => Some(BroadcastRgb::Full),
+                Ok("limited") => Some(BroadcastRgb::Limited),
+                Ok("auto") => Some(BroadcastRgb::Automatic),
+                _ => edid(&device.drm, connector.handle())
+                    .ok()
+                    .flatten()
+                    .map(|edid| BroadcastRgb::from_edid(&edid)),
+            };
+            if let Some(broadcast_rgb) = broadcast_rgb {
+                if let Err(err) = set_broadcast_rgb(&device.drm, connector.handle(), broadcast_rgb) {
+                    debug!(?err, "Failed to set broadcast rgb");
+                }
+            }
+
             output.set_preferred(wl_mode);
-            output.change_current_state(Some(wl_mode), None, None, Some(position));
+            output.change_current_state(Some(wl_mode), transform, None, Some(position));
             self.space.map_output(&output, position);
 
             output.user_data().insert_if_missing(|| UdevOutputId {
@@ -1115,6 +1205,11 @@ impl AnvilState<UdevData> {
                 #[cfg(feature = "debug")]
                 fps_element,
                 dmabuf_feedback,
+                connector: connector.handle(),
+                privacy_screen: privacy_screen_state(&device.drm, connector.handle())
+                    .ok()
+                    .flatten(),
+                content_type: None,
             };
 
             device.surfaces.insert(crtc, surface);
@@ -1192,6 +1287,68 @@ impl AnvilState<UdevData> {
             }
         }
 
+        // with an inherited drm state, connectors not used by any output are still lit
+        if std::env::var("ANVIL_INHERIT_DRM_STATE").is_ok() {
+            if let Some(device) = self.backend_data.backends.get_mut(&node) {
+                if let Err(err) = device.drm.disable_unused() {
+                    warn!(?err, "Failed to disable unused connectors");
+                }
+            }
+        }
+
+        // virtual machines change the preferred mode, when the window on the host gets resized
+        if let Some(device) = self.backend_data.backends.get_mut(&node) {
+            let is_virtual = device
+                .drm
+                .get_driver()
+                .map(|driver| matches!(driver.name().to_str(), Some("virtio_gpu" | "qxl" | "vmwgfx")))
+                .unwrap_or(false);
+            if is_virtual {
+                for (crtc, surface) in device.surfaces.iter_mut() {
+                    let Some(mode) = device
+                        .drm
+                        .get_connector(surface.connector, false)
+                        .ok()
+                        .and_then(|info| preferred_mode(&info))
+                    else {
+                        continue;
+                    };
+                    if mode == surface.compositor.surface().pending_mode() {
+                        continue;
+                    }
+                    info!(?crtc, ?mode, "Preferred mode changed, following the host window");
+                    if let Err(err) = surface.compositor.use_mode(mode) {
+                        warn!(?err, "Failed to change mode");
+                        continue;
+                    }
+                    let output = self.space.outputs().find(|o| {
+                        o.user_data()
+                            .get::<UdevOutputId>()
+                            .map(|id| id.device_id == node && id.crtc == *crtc)
+                            .unwrap_or(false)
+                    });
+                    if let Some(output) = output {
+                        let wl_mode = WlMode::from(mode);
+                        output.set_preferred(wl_mode);
+                        output.change_current_state(Some(wl_mode), None, None, None);
+                    }
+                }
+            }
+        }
+
+        // the privacy screen might have been toggled by a hardware hotkey
+        if let Some(device) = self.backend_data.backends.get_mut(&node) {
+            for surface in device.surfaces.values_mut() {
+                let state = privacy_screen_state(&device.drm, surface.connector)
+                    .ok()
+                    .flatten();
+                if state != surface.privacy_screen {
+                    info!(connector = ?surface.connector, ?state, "Privacy screen state changed");
+                    surface.privacy_screen = state;
+                }
+            }
+        }
+
         // fixup window coordinates
         crate::shell::fixup_positions(&mut self.space, self.pointer.current_location());
     }
@@ -1420,6 +1577,9 @@ impl AnvilState<UdevData> {
             return;
         };
 
+        // the idle timer either fired or is superseded by this repaint
+        self.backend_data.idle_timers.remove(&(node, crtc));
+
         let start = Instant::now();
 
         // TODO get scale from the rendersurface when supporting HiDPI
@@ -1476,6 +1636,27 @@ impl AnvilState<UdevData> {
             return;
         };
 
+        // let the display know, when a fullscreen game or video is shown,
+        // so e.g. TVs can switch to a low latency mode.
+        let content_type = output
+            .user_data()
+            .get::<FullscreenSurface>()
+            .and_then(|fullscreen| fullscreen.get())
+            .and_then(|window| window.wl_surface().map(|surface| surface.into_owned()))
+            .map(|surface| {
+                compositor::with_states(&surface, |states| {
+                    let mut guard = states.cached_state.get::<ContentTypeSurfaceCachedState>();
+                    ContentType::from(*guard.current().content_type())
+                })
+            })
+            .unwrap_or(ContentType::Graphics);
+        if surface.content_type != Some(content_type) {
+            if let Err(err) = surface.compositor.surface().set_content_type(content_type) {
+                debug!(?err, "Failed to set content type");
+            }
+            surface.content_type = Some(content_type);
+        }
+
         let result = render_surface(
             surface,
             &mut renderer,
@@ -1531,12 +1712,14 @@ impl AnvilState<UdevData> {
                 crtc,
             );
             let timer = Timer::from_duration(reschedule_duration);
-            self.handle
+            let token = self
+                .handle
                 .insert_source(timer, move |_, _, data| {
                     data.render(node, Some(crtc));
                     TimeoutAction::Drop
                 })
                 .expect("failed to schedule frame timer");
+            self.backend_data.idle_timers.insert((node, crtc), token);
         } else {
             let elapsed = start.elapsed();
             tracing::trace!(?elapsed, "rendered surface");
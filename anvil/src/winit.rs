@@ -89,6 +89,7 @@ impl Backend for WinitData {
         self.full_redraw = 4;
     }
     fn early_import(&mut self, _surface: &wl_surface::WlSurface) {}
+    fn window_mapped(&mut self, _output: &Output) {}
     fn update_led_state(&mut self, _led_state: LedState) {}
 }
 
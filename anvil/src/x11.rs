@@ -92,6 +92,7 @@ impl Backend for X11Data {
         self.surface.reset_buffers();
     }
     fn early_import(&mut self, _surface: &wl_surface::WlSurface) {}
+    fn window_mapped(&mut self, _output: &Output) {}
     fn update_led_state(&mut self, _led_state: LedState) {}
 }
 
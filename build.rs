@@ -44,6 +44,7 @@ fn gl_generate() {
                 "EGL_EXT_buffer_age",
                 "EGL_EXT_swap_buffers_with_damage",
                 "EGL_KHR_swap_buffers_with_damage",
+                "EGL_KHR_partial_update",
                 "EGL_KHR_fence_sync",
                 "EGL_ANDROID_native_fence_sync",
                 "EGL_IMG_context_priority",
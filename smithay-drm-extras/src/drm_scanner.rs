@@ -28,7 +28,7 @@ use std::{
 use drm::control::{connector, crtc, Device as ControlDevice};
 
 mod connector_scanner;
-pub use connector_scanner::{ConnectorScanEvent, ConnectorScanResult, ConnectorScanner};
+pub use connector_scanner::{connector_path, ConnectorScanEvent, ConnectorScanResult, ConnectorScanner};
 
 mod crtc_mapper;
 pub use crtc_mapper::{CrtcMapper, SimpleCrtcMapper};
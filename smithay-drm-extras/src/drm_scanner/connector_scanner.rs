@@ -40,6 +40,20 @@ impl ConnectorScanner {
         let mut added = Vec::new();
         let mut removed = Vec::new();
 
+        // Connectors behind MST hubs (e.g. docks) are created and destroyed dynamically by the kernel.
+        // Once a branch vanishes its connectors are gone from the resource handles entirely,
+        // so they have to be reported as disconnected based on the last known state.
+        self.connectors.retain(|handle, info| {
+            if connector_handles.contains(handle) {
+                return true;
+            }
+
+            if info.state() == connector::State::Connected {
+                removed.push(info.clone());
+            }
+            false
+        });
+
         for conn in connector_handles
             .iter()
             .filter_map(|conn| drm.get_connector(*conn, true).ok())
@@ -75,6 +89,37 @@ impl ConnectorScanner {
     }
 }
 
+/// Read the `PATH` property of a connector.
+///
+/// Connectors behind DisplayPort MST hubs get new handles every time they (re-)appear,
+/// the path (e.g. `mst:58-1-8`) on the other hand stays the same for a given port of a hub
+/// and can be used to identify the output across hotplugs.
+///
+/// Returns `None` for connectors without a path, which is the case for all non-MST connectors.
+pub fn connector_path(drm: &impl ControlDevice, connector: connector::Handle) -> Option<String> {
+    let props = drm.get_properties(connector).ok()?;
+
+    let (info, value) = props
+        .into_iter()
+        .filter_map(|(handle, value)| {
+            let info = drm.get_property(handle).ok()?;
+
+            Some((info, value))
+        })
+        .find(|(info, _)| info.name().to_str() == Ok("PATH"))?;
+
+    let blob = info.value_type().convert_value(value).as_blob()?;
+    if blob == 0 {
+        return None;
+    }
+    let data = drm.get_property_blob(blob).ok()?;
+
+    let data = data.split(|b| *b == 0).next().unwrap_or_default();
+    let path = std::str::from_utf8(data).ok()?;
+
+    (!path.is_empty()).then(|| path.to_owned())
+}
+
 /// Result of [`ConnectorScanner::scan`]
 ///
 /// You can use `added` and `removed` fields of this result manually,
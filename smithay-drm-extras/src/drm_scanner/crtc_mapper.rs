@@ -86,12 +86,13 @@ impl super::CrtcMapper for SimpleCrtcMapper {
         drm: &impl ControlDevice,
         connectors: impl Iterator<Item = &'a connector::Info> + Clone,
     ) {
-        for connector in connectors
-            .clone()
-            .filter(|conn| conn.state() != connector::State::Connected)
-        {
-            self.crtcs.remove(&connector.handle());
-        }
+        // Drop mappings of connectors that do not exist anymore (e.g. removed MST connectors)
+        // as well as connectors that got disconnected, so their crtcs can be reused.
+        self.crtcs.retain(|handle, _| {
+            connectors
+                .clone()
+                .any(|conn| conn.handle() == *handle && conn.state() == connector::State::Connected)
+        });
 
         let mut needs_crtc: Vec<&connector::Info> = connectors
             .filter(|conn| conn.state() == connector::State::Connected)
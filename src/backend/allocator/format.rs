@@ -36,10 +36,13 @@
 //! assert_eq!(get_depth(Fourcc::Argb8888), Some(32));
 //! assert_eq!(get_depth(Fourcc::Xrgb8888), Some(24));
 //! ```
+//!
+//! Additionally [`negotiate_formats`] picks the formats and modifiers usable for buffers, that are
+//! rendered to and scanned out.
 
 use std::sync::Arc;
 
-use super::Format;
+use super::{Format, Fourcc, Modifier};
 use indexmap::IndexSet;
 
 /// Macro to generate table lookup functions for formats.
@@ -368,6 +371,73 @@ format_tables! {
     // TODO: YUV and other formats
 }
 
+/// Intersect the modifiers of a format supported by a renderer and for scan-out
+///
+/// The modifiers are returned in the order of `scanout`.
+/// If one side only supports implicit modifiers and the other explicitly supports [`Modifier::Linear`],
+/// but no implicit modifiers, [`Modifier::Invalid`] is returned instead of an empty intersection.
+/// This should at least result in a working pipeline, possibly with a linear buffer.
+pub fn negotiate_modifiers(
+    renderer: impl IntoIterator<Item = Modifier>,
+    scanout: impl IntoIterator<Item = Modifier>,
+) -> Vec<Modifier> {
+    let renderer = renderer.into_iter().collect::<IndexSet<_>>();
+    let scanout = scanout.into_iter().collect::<IndexSet<_>>();
+
+    let implicit_only =
+        |modifiers: &IndexSet<Modifier>| modifiers.len() == 1 && modifiers.contains(&Modifier::Invalid);
+    let explicit_linear = |modifiers: &IndexSet<Modifier>| {
+        !modifiers.contains(&Modifier::Invalid) && modifiers.contains(&Modifier::Linear)
+    };
+
+    if (implicit_only(&scanout) && explicit_linear(&renderer))
+        || (implicit_only(&renderer) && explicit_linear(&scanout))
+    {
+        vec![Modifier::Invalid]
+    } else {
+        scanout.intersection(&renderer).copied().collect()
+    }
+}
+
+/// Negotiate the formats of buffers, that are rendered to and scanned out
+///
+/// Returns the codes of `preferred`, which are supported by the renderer, the allocator and for scan-out,
+/// each with its usable modifiers (see [`negotiate_modifiers`]). The order of `preferred` is kept,
+/// so the first entry is the optimal format. Scan-out is allowed to only support the opaque alternative
+/// of a code (see [`get_opaque`]).
+///
+/// - `renderer` are the formats the renderer can render to,
+///   e.g. [`Bind::supported_formats`](crate::backend::renderer::Bind::supported_formats)
+/// - `allocator` are the formats the allocator can allocate, `None` if they are unknown
+/// - `scanout` are the formats of the plane used for scan-out, e.g. `PlaneInfo::formats` of a drm surface
+pub fn negotiate_formats(
+    preferred: &[Fourcc],
+    renderer: &FormatSet,
+    allocator: Option<&FormatSet>,
+    scanout: &FormatSet,
+) -> Vec<(Fourcc, Vec<Modifier>)> {
+    preferred
+        .iter()
+        .filter_map(|&code| {
+            let opaque_code = get_opaque(code).unwrap_or(code);
+            let mut modifiers = negotiate_modifiers(
+                renderer
+                    .iter()
+                    .filter(|format| format.code == code)
+                    .map(|format| format.modifier),
+                scanout
+                    .iter()
+                    .filter(|format| format.code == code || format.code == opaque_code)
+                    .map(|format| format.modifier),
+            );
+            if let Some(allocator) = allocator {
+                modifiers.retain(|&modifier| allocator.contains(&Format { code, modifier }));
+            }
+            (!modifiers.is_empty()).then_some((code, modifiers))
+        })
+        .collect()
+}
+
 /// A set of [`Format`]s
 #[derive(Debug, Default, Clone)]
 pub struct FormatSet {
@@ -493,7 +563,50 @@ impl Iterator for FormatSetIntoIter {
 
 #[cfg(test)]
 mod tests {
-    use super::{_impl_formats, get_bpp, get_depth, get_opaque, get_transparent, has_alpha};
+    use super::{
+        _impl_formats, get_bpp, get_depth, get_opaque, get_transparent, has_alpha, negotiate_formats,
+        negotiate_modifiers, FormatSet,
+    };
+    use crate::backend::allocator::{Format, Fourcc, Modifier};
+
+    #[test]
+    fn negotiate() {
+        let formats = |formats: &[(Fourcc, Modifier)]| {
+            formats
+                .iter()
+                .map(|&(code, modifier)| Format { code, modifier })
+                .collect::<FormatSet>()
+        };
+        // I915_FORMAT_MOD_X_TILED
+        let tiled = Modifier::from(0x0100_0000_0000_0001);
+        let renderer = formats(&[
+            (Fourcc::Argb2101010, Modifier::Linear),
+            (Fourcc::Argb8888, Modifier::Linear),
+            (Fourcc::Argb8888, tiled),
+        ]);
+        let scanout = formats(&[(Fourcc::Xrgb8888, tiled), (Fourcc::Xrgb8888, Modifier::Linear)]);
+
+        assert_eq!(
+            negotiate_formats(
+                &[Fourcc::Argb2101010, Fourcc::Argb8888],
+                &renderer,
+                None,
+                &scanout
+            ),
+            vec![(Fourcc::Argb8888, vec![tiled, Modifier::Linear])]
+        );
+        let allocator = formats(&[(Fourcc::Argb8888, Modifier::Linear)]);
+        assert_eq!(
+            negotiate_formats(&[Fourcc::Argb8888], &renderer, Some(&allocator), &scanout),
+            vec![(Fourcc::Argb8888, vec![Modifier::Linear])]
+        );
+
+        assert_eq!(
+            negotiate_modifiers([Modifier::Linear], [Modifier::Invalid]),
+            vec![Modifier::Invalid]
+        );
+        assert!(negotiate_modifiers([Modifier::Linear, Modifier::Invalid], [tiled]).is_empty());
+    }
 
     /// Tests that opaque alternatives are not the same as the variant with alpha.
     #[test]
@@ -149,7 +149,7 @@ use crate::{
     backend::{
         allocator::{
             dmabuf::{AsDmabuf, Dmabuf},
-            format::{get_opaque, has_alpha},
+            format::{get_opaque, has_alpha, negotiate_modifiers},
             gbm::{GbmAllocator, GbmBuffer, GbmBufferFlags, GbmDevice},
             Allocator, Buffer, Slot, Swapchain,
         },
@@ -172,7 +172,11 @@ use crate::{
     wayland::{shm, single_pixel_buffer},
 };
 
-use super::{error::AccessError, DrmDeviceFd, DrmSurface, Framebuffer, PlaneClaim, PlaneInfo, Planes};
+use super::{
+    error::AccessError,
+    refresh_rate::{find_mode_for_content, RefreshRatePolicy},
+    DrmDeviceFd, DrmSurface, Framebuffer, PlaneClaim, PlaneInfo, Planes,
+};
 
 pub mod dumb;
 mod elements;
@@ -1858,37 +1862,8 @@ where
             return Err((allocator, FrameError::NoSupportedRendererFormat));
         }
 
-        let formats = {
-            // Special case: if a format supports explicit LINEAR (but no implicit Modifiers)
-            // and the other doesn't support any modifier, force Implicit.
-            // This should at least result in a working pipeline possibly with a linear buffer,
-            // but we cannot be sure.
-            if (plane_formats.len() == 1
-                && plane_formats.iter().next().unwrap().modifier == DrmModifier::Invalid
-                && renderer_formats
-                    .iter()
-                    .all(|x| x.modifier != DrmModifier::Invalid)
-                && renderer_formats.iter().any(|x| x.modifier == DrmModifier::Linear))
-                || (renderer_formats.len() == 1
-                    && renderer_formats.first().unwrap().modifier == DrmModifier::Invalid
-                    && plane_formats.iter().all(|x| x.modifier != DrmModifier::Invalid)
-                    && plane_formats.iter().any(|x| x.modifier == DrmModifier::Linear))
-            {
-                vec![DrmFormat {
-                    code,
-                    modifier: DrmModifier::Invalid,
-                }]
-            } else {
-                plane_modifiers
-                    .intersection(&renderer_modifiers)
-                    .cloned()
-                    .map(|modifier| DrmFormat { code, modifier })
-                    .collect::<Vec<_>>()
-            }
-        };
-        debug!("Testing Formats: {:?}", formats);
-
-        let modifiers = formats.iter().map(|x| x.modifier).collect::<Vec<_>>();
+        let modifiers = negotiate_modifiers(renderer_modifiers, plane_modifiers);
+        debug!("Testing modifiers: {:?}", modifiers);
         let mode = drm.pending_mode();
 
         let mut swapchain: Swapchain<A> = Swapchain::new(
@@ -2274,6 +2249,24 @@ where
         // This will hold the element assigned on the cursor plane if any
         let mut cursor_plane_element: Option<&'a E> = None;
 
+        // Overlay planes are reserved for scan-out candidates further down the stack,
+        // so they are not taken by elements less likely to benefit from them.
+        // A candidate at the bottom of the stack covering the whole output, like a fullscreen game,
+        // is expected to end up on the primary plane, so no overlay plane is reserved for it.
+        let primary_plane_candidate =
+            output_elements
+                .last()
+                .is_some_and(|(element, element_geometry, _, element_is_opaque)| {
+                    element.kind() == Kind::ScanoutCandidate
+                        && *element_is_opaque
+                        && element_geometry.contains_rect(output_geometry)
+                });
+        let mut remaining_scanout_candidates = output_elements
+            .iter()
+            .filter(|(element, ..)| element.kind() == Kind::ScanoutCandidate)
+            .count()
+            - primary_plane_candidate as usize;
+
         let output_elements_len = output_elements.len();
         for (index, (element, element_geometry, element_visible_area, element_is_opaque)) in
             output_elements.iter().enumerate()
@@ -2282,6 +2275,14 @@ where
             let element_geometry = *element_geometry;
             let remaining_elements = output_elements_len - index;
             let element_is_opaque = *element_is_opaque;
+            let reserved_overlay_planes = if element.kind() == Kind::ScanoutCandidate {
+                if !(primary_plane_candidate && remaining_elements == 1) {
+                    remaining_scanout_candidates -= 1;
+                }
+                0
+            } else {
+                remaining_scanout_candidates
+            };
 
             // Check if we found our last item, we can try to do
             // direct scan-out on the primary plane
@@ -2322,6 +2323,7 @@ where
                 output_transform,
                 output_geometry,
                 try_assign_primary_plane,
+                reserved_overlay_planes,
             ) {
                 Ok(direct_scan_out_plane) => {
                     match direct_scan_out_plane.type_ {
@@ -2343,6 +2345,14 @@ where
                     }
                 }
                 Err(reason) => {
+                    if element.kind() == Kind::ScanoutCandidate {
+                        trace!(
+                            "scan-out candidate {:?} not offloaded, rendering it on the primary plane: {:?}",
+                            element_id,
+                            reason,
+                        );
+                    }
+
                     if let Some(reason) = reason {
                         if !render_element_states.states.contains_key(element_id) {
                             render_element_states.states.insert(
@@ -2755,6 +2765,21 @@ where
         Ok(())
     }
 
+    /// Resets the compositor after the system resumed from suspend or hibernation.
+    ///
+    /// The gpu state might have been lost without any session switch, so in addition to
+    /// [`reset_state`](DrmCompositor::reset_state) this drops the underlying buffers, which forces
+    /// the next call to [`render_frame`](DrmCompositor::render_frame) to redraw the whole output.
+    ///
+    /// Smithay does not listen for system resumes itself, call this for every surface once
+    /// logind reports `PrepareForSleep(false)`. Imported client buffers can be invalidated using
+    /// `GlesRenderer::clear_dmabuf_cache` and `invalidate_surface_tree_textures`.
+    pub fn reset_after_resume(&mut self) -> Result<(), DrmError> {
+        self.reset_state()?;
+        self.reset_buffers();
+        Ok(())
+    }
+
     #[profiling::function]
     fn submit(&mut self) -> FrameResult<(), A, F> {
         let QueuedFrame {
@@ -2930,6 +2955,33 @@ where
         Ok(())
     }
 
+    /// Tries to switch to a mode matching the frame rate of the displayed content
+    ///
+    /// `modes` are the available modes of the connectors of this compositor and `content_rate`
+    /// the frame rate of the content in millihertz. A matching mode is selected via
+    /// [`find_mode_for_content`](super::refresh_rate::find_mode_for_content) and only used,
+    /// if the `policy` allows the switch. Like [`DrmCompositor::use_mode`] the mode is applied
+    /// on the next commit.
+    ///
+    /// Returns the new mode, if the mode was changed.
+    pub fn use_content_refresh_rate(
+        &mut self,
+        modes: &[Mode],
+        content_rate: u32,
+        policy: &mut impl RefreshRatePolicy,
+    ) -> FrameResult<Option<Mode>, A, F> {
+        let current = self.pending_mode();
+        let Some(mode) = find_mode_for_content(&current, modes, content_rate) else {
+            return Ok(None);
+        };
+        if mode == current || !policy.allow_switch(&current, &mode, content_rate) {
+            return Ok(None);
+        }
+
+        self.use_mode(mode)?;
+        Ok(Some(mode))
+    }
+
     /// Set the [`DebugFlags`] to use
     ///
     /// Note: This will reset the primary plane swapchain if
@@ -2987,6 +3039,7 @@ where
         output_transform: Transform,
         output_geometry: Rectangle<i32, Physical>,
         try_assign_primary_plane: bool,
+        reserved_overlay_planes: usize,
     ) -> Result<PlaneAssignment, Option<RenderingReason>>
     where
         R: Renderer + Bind<Dmabuf>,
@@ -3053,6 +3106,7 @@ where
             frame_state,
             output_transform,
             output_geometry,
+            reserved_overlay_planes,
         ) {
             Ok(plane) => {
                 trace!(
@@ -3865,6 +3919,7 @@ where
         frame_state: &mut Frame<A, F>,
         output_transform: Transform,
         output_geometry: Rectangle<i32, Physical>,
+        reserved_overlay_planes: usize,
     ) -> Result<PlaneAssignment, Option<RenderingReason>>
     where
         R: Renderer,
@@ -3873,12 +3928,13 @@ where
         let element_id = element.id();
 
         // Check if we have a free plane, otherwise we can exit early
-        if self
+        let free_overlay_planes = self
             .planes
             .overlay
             .iter()
-            .all(|plane| frame_state.is_assigned(plane.handle))
-        {
+            .filter(|plane| !frame_state.is_assigned(plane.handle))
+            .count();
+        if free_overlay_planes == 0 {
             trace!(
                 "skipping overlay planes for element {:?}, no free planes",
                 element_id
@@ -3886,6 +3942,20 @@ where
             return Err(None);
         }
 
+        if free_overlay_planes <= reserved_overlay_planes {
+            trace!(
+                "skipping overlay planes for element {:?}, {} free plane(s) reserved for {} scan-out candidate(s)",
+                element_id,
+                free_overlay_planes,
+                reserved_overlay_planes,
+            );
+            return Err(None);
+        }
+
+        if element.kind() == Kind::ScanoutCandidate {
+            trace!("trying overlay planes for scan-out candidate {:?}", element_id);
+        }
+
         let element_config = self.element_config(
             renderer,
             element,
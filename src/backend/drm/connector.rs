@@ -0,0 +1,581 @@
+//! Helpers for querying and setting [`connector`] properties
+//!
+//! Connectors expose a couple of optional properties describing quirks of the attached
+//! display, that compositors usually want to take into account when setting up an output.
+//!
+//! Additionally this contains helpers for selecting and generating modes, see [`preferred_mode`]
+//! and [`custom_mode`].
+
+use drm::control::{connector, property, Device as ControlDevice, Mode, ModeFlags, ModeTypeFlags};
+
+use super::error::{AccessError, Error};
+use crate::utils::{DevPath, Transform};
+
+/// Orientation of a panel as reported by the `panel orientation` connector property
+///
+/// Some devices (mostly convertibles and handhelds) ship with panels, that are mounted
+/// rotated inside the chassis. The kernel reports these via quirk tables or firmware info.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PanelOrientation {
+    /// The panel is mounted upright
+    Normal,
+    /// The panel is mounted upside down
+    UpsideDown,
+    /// The top of the panel is on the left side of the device
+    LeftSideUp,
+    /// The top of the panel is on the right side of the device
+    RightSideUp,
+}
+
+impl PanelOrientation {
+    /// Returns the output transform necessary to display content upright on this panel
+    ///
+    /// Passing this to [`Output::change_current_state`](crate::output::Output::change_current_state)
+    /// will cause renderers to compensate for the orientation. The [`DrmCompositor`](super::compositor::DrmCompositor)
+    /// will make use of hardware rotation of the planes, if the driver supports it.
+    pub fn transform(&self) -> Transform {
+        match self {
+            PanelOrientation::Normal => Transform::Normal,
+            PanelOrientation::UpsideDown => Transform::_180,
+            PanelOrientation::LeftSideUp => Transform::_90,
+            PanelOrientation::RightSideUp => Transform::_270,
+        }
+    }
+}
+
+impl From<PanelOrientation> for Transform {
+    #[inline]
+    fn from(orientation: PanelOrientation) -> Self {
+        orientation.transform()
+    }
+}
+
+/// Query the `panel orientation` of a connector
+///
+/// Returns `None` if the connector does not expose the property,
+/// which usually means it does not drive an internal panel.
+pub fn panel_orientation(
+    dev: &(impl ControlDevice + DevPath),
+    connector: connector::Handle,
+) -> Result<Option<PanelOrientation>, Error> {
+    let Some(name) = connector_enum_property(dev, connector, "panel orientation")? else {
+        return Ok(None);
+    };
+
+    Ok(match name.as_str() {
+        "Normal" => Some(PanelOrientation::Normal),
+        "Upside Down" => Some(PanelOrientation::UpsideDown),
+        "Left Side Up" => Some(PanelOrientation::LeftSideUp),
+        "Right Side Up" => Some(PanelOrientation::RightSideUp),
+        _ => None,
+    })
+}
+
+/// State of a privacy screen as reported by the `privacy-screen hw-state` connector property
+///
+/// Some laptops contain a built-in privacy screen, which limits the viewing angle of the panel.
+/// Besides being controlled via [`set_privacy_screen`], it might be toggled by hardware hotkeys
+/// or be locked in a state by a hardware switch.
+///
+/// The kernel emits a change uevent for the drm device, whenever the state changes,
+/// which is reported as [`UdevEvent::Changed`](crate::backend::udev::UdevEvent::Changed).
+/// Query the state again on these events to keep track of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PrivacyScreenState {
+    /// The privacy screen is disabled
+    Disabled,
+    /// The privacy screen is enabled
+    Enabled,
+    /// The privacy screen is disabled and cannot be changed by software
+    DisabledLocked,
+    /// The privacy screen is enabled and cannot be changed by software
+    EnabledLocked,
+}
+
+impl PrivacyScreenState {
+    /// Returns `true` if the privacy screen is enabled
+    pub fn is_enabled(&self) -> bool {
+        matches!(
+            self,
+            PrivacyScreenState::Enabled | PrivacyScreenState::EnabledLocked
+        )
+    }
+
+    /// Returns `true` if the state is locked by hardware and cannot be changed
+    pub fn is_locked(&self) -> bool {
+        matches!(
+            self,
+            PrivacyScreenState::DisabledLocked | PrivacyScreenState::EnabledLocked
+        )
+    }
+}
+
+/// Query the current state of the privacy screen of a connector
+///
+/// This reads the `privacy-screen hw-state` property, which reflects the actual state
+/// of the hardware, including changes made by hotkeys.
+///
+/// Returns `None` if the connector has no privacy screen.
+pub fn privacy_screen_state(
+    dev: &(impl ControlDevice + DevPath),
+    connector: connector::Handle,
+) -> Result<Option<PrivacyScreenState>, Error> {
+    let Some(name) = connector_enum_property(dev, connector, "privacy-screen hw-state")? else {
+        return Ok(None);
+    };
+
+    Ok(match name.as_str() {
+        "Disabled" => Some(PrivacyScreenState::Disabled),
+        "Enabled" => Some(PrivacyScreenState::Enabled),
+        "Disabled-locked" => Some(PrivacyScreenState::DisabledLocked),
+        "Enabled-locked" => Some(PrivacyScreenState::EnabledLocked),
+        _ => None,
+    })
+}
+
+/// Enable or disable the privacy screen of a connector
+///
+/// This sets the `privacy-screen sw-state` property. The change might not be applied,
+/// if the state is locked by hardware, see [`privacy_screen_state`].
+///
+/// Returns [`Error::UnknownProperty`] if the connector has no privacy screen.
+pub fn set_privacy_screen(
+    dev: &(impl ControlDevice + DevPath),
+    connector: connector::Handle,
+    enabled: bool,
+) -> Result<(), Error> {
+    set_connector_enum_property(
+        dev,
+        connector,
+        "privacy-screen sw-state",
+        if enabled { "Enabled" } else { "Disabled" },
+    )
+}
+
+/// RGB quantization range used on a connector as set by the `Broadcast RGB` property
+///
+/// Most TVs expect limited range RGB (16-235) over HDMI, while monitors usually expect
+/// full range RGB (0-255). Sending the wrong range results in crushed blacks or washed out colors.
+/// The conversion is done by the display hardware, so no changes to rendering are necessary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BroadcastRgb {
+    /// Let the driver choose the range based on the current mode and sink type
+    Automatic,
+    /// Always use full range RGB
+    Full,
+    /// Always use limited range RGB (16-235)
+    Limited,
+}
+
+impl BroadcastRgb {
+    /// Guess a suitable range from the EDID of a display
+    ///
+    /// Returns [`BroadcastRgb::Full`], if the display advertises a selectable RGB quantization range
+    /// in its CTA-861 video capability data block, as full range is the more accurate choice
+    /// in that case. Otherwise [`BroadcastRgb::Automatic`] is returned, which
+    /// follows the defaults of the CTA-861 specification.
+    pub fn from_edid(edid: &[u8]) -> BroadcastRgb {
+        if rgb_quantization_selectable(edid) {
+            BroadcastRgb::Full
+        } else {
+            BroadcastRgb::Automatic
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            BroadcastRgb::Automatic => "Automatic",
+            BroadcastRgb::Full => "Full",
+            BroadcastRgb::Limited => "Limited 16:235",
+        }
+    }
+}
+
+// checks the QS bit of the video capability data block of any CTA-861 extension
+fn rgb_quantization_selectable(edid: &[u8]) -> bool {
+    const BLOCK_SIZE: usize = 128;
+    const CTA_EXTENSION_TAG: u8 = 0x02;
+    const EXTENDED_TAG: u8 = 0x07;
+    const VIDEO_CAPABILITY_TAG: u8 = 0x00;
+
+    edid.chunks_exact(BLOCK_SIZE).skip(1).any(|block| {
+        if block[0] != CTA_EXTENSION_TAG {
+            return false;
+        }
+
+        // data blocks are stored between byte 4 and the offset of the detailed timing descriptors
+        let end = (block[2] as usize).min(BLOCK_SIZE);
+        let mut offset = 4;
+        while offset < end {
+            let tag = block[offset] >> 5;
+            let len = (block[offset] & 0x1f) as usize;
+            if offset + len >= end {
+                break;
+            }
+            if tag == EXTENDED_TAG && len >= 2 && block[offset + 1] == VIDEO_CAPABILITY_TAG {
+                return block[offset + 2] & 0x40 != 0;
+            }
+            offset += len + 1;
+        }
+        false
+    })
+}
+
+/// Query the `Broadcast RGB` property of a connector
+///
+/// Returns `None` if the connector does not support changing the quantization range.
+pub fn broadcast_rgb(
+    dev: &(impl ControlDevice + DevPath),
+    connector: connector::Handle,
+) -> Result<Option<BroadcastRgb>, Error> {
+    let Some(name) = connector_enum_property(dev, connector, "Broadcast RGB")? else {
+        return Ok(None);
+    };
+
+    Ok(match name.as_str() {
+        "Automatic" => Some(BroadcastRgb::Automatic),
+        "Full" => Some(BroadcastRgb::Full),
+        "Limited 16:235" => Some(BroadcastRgb::Limited),
+        _ => None,
+    })
+}
+
+/// Set the `Broadcast RGB` property of a connector
+///
+/// Changing the range on an enabled connector might cause a modeset.
+///
+/// Returns [`Error::UnknownProperty`] if the connector does not support changing the quantization range.
+pub fn set_broadcast_rgb(
+    dev: &(impl ControlDevice + DevPath),
+    connector: connector::Handle,
+    range: BroadcastRgb,
+) -> Result<(), Error> {
+    set_connector_enum_property(dev, connector, "Broadcast RGB", range.name())
+}
+
+/// Read the raw EDID blob of a connector
+///
+/// Returns `None` if no display is attached or the display does not provide an EDID.
+pub fn edid(
+    dev: &(impl ControlDevice + DevPath),
+    connector: connector::Handle,
+) -> Result<Option<Vec<u8>>, Error> {
+    let Some((info, val)) = connector_property(dev, connector, "EDID")? else {
+        return Ok(None);
+    };
+    let Some(blob) = info.value_type().convert_value(val).as_blob() else {
+        return Ok(None);
+    };
+    if blob == 0 {
+        return Ok(None);
+    }
+
+    dev.get_property_blob(blob).map(Some).map_err(|source| {
+        Error::Access(AccessError {
+            errmsg: "Failed to read EDID blob",
+            dev: dev.dev_path(),
+            source,
+        })
+    })
+}
+
+/// Query if a connector drives a display not meant for desktop usage
+///
+/// This reads the `non-desktop` property, which is set by the kernel for displays like
+/// VR headsets. Compositors should not extend their desktop onto these connectors and
+/// instead offer them for leasing, e.g. via the `wp_drm_lease_v1` protocol.
+///
+/// Returns `false` if the connector does not expose the property.
+pub fn is_non_desktop(
+    dev: &(impl ControlDevice + DevPath),
+    connector: connector::Handle,
+) -> Result<bool, Error> {
+    let Some((info, val)) = connector_property(dev, connector, "non-desktop")? else {
+        return Ok(false);
+    };
+
+    Ok(info.value_type().convert_value(val).as_boolean().unwrap_or(false))
+}
+
+/// Type of content shown on a connector as set by the `content type` property
+///
+/// This is forwarded to the sink via HDMI infoframes, which allows e.g. TVs to
+/// switch to a low latency game mode automatically.
+///
+/// Use [`DrmSurface::set_content_type`](super::DrmSurface::set_content_type) to change it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ContentType {
+    /// No information about the content is provided
+    NoData,
+    /// Generic desktop content
+    Graphics,
+    /// Still images
+    Photo,
+    /// Movies and other video content
+    Cinema,
+    /// Games, which benefit from low latency
+    Game,
+}
+
+impl ContentType {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            ContentType::NoData => "No Data",
+            ContentType::Graphics => "Graphics",
+            ContentType::Photo => "Photo",
+            ContentType::Cinema => "Cinema",
+            ContentType::Game => "Game",
+        }
+    }
+}
+
+#[cfg(feature = "wayland_frontend")]
+impl From<wayland_protocols::wp::content_type::v1::server::wp_content_type_v1::Type> for ContentType {
+    #[inline]
+    fn from(ty: wayland_protocols::wp::content_type::v1::server::wp_content_type_v1::Type) -> Self {
+        use wayland_protocols::wp::content_type::v1::server::wp_content_type_v1::Type;
+        match ty {
+            Type::Photo => ContentType::Photo,
+            Type::Video => ContentType::Cinema,
+            Type::Game => ContentType::Game,
+            _ => ContentType::NoData,
+        }
+    }
+}
+
+/// Query the `content type` property of a connector
+///
+/// Returns `None` if the connector does not support signaling the content type.
+pub fn content_type(
+    dev: &(impl ControlDevice + DevPath),
+    connector: connector::Handle,
+) -> Result<Option<ContentType>, Error> {
+    let Some(name) = connector_enum_property(dev, connector, "content type")? else {
+        return Ok(None);
+    };
+
+    Ok(match name.as_str() {
+        "No Data" => Some(ContentType::NoData),
+        "Graphics" => Some(ContentType::Graphics),
+        "Photo" => Some(ContentType::Photo),
+        "Cinema" => Some(ContentType::Cinema),
+        "Game" => Some(ContentType::Game),
+        _ => None,
+    })
+}
+
+/// Returns the preferred mode of a connector
+///
+/// Falls back to the first mode, if no mode is marked as preferred.
+///
+/// Virtual gpus, like `virtio_gpu` or `qxl`, change the preferred mode once the window of the
+/// virtual machine is resized on the host and notify about it with a udev change event.
+/// Comparing the preferred mode to the current one after such an event allows to follow
+/// the size of the window.
+pub fn preferred_mode(info: &connector::Info) -> Option<Mode> {
+    info.modes()
+        .iter()
+        .find(|mode| mode.mode_type().contains(ModeTypeFlags::PREFERRED))
+        .or_else(|| info.modes().first())
+        .copied()
+}
+
+/// Generate a mode for an arbitrary resolution and refresh rate (in Hz)
+///
+/// The timings follow the VESA CVT reduced blanking formula. Physical displays usually only
+/// accept the modes they advertise, but virtual gpus accept any mode, e.g. to match the size of
+/// the window of a virtual machine on the host.
+pub fn custom_mode(width: u16, height: u16, refresh: u32) -> Mode {
+    const H_BLANK: u16 = 160;
+    const H_SYNC: u16 = 32;
+    const H_FRONT_PORCH: u16 = 48;
+    const V_FRONT_PORCH: u16 = 3;
+    const MIN_V_BACK_PORCH: u16 = 6;
+    const MIN_V_BLANK_US: f64 = 460.0;
+    const CLOCK_STEP_KHZ: u64 = 250;
+
+    let refresh = refresh.max(1);
+
+    // the sync width encodes the aspect ratio
+    let aspect = |num: u32, den: u32| {
+        let w = height as u32 * num / den;
+        w - w % 8 == width as u32
+    };
+    let v_sync = if aspect(4, 3) {
+        4
+    } else if aspect(16, 9) {
+        5
+    } else if aspect(16, 10) {
+        6
+    } else if aspect(5, 4) || aspect(15, 9) {
+        7
+    } else {
+        10
+    };
+
+    let h_period_us = (1_000_000.0 / refresh as f64 - MIN_V_BLANK_US) / height.max(1) as f64;
+    let v_blank = ((MIN_V_BLANK_US / h_period_us) as u16 + 1).max(V_FRONT_PORCH + v_sync + MIN_V_BACK_PORCH);
+
+    let htotal = width.saturating_add(H_BLANK);
+    let vtotal = height.saturating_add(v_blank);
+    let clock = refresh as u64 * htotal as u64 * vtotal as u64 / 1000 / CLOCK_STEP_KHZ * CLOCK_STEP_KHZ;
+
+    let mut info: drm_ffi::drm_mode_modeinfo = unsafe { std::mem::zeroed() };
+    info.clock = clock as u32;
+    info.hdisplay = width;
+    info.hsync_start = width.saturating_add(H_FRONT_PORCH);
+    info.hsync_end = width.saturating_add(H_FRONT_PORCH + H_SYNC);
+    info.htotal = htotal;
+    info.vdisplay = height;
+    info.vsync_start = height.saturating_add(V_FRONT_PORCH);
+    info.vsync_end = height.saturating_add(V_FRONT_PORCH + v_sync);
+    info.vtotal = vtotal;
+    info.vrefresh = refresh;
+    info.flags = (ModeFlags::PHSYNC | ModeFlags::NVSYNC).bits();
+    info.type_ = ModeTypeFlags::USERDEF.bits();
+    let name = format!("{}x{}", width, height);
+    for (dst, src) in info.name.iter_mut().zip(name.bytes().take(31)) {
+        *dst = src as _;
+    }
+    Mode::from(info)
+}
+
+/// Looks up a property of a connector by name, returning its info and current value
+pub(crate) fn connector_property(
+    dev: &(impl ControlDevice + DevPath),
+    connector: connector::Handle,
+    name: &str,
+) -> Result<Option<(property::Info, property::RawValue)>, Error> {
+    let props = dev.get_properties(connector).map_err(|source| {
+        Error::Access(AccessError {
+            errmsg: "Failed to get properties of connector",
+            dev: dev.dev_path(),
+            source,
+        })
+    })?;
+    let (ids, vals) = props.as_props_and_values();
+    for (&id, &val) in ids.iter().zip(vals.iter()) {
+        let info = dev.get_property(id).map_err(|source| {
+            Error::Access(AccessError {
+                errmsg: "Failed to get property info",
+                dev: dev.dev_path(),
+                source,
+            })
+        })?;
+        if info.name().to_str().map(|x| x == name).unwrap_or(false) {
+            return Ok(Some((info, val)));
+        }
+    }
+    Ok(None)
+}
+
+/// Looks up an enum property of a connector by name, returning the name of the current value
+pub(crate) fn connector_enum_property(
+    dev: &(impl ControlDevice + DevPath),
+    connector: connector::Handle,
+    name: &str,
+) -> Result<Option<String>, Error> {
+    let Some((info, val)) = connector_property(dev, connector, name)? else {
+        return Ok(None);
+    };
+
+    Ok(match info.value_type().convert_value(val) {
+        property::Value::Enum(Some(val)) => Some(val.name().to_string_lossy().into_owned()),
+        _ => None,
+    })
+}
+
+/// Looks up the property handle and raw value of an enum property of a connector by the name of the value
+pub(crate) fn connector_enum_value(
+    dev: &(impl ControlDevice + DevPath),
+    connector: connector::Handle,
+    name: &'static str,
+    value: &str,
+) -> Result<(property::Handle, property::RawValue), Error> {
+    let Some((info, _)) = connector_property(dev, connector, name)? else {
+        return Err(Error::UnknownProperty {
+            handle: connector.into(),
+            name,
+        });
+    };
+
+    let raw_value = match info.value_type() {
+        property::ValueType::Enum(values) => {
+            let (raw_values, enum_values) = values.values();
+            raw_values
+                .iter()
+                .zip(enum_values.iter())
+                .find(|(_, val)| val.name().to_str().map(|x| x == value).unwrap_or(false))
+                .map(|(raw, _)| *raw)
+        }
+        _ => None,
+    };
+    let Some(raw_value) = raw_value else {
+        return Err(Error::Access(AccessError {
+            errmsg: "Unsupported property value",
+            dev: dev.dev_path(),
+            source: std::io::ErrorKind::InvalidInput.into(),
+        }));
+    };
+
+    Ok((info.handle(), raw_value))
+}
+
+/// Sets an enum property of a connector by the name of the value
+pub(crate) fn set_connector_enum_property(
+    dev: &(impl ControlDevice + DevPath),
+    connector: connector::Handle,
+    name: &'static str,
+    value: &str,
+) -> Result<(), Error> {
+    let (prop, raw_value) = connector_enum_value(dev, connector, name, value)?;
+    dev.set_property(connector, prop, raw_value).map_err(|source| {
+        Error::Access(AccessError {
+            errmsg: "Failed to set connector property",
+            dev: dev.dev_path(),
+            source,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{custom_mode, BroadcastRgb};
+
+    fn edid_with_cta_blocks(data_blocks: &[u8]) -> Vec<u8> {
+        let mut edid = vec![0u8; 256];
+        edid[128] = 0x02;
+        edid[129] = 0x03;
+        edid[130] = (4 + data_blocks.len()) as u8;
+        edid[132..132 + data_blocks.len()].copy_from_slice(data_blocks);
+        edid
+    }
+
+    #[test]
+    fn broadcast_rgb_without_extension() {
+        assert_eq!(BroadcastRgb::from_edid(&[0u8; 128]), BroadcastRgb::Automatic);
+    }
+
+    #[test]
+    fn broadcast_rgb_selectable() {
+        // audio data block followed by a video capability data block with QS set
+        let edid = edid_with_cta_blocks(&[0x23, 0x09, 0x07, 0x07, 0xe2, 0x00, 0x40]);
+        assert_eq!(BroadcastRgb::from_edid(&edid), BroadcastRgb::Full);
+    }
+
+    #[test]
+    fn broadcast_rgb_not_selectable() {
+        let edid = edid_with_cta_blocks(&[0xe2, 0x00, 0x00]);
+        assert_eq!(BroadcastRgb::from_edid(&edid), BroadcastRgb::Automatic);
+    }
+
+    #[test]
+    fn cvt_reduced_blanking_mode() {
+        let mode = custom_mode(1920, 1080, 60);
+        assert_eq!(mode.size(), (1920, 1080));
+        assert_eq!(mode.hsync(), (1968, 2000, 2080));
+        assert_eq!(mode.vsync(), (1083, 1088, 1111));
+        assert_eq!(mode.clock(), 138500);
+        assert_eq!(mode.name().to_str().unwrap(), "1920x1080");
+    }
+}
@@ -1,17 +1,18 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{
     atomic::{AtomicBool, Ordering},
-    Arc, RwLock,
+    Arc, Mutex, RwLock,
 };
 
 use drm::control::atomic::AtomicModeReq;
 use drm::control::{
     connector, crtc, framebuffer, plane, property, AtomicCommitFlags, Device as ControlDevice,
-    PropertyValueSet, ResourceHandle,
+    PropertyValueSet, RawResourceHandle, ResourceHandle,
 };
 
 use super::DrmDeviceFd;
 use crate::backend::drm::error::AccessError;
+use crate::backend::drm::surface::atomic::AtomicDrmSurface;
 use crate::{backend::drm::error::Error, utils::DevPath};
 
 use tracing::{debug, error, info_span, trace};
@@ -80,12 +81,94 @@ impl PropMapping {
     }
 }
 
+/// A list of properties making up an atomic request
+///
+/// Properties can be marked as cacheable, in which case they will be skipped
+/// when converting the list into a request, if the [`PropertyCache`] indicates
+/// the value is already set.
+#[derive(Debug, Default, Clone)]
+pub struct PropertyList {
+    props: Vec<(RawResourceHandle, property::Handle, property::RawValue, bool)>,
+}
+
+impl PropertyList {
+    /// Add a property, that may be skipped if unchanged
+    pub fn add_property<H: ResourceHandle>(
+        &mut self,
+        handle: H,
+        property: property::Handle,
+        value: property::Value<'_>,
+    ) {
+        self.props.push((handle.into(), property, value.into(), true));
+    }
+
+    /// Add a property, that will always be part of the request
+    pub fn add_uncached_property<H: ResourceHandle>(
+        &mut self,
+        handle: H,
+        property: property::Handle,
+        value: property::Value<'_>,
+    ) {
+        self.props.push((handle.into(), property, value.into(), false));
+    }
+
+    /// Append all properties of another list
+    ///
+    /// Properties set in both lists will end up with the value of `other`.
+    pub fn extend(&mut self, other: PropertyList) {
+        self.props.extend(other.props);
+    }
+
+    /// Build the actual request, skipping all cacheable properties already known to be set
+    pub fn to_request(&self, cache: Option<&PropertyCache>) -> AtomicModeReq {
+        let mut req = AtomicModeReq::new();
+        for (handle, property, value, cacheable) in self.props.iter() {
+            if *cacheable && cache.and_then(|cache| cache.values.get(&(*handle, *property))) == Some(value) {
+                continue;
+            }
+            req.add_raw_property(*handle, *property, *value);
+        }
+        req
+    }
+}
+
+/// Property values of the last successful commits
+///
+/// This is shared between all surfaces of a device, as planes
+/// may be moved between crtcs.
+#[derive(Debug, Default)]
+pub struct PropertyCache {
+    values: HashMap<(RawResourceHandle, property::Handle), property::RawValue>,
+}
+
+impl PropertyCache {
+    /// Record the values of a successfully committed list of properties
+    pub fn update(&mut self, props: &PropertyList) {
+        for (handle, property, value, cacheable) in props.props.iter() {
+            if *cacheable {
+                self.values.insert((*handle, *property), *value);
+            } else {
+                self.values.remove(&(*handle, *property));
+            }
+        }
+    }
+
+    /// Forget all cached values
+    ///
+    /// This needs to be called whenever the state of the device might have been
+    /// modified without us knowing, e.g. after a session switch.
+    pub fn clear(&mut self) {
+        self.values.clear();
+    }
+}
+
 #[derive(Debug)]
 pub struct AtomicDrmDevice {
     pub(crate) fd: DrmDeviceFd,
     pub(crate) active: Arc<AtomicBool>,
     old_state: OldState,
     pub(crate) prop_mapping: Arc<RwLock<PropMapping>>,
+    pub(crate) prop_cache: Arc<Mutex<PropertyCache>>,
     pub(super) span: tracing::Span,
 }
 
@@ -97,6 +180,7 @@ impl AtomicDrmDevice {
             active,
             old_state: (Vec::new(), Vec::new(), Vec::new(), Vec::new()),
             prop_mapping: Default::default(),
+            prop_cache: Default::default(),
             span,
         };
         let _guard = dev.span.enter();
@@ -168,6 +252,86 @@ impl AtomicDrmDevice {
         // on top of the state the previous compositor left the device in.
         // This is because we do commits per surface and not per device, so we do a global
         // commit here, to fix any conflicts.
+        let req = self.reset_request()?;
+
+        // whatever we knew about the state is outdated now
+        self.prop_cache.lock().unwrap().clear();
+        self.fd
+            .atomic_commit(AtomicCommitFlags::ALLOW_MODESET, req.to_request(None))
+            .map_err(|source| {
+                Error::Access(AccessError {
+                    errmsg: "Failed to disable connectors",
+                    dev: self.fd.dev_path(),
+                    source,
+                })
+            })?;
+
+        Ok(())
+    }
+
+    pub(super) fn restore_state<'a>(
+        &self,
+        surfaces: impl IntoIterator<Item = &'a AtomicDrmSurface>,
+    ) -> Result<(), Error> {
+        // Same as `reset_state` we need to do this in one global commit to avoid conflicts
+        // with whatever state the device was left in. So we start by disabling everything
+        // and then apply the last committed state of every surface on top of it.
+        let mut req = self.reset_request()?;
+        for surface in surfaces {
+            if let Some(props) = surface.restore_request()? {
+                req.extend(props);
+            }
+        }
+
+        let mut cache = self.prop_cache.lock().unwrap();
+        cache.clear();
+        self.fd
+            .atomic_commit(AtomicCommitFlags::ALLOW_MODESET, req.to_request(None))
+            .map_err(|source| {
+                Error::Access(AccessError {
+                    errmsg: "Failed to restore state",
+                    dev: self.fd.dev_path(),
+                    source,
+                })
+            })?;
+        cache.update(&req);
+
+        Ok(())
+    }
+
+    pub(super) fn disable_unused(
+        &self,
+        used_crtcs: &HashSet<crtc::Handle>,
+        used_connectors: &HashSet<connector::Handle>,
+    ) -> Result<(), Error> {
+        let req = self.disable_request(used_crtcs, used_connectors)?;
+
+        let mut cache = self.prop_cache.lock().unwrap();
+        self.fd
+            .atomic_commit(AtomicCommitFlags::ALLOW_MODESET, req.to_request(None))
+            .map_err(|source| {
+                Error::Access(AccessError {
+                    errmsg: "Failed to disable unused connectors",
+                    dev: self.fd.dev_path(),
+                    source,
+                })
+            })?;
+        cache.update(&req);
+
+        Ok(())
+    }
+
+    // Creates a request disabling all connectors, crtcs and planes
+    fn reset_request(&self) -> Result<PropertyList, Error> {
+        self.disable_request(&HashSet::new(), &HashSet::new())
+    }
+
+    // Creates a request disabling all connectors, crtcs and planes not in use by the given crtcs and connectors
+    fn disable_request(
+        &self,
+        used_crtcs: &HashSet<crtc::Handle>,
+        used_connectors: &HashSet<connector::Handle>,
+    ) -> Result<PropertyList, Error> {
         let res_handles = self.fd.resource_handles().map_err(|source| {
             Error::Access(AccessError {
                 errmsg: "Error loading drm resources",
@@ -190,8 +354,11 @@ impl AtomicDrmDevice {
         map_props(&self.fd, res_handles.connectors(), &mut prop_mapping.connectors)?;
 
         // Disable all connectors (otherwise we might run into conflicting commits when restarting the rendering loop)
-        let mut req = AtomicModeReq::new();
+        let mut req = PropertyList::default();
         for conn in res_handles.connectors() {
+            if used_connectors.contains(conn) {
+                continue;
+            }
             let prop = prop_mapping
                 .conn_prop_handle(*conn, "CRTC_ID")
                 .expect("Unknown property CRTC_ID");
@@ -199,6 +366,18 @@ impl AtomicDrmDevice {
         }
         // Disable all planes
         for plane in plane_handles {
+            if !used_crtcs.is_empty() {
+                let info = self.fd.get_plane(plane).map_err(|source| {
+                    Error::Access(AccessError {
+                        errmsg: "Error loading plane info",
+                        dev: self.fd.dev_path(),
+                        source,
+                    })
+                })?;
+                if info.crtc().is_some_and(|crtc| used_crtcs.contains(&crtc)) {
+                    continue;
+                }
+            }
             let prop = prop_mapping
                 .plane_prop_handle(plane, "CRTC_ID")
                 .expect("Unknown property CRTC_ID");
@@ -212,6 +391,9 @@ impl AtomicDrmDevice {
         // A crtc without a connector has no mode, we also need to reset that.
         // Otherwise the commit will not be accepted.
         for crtc in res_handles.crtcs() {
+            if used_crtcs.contains(crtc) {
+                continue;
+            }
             let mode_prop = prop_mapping
                 .crtc_prop_handle(*crtc, "MODE_ID")
                 .expect("Unknown property MODE_ID");
@@ -221,17 +403,8 @@ impl AtomicDrmDevice {
             req.add_property(*crtc, active_prop, property::Value::Boolean(false));
             req.add_property(*crtc, mode_prop, property::Value::Unknown(0));
         }
-        self.fd
-            .atomic_commit(AtomicCommitFlags::ALLOW_MODESET, req)
-            .map_err(|source| {
-                Error::Access(AccessError {
-                    errmsg: "Failed to disable connectors",
-                    dev: self.fd.dev_path(),
-                    source,
-                })
-            })?;
 
-        Ok(())
+        Ok(req)
     }
 }
 
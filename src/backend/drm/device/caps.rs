@@ -0,0 +1,73 @@
+use drm::{Device as BasicDevice, DriverCapability};
+
+use crate::utils::{Buffer, Size};
+
+/// Capabilities of a drm device
+///
+/// Queried once on creation of the [`DrmDevice`](super::DrmDevice),
+/// see [`DrmDevice::capabilities`](super::DrmDevice::capabilities).
+/// Capabilities not reported by the kernel are assumed to be unsupported.
+///
+/// Besides the driver capabilities this includes the client capabilities, that the
+/// [`DrmDevice`](super::DrmDevice) successfully enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DrmCapabilities {
+    /// The device supports dumb buffers
+    pub dumb_buffer: bool,
+    /// Preferred depth of dumb buffers
+    pub dumb_preferred_depth: u64,
+    /// Maximum size of a buffer on the cursor plane
+    pub cursor_size: Size<u32, Buffer>,
+    /// Framebuffers can be created with explicit modifiers
+    pub addfb2_modifiers: bool,
+    /// Page flips can be done asynchronously (tearing) via the legacy api
+    pub async_page_flip: bool,
+    /// Vblank timestamps are based on `CLOCK_MONOTONIC`
+    pub monotonic_timestamp: bool,
+    /// The device supports sync objects
+    pub syncobj: bool,
+    /// The device supports timeline sync objects
+    pub syncobj_timeline: bool,
+    /// Buffers can be imported via PRIME
+    pub prime_import: bool,
+    /// Buffers can be exported via PRIME
+    pub prime_export: bool,
+    /// The universal planes client capability was enabled, exposing primary and cursor planes
+    pub universal_planes: bool,
+    /// The atomic client capability was enabled and the atomic modesetting api is used
+    ///
+    /// This is `false` if atomic modesetting was disabled via `SMITHAY_USE_LEGACY`.
+    pub atomic: bool,
+}
+
+impl DrmCapabilities {
+    /// Query the capabilities of an open drm node
+    ///
+    /// This does not enable any client capabilities,
+    /// [`universal_planes`](DrmCapabilities::universal_planes) and [`atomic`](DrmCapabilities::atomic)
+    /// are reported as `false`.
+    pub fn query(fd: &impl BasicDevice) -> DrmCapabilities {
+        let cap = |c: DriverCapability| fd.get_driver_capability(c).ok();
+        let flag = |c: DriverCapability| cap(c).unwrap_or(0) != 0;
+
+        let prime = cap(DriverCapability::Prime).unwrap_or(0);
+        DrmCapabilities {
+            dumb_buffer: flag(DriverCapability::DumbBuffer),
+            dumb_preferred_depth: cap(DriverCapability::DumbPreferredDepth).unwrap_or(0),
+            cursor_size: Size::from((
+                cap(DriverCapability::CursorWidth).unwrap_or(64) as u32,
+                cap(DriverCapability::CursorHeight).unwrap_or(64) as u32,
+            )),
+            addfb2_modifiers: flag(DriverCapability::AddFB2Modifiers),
+            async_page_flip: flag(DriverCapability::ASyncPageFlip),
+            monotonic_timestamp: flag(DriverCapability::MonotonicTimestamp),
+            syncobj: flag(DriverCapability::SyncObj),
+            syncobj_timeline: flag(DriverCapability::TimelineSyncObj),
+            prime_import: prime & 0x1 != 0,
+            prime_export: prime & 0x2 != 0,
+            universal_planes: false,
+            atomic: false,
+        }
+    }
+}
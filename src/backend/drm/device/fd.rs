@@ -77,6 +77,17 @@ impl DrmDeviceFd {
         DrmDeviceFd(Arc::new(dev))
     }
 
+    /// Create a new `DrmDeviceFd` without trying to acquire the master lock.
+    ///
+    /// This is meant for render nodes, which never require drm master,
+    /// or for devices driven by another process. Modesetting will not be possible.
+    pub fn new_unprivileged(fd: DeviceFd) -> DrmDeviceFd {
+        DrmDeviceFd(Arc::new(InternalDrmDeviceFd {
+            fd,
+            privileged: false,
+        }))
+    }
+
     pub(in crate::backend::drm) fn is_privileged(&self) -> bool {
         self.0.privileged
     }
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc,
@@ -8,6 +8,7 @@ use drm::control::{connector, crtc, Device as ControlDevice};
 
 use super::DrmDeviceFd;
 use crate::backend::drm::error::{AccessError, Error};
+use crate::backend::drm::surface::legacy::LegacyDrmSurface;
 use crate::utils::DevPath;
 
 use tracing::{debug, error, info_span, trace};
@@ -121,6 +122,87 @@ impl LegacyDrmDevice {
     }
 }
 
+impl LegacyDrmDevice {
+    pub(super) fn disable_unused(
+        &self,
+        used_crtcs: &HashSet<crtc::Handle>,
+        used_connectors: &HashSet<connector::Handle>,
+    ) -> Result<(), Error> {
+        let res_handles = self.fd.resource_handles().map_err(|source| {
+            Error::Access(AccessError {
+                errmsg: "Failed to query resource handles",
+                dev: self.fd.dev_path(),
+                source,
+            })
+        })?;
+        set_connector_state(
+            &self.fd,
+            res_handles
+                .connectors()
+                .iter()
+                .copied()
+                .filter(|conn| !used_connectors.contains(conn)),
+            false,
+        )?;
+
+        for crtc in res_handles.crtcs() {
+            if used_crtcs.contains(crtc) {
+                continue;
+            }
+
+            self.fd
+                .set_crtc(*crtc, None, (0, 0), &[], None)
+                .map_err(|source| {
+                    Error::Access(AccessError {
+                        errmsg: "Error setting crtc",
+                        dev: self.fd.dev_path(),
+                        source,
+                    })
+                })?;
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn restore_state<'a>(
+        &self,
+        surfaces: impl IntoIterator<Item = &'a LegacyDrmSurface>,
+    ) -> Result<(), Error> {
+        let surfaces = surfaces.into_iter().collect::<Vec<_>>();
+        let res_handles = self.fd.resource_handles().map_err(|source| {
+            Error::Access(AccessError {
+                errmsg: "Failed to query resource handles",
+                dev: self.fd.dev_path(),
+                source,
+            })
+        })?;
+
+        // disable all crtcs not belonging to one of our surfaces, the surfaces
+        // themselves are directly set to their previous state to avoid any flickering.
+        for crtc in res_handles.crtcs() {
+            if surfaces.iter().any(|surface| surface.crtc() == *crtc) {
+                continue;
+            }
+
+            self.fd
+                .set_crtc(*crtc, None, (0, 0), &[], None)
+                .map_err(|source| {
+                    Error::Access(AccessError {
+                        errmsg: "Error setting crtc",
+                        dev: self.fd.dev_path(),
+                        source,
+                    })
+                })?;
+        }
+
+        for surface in surfaces {
+            surface.restore_state()?;
+        }
+
+        Ok(())
+    }
+}
+
 impl Drop for LegacyDrmDevice {
     fn drop(&mut self) {
         if self.active.load(Ordering::SeqCst) {
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::io;
 use std::os::unix::io::{AsFd, BorrowedFd};
 use std::sync::atomic::Ordering;
@@ -7,10 +7,12 @@ use std::time::{Duration, SystemTime};
 
 use calloop::{EventSource, Interest, Poll, PostAction, Readiness, Token, TokenFactory};
 use drm::control::{connector, crtc, plane, Device as ControlDevice, Event, Mode, ResourceHandles};
-use drm::{ClientCapability, Device as BasicDevice, DriverCapability};
+use drm::{ClientCapability, Device as BasicDevice};
 use libc::dev_t;
 
 pub(super) mod atomic;
+mod caps;
+pub use self::caps::DrmCapabilities;
 mod fd;
 pub use self::fd::DrmDeviceFd;
 pub(super) mod legacy;
@@ -113,7 +115,7 @@ pub struct DrmDevice {
     pub(super) dev_id: dev_t,
     pub(crate) internal: Arc<DrmDeviceInternal>,
     has_universal_planes: bool,
-    cursor_size: Size<u32, Buffer>,
+    capabilities: DrmCapabilities,
     resources: ResourceHandles,
     plane_claim_storage: PlaneClaimStorage,
     surfaces: Vec<Weak<DrmSurfaceInternal>>,
@@ -198,17 +200,8 @@ impl DrmDevice {
         let has_universal_planes = fd
             .set_client_capability(ClientCapability::UniversalPlanes, true)
             .is_ok();
-        let has_monotonic_timestamps = fd
-            .get_driver_capability(DriverCapability::MonotonicTimestamp)
-            .unwrap_or(0)
-            == 1;
-        let cursor_width = fd
-            .get_driver_capability(DriverCapability::CursorWidth)
-            .unwrap_or(64);
-        let cursor_height = fd
-            .get_driver_capability(DriverCapability::CursorHeight)
-            .unwrap_or(64);
-        let cursor_size = Size::from((cursor_width as u32, cursor_height as u32));
+        let mut capabilities = DrmCapabilities::query(&fd);
+        capabilities.universal_planes = has_universal_planes;
         let resources = fd.resource_handles().map_err(|source| {
             Error::Access(AccessError {
                 errmsg: "Error loading resource handles",
@@ -218,20 +211,21 @@ impl DrmDevice {
         })?;
 
         let internal = Arc::new(DrmDevice::create_internal(fd, active, disable_connectors)?);
+        capabilities.atomic = matches!(*internal, DrmDeviceInternal::Atomic(_));
 
         Ok((
             DrmDevice {
                 dev_id,
                 internal: internal.clone(),
                 has_universal_planes,
-                cursor_size,
+                capabilities,
                 resources,
                 plane_claim_storage: Default::default(),
                 surfaces: Default::default(),
             },
             DrmDeviceNotifier {
                 internal,
-                has_monotonic_timestamps,
+                has_monotonic_timestamps: capabilities.monotonic_timestamp,
                 token: None,
             },
         ))
@@ -280,6 +274,47 @@ impl DrmDevice {
         planes(self, crtc, self.has_universal_planes)
     }
 
+    /// Returns the crtc and mode currently driving a connector, if it is enabled
+    ///
+    /// Together with creating the device with `disable_connectors` set to `false`, this allows
+    /// to take over the configuration left by the previous drm master, like plymouth or fbcon.
+    /// Creating a surface with the same crtc, mode and connector does not require a modeset,
+    /// so the previous contents stay on screen until the first frame is committed.
+    pub fn current_crtc_mode(
+        &self,
+        connector: connector::Handle,
+    ) -> Result<Option<(crtc::Handle, Mode)>, Error> {
+        let access_error = |errmsg| {
+            move |source| {
+                Error::Access(AccessError {
+                    errmsg,
+                    dev: self.dev_path(),
+                    source,
+                })
+            }
+        };
+
+        let info = self
+            .get_connector(connector, false)
+            .map_err(access_error("Error loading connector info"))?;
+        let Some(encoder) = info.current_encoder() else {
+            return Ok(None);
+        };
+        let Some(crtc) = self
+            .get_encoder(encoder)
+            .map_err(access_error("Error loading encoder info"))?
+            .crtc()
+        else {
+            return Ok(None);
+        };
+        let mode = self
+            .get_crtc(crtc)
+            .map_err(access_error("Error loading crtc info"))?
+            .mode();
+
+        Ok(mode.map(|mode| (crtc, mode)))
+    }
+
     /// Claim a plane so that it won't be used by a different crtc
     ///  
     /// Returns `None` if the plane could not be claimed
@@ -293,7 +328,12 @@ impl DrmDevice {
     /// maximum size of a buffer that can be used on
     /// the cursor plane.
     pub fn cursor_size(&self) -> Size<u32, Buffer> {
-        self.cursor_size
+        self.capabilities.cursor_size
+    }
+
+    /// Returns the capabilities of the underlying device
+    pub fn capabilities(&self) -> &DrmCapabilities {
+        &self.capabilities
     }
 
     /// Creates a new rendering surface.
@@ -364,8 +404,8 @@ impl DrmDevice {
         };
 
         let internal = if self.is_atomic() {
-            let mapping = match &*self.internal {
-                DrmDeviceInternal::Atomic(dev) => dev.prop_mapping.clone(),
+            let (mapping, cache) = match &*self.internal {
+                DrmDeviceInternal::Atomic(dev) => (dev.prop_mapping.clone(), dev.prop_cache.clone()),
                 _ => unreachable!(),
             };
 
@@ -374,7 +414,9 @@ impl DrmDevice {
                 active,
                 crtc,
                 plane.handle,
+                super::plane_zpos_values(self, &planes),
                 mapping,
+                cache,
                 mode,
                 connectors,
             )?)
@@ -437,6 +479,10 @@ impl DrmDevice {
                 error!("Failed to acquire drm master again. Error: {}", err);
             }
         }
+        if let DrmDeviceInternal::Atomic(dev) = &*self.internal {
+            // somebody else might have modified the state while we were paused
+            dev.prop_cache.lock().unwrap().clear();
+        }
         if !self.set_active(true) && disable_connectors {
             self.reset_state()
         } else {
@@ -482,6 +528,91 @@ impl DrmDevice {
         Ok(())
     }
 
+    /// Disable all connectors, crtcs and planes not used by any surface of this device
+    ///
+    /// When creating the device with `disable_connectors` set to `false`, the state left by
+    /// the previous drm master is kept, so surfaces created with the same configuration keep
+    /// showing the previous contents until the first frame is committed, without any black gap.
+    /// Connectors not taken over by a surface however stay enabled and should be disabled
+    /// using this function, once all surfaces have been created.
+    ///
+    /// Connectors and crtcs of surfaces are not touched, even if they are not committed yet.
+    pub fn disable_unused(&mut self) -> Result<(), Error> {
+        if !self.is_active() {
+            return Err(Error::DeviceInactive);
+        }
+
+        self.surfaces.retain(|surface| surface.strong_count() != 0);
+        let mut used_crtcs = HashSet::new();
+        let mut used_connectors = HashSet::new();
+        for surface in self.surfaces.iter().filter_map(|surface| surface.upgrade()) {
+            match &*surface {
+                DrmSurfaceInternal::Atomic(surf) => {
+                    used_crtcs.insert(surf.crtc());
+                    used_connectors.extend(surf.current_connectors());
+                    used_connectors.extend(surf.pending_connectors());
+                }
+                DrmSurfaceInternal::Legacy(surf) => {
+                    used_crtcs.insert(surf.crtc());
+                    used_connectors.extend(surf.current_connectors());
+                    used_connectors.extend(surf.pending_connectors());
+                }
+            }
+        }
+
+        match &*self.internal {
+            DrmDeviceInternal::Atomic(internal) => internal.disable_unused(&used_crtcs, &used_connectors),
+            DrmDeviceInternal::Legacy(internal) => internal.disable_unused(&used_crtcs, &used_connectors),
+        }
+    }
+
+    /// Restores the last committed state of all surfaces of this device
+    ///
+    /// This re-applies the mode, connectors and framebuffers last committed on every surface
+    /// and disables everything else. On atomic devices this includes the cursor and overlay planes
+    /// and happens in a single commit.
+    ///
+    /// Legacy devices only support the primary plane through [`DrmSurface`](super::DrmSurface),
+    /// so only its framebuffer is restored, which already contains the cursor if it was rendered
+    /// by the [`DrmCompositor`](crate::backend::drm::compositor::DrmCompositor).
+    /// A hardware cursor set by the compositor directly through the legacy cursor ioctls
+    /// is not tracked and has to be set again.
+    ///
+    /// It is meant to be called after [`DrmDevice::activate`] (with `disable_connectors` set to `false`)
+    /// on session activation, to avoid showing a black frame and a missing cursor until the
+    /// next frame is rendered.
+    ///
+    /// The framebuffers of the last commit need to be still alive for this to succeed, which
+    /// is the case if the [`DrmCompositor`](crate::backend::drm::compositor::DrmCompositor) is used.
+    /// If this fails you should fall back to [`DrmDevice::reset_state`].
+    pub fn restore_state(&mut self) -> Result<(), Error> {
+        if !self.is_active() {
+            return Err(Error::DeviceInactive);
+        }
+
+        self.surfaces.retain(|surface| surface.strong_count() != 0);
+        let surfaces = self
+            .surfaces
+            .iter()
+            .filter_map(|surface| surface.upgrade())
+            .collect::<Vec<_>>();
+
+        match &*self.internal {
+            DrmDeviceInternal::Atomic(internal) => {
+                internal.restore_state(surfaces.iter().filter_map(|surface| match &**surface {
+                    DrmSurfaceInternal::Atomic(surf) => Some(surf),
+                    _ => None,
+                }))
+            }
+            DrmDeviceInternal::Legacy(internal) => {
+                internal.restore_state(surfaces.iter().filter_map(|surface| match &**surface {
+                    DrmSurfaceInternal::Legacy(surf) => Some(surf),
+                    _ => None,
+                }))
+            }
+        }
+    }
+
     fn set_active(&self, active: bool) -> bool {
         match &*self.internal {
             DrmDeviceInternal::Atomic(internal) => internal.active.swap(active, Ordering::SeqCst),
@@ -4,7 +4,7 @@ use drm_fourcc::{DrmFormat, DrmModifier};
 
 use drm::{
     buffer::{Buffer as DrmBuffer, PlanarBuffer},
-    control::{framebuffer, Device, FbCmd2Flags},
+    control::{framebuffer, Device},
 };
 use tracing::{trace, warn};
 
@@ -21,16 +21,25 @@ use crate::{
     utils::{Buffer as BufferCoords, Size},
 };
 
-use super::{error::AccessError, warn_legacy_fb_export, Framebuffer};
+use super::{add_planar_framebuffer, error::AccessError, warn_legacy_fb_export, AddFbInterface, Framebuffer};
 
 /// A GBM backed framebuffer
 #[derive(Debug)]
 pub struct DumbFramebuffer {
     fb: framebuffer::Handle,
     format: drm_fourcc::DrmFormat,
+    interface: AddFbInterface,
     drm: DrmDeviceFd,
 }
 
+impl DumbFramebuffer {
+    /// Interface that was used to add the framebuffer
+    #[inline]
+    pub fn interface(&self) -> AddFbInterface {
+        self.interface
+    }
+}
+
 impl Drop for DumbFramebuffer {
     fn drop(&mut self) {
         trace!(fb = ?self.fb, "destroying framebuffer");
@@ -149,28 +158,24 @@ pub fn framebuffer_from_dumb_buffer(
 
     let format = Buffer::format(&buffer);
     let modifier = buffer.modifier();
-    let flags = if modifier.is_some() {
-        FbCmd2Flags::MODIFIERS
-    } else {
-        FbCmd2Flags::empty()
-    };
 
     let ret = if use_opaque {
         let opaque_wrapper = OpaqueBufferWrapper(&buffer);
-        drm.add_planar_framebuffer(&opaque_wrapper, flags).map(|fb| {
+        add_planar_framebuffer(drm, &opaque_wrapper).map(|(fb, interface)| {
             (
                 fb,
                 drm_fourcc::DrmFormat {
                     code: opaque_wrapper.format(),
                     modifier: modifier.unwrap_or(DrmModifier::Invalid),
                 },
+                interface,
             )
         })
     } else {
-        drm.add_planar_framebuffer(&buffer, flags).map(|fb| (fb, format))
+        add_planar_framebuffer(drm, &buffer).map(|(fb, interface)| (fb, format, interface))
     };
 
-    let (fb, format) = match ret {
+    let (fb, format, interface) = match ret {
         Ok(fb) => fb,
         Err(source) => {
             warn_legacy_fb_export();
@@ -191,13 +196,14 @@ pub fn framebuffer_from_dumb_buffer(
                     dev: drm.dev_path(),
                     source,
                 })?;
-            (fb, format)
+            (fb, format, AddFbInterface::AddFb)
         }
     };
 
     Ok(DumbFramebuffer {
         fb,
         format,
+        interface,
         drm: drm.clone(),
     })
 }
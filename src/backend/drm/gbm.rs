@@ -6,7 +6,7 @@ use thiserror::Error;
 
 use drm::{
     buffer::PlanarBuffer,
-    control::{framebuffer, Device, FbCmd2Flags},
+    control::{framebuffer, Device},
 };
 use drm_fourcc::DrmModifier;
 use tracing::{trace, warn};
@@ -26,16 +26,25 @@ use crate::backend::{
 };
 use crate::utils::DevPath;
 
-use super::{error::AccessError, warn_legacy_fb_export, Framebuffer};
+use super::{add_planar_framebuffer, error::AccessError, warn_legacy_fb_export, AddFbInterface, Framebuffer};
 
 /// A GBM backed framebuffer
 #[derive(Debug)]
 pub struct GbmFramebuffer {
     fb: framebuffer::Handle,
     format: drm_fourcc::DrmFormat,
+    interface: AddFbInterface,
     drm: DrmDeviceFd,
 }
 
+impl GbmFramebuffer {
+    /// Interface that was used to add the framebuffer
+    #[inline]
+    pub fn interface(&self) -> AddFbInterface {
+        self.interface
+    }
+}
+
 impl Drop for GbmFramebuffer {
     #[inline]
     fn drop(&mut self) {
@@ -100,7 +109,7 @@ pub fn framebuffer_from_wayland_buffer<A: AsFd + 'static>(
             .import_buffer_object_from_wayland::<()>(buffer, gbm::BufferObjectFlags::SCANOUT)
             .map(|bo| GbmBuffer::from_bo(bo, true))
             .map_err(Error::Import)?;
-        let (fb, format) = framebuffer_from_bo_internal(
+        let (fb, format, interface) = framebuffer_from_bo_internal(
             drm,
             BufferObjectInternal {
                 bo: &bo,
@@ -115,6 +124,7 @@ pub fn framebuffer_from_wayland_buffer<A: AsFd + 'static>(
         return Ok(Some(GbmFramebuffer {
             fb,
             format,
+            interface,
             drm: drm.clone(),
         }));
     }
@@ -174,9 +184,10 @@ pub fn framebuffer_from_dmabuf<A: AsFd + 'static>(
         allow_legacy,
     )
     .map_err(Error::Drm)
-    .map(|(fb, format)| GbmFramebuffer {
+    .map(|(fb, format, interface)| GbmFramebuffer {
         fb,
         format,
+        interface,
         drm: drm.clone(),
     })
 }
@@ -198,9 +209,10 @@ pub fn framebuffer_from_bo(
         use_opaque,
         true,
     )
-    .map(|(fb, format)| GbmFramebuffer {
+    .map(|(fb, format, interface)| GbmFramebuffer {
         fb,
         format,
+        interface,
         drm: drm.clone(),
     })
 }
@@ -296,41 +308,38 @@ fn framebuffer_from_bo_internal<D>(
     bo: BufferObjectInternal<'_>,
     use_opaque: bool,
     allow_legacy: bool,
-) -> Result<(framebuffer::Handle, drm_fourcc::DrmFormat), AccessError>
+) -> Result<(framebuffer::Handle, drm_fourcc::DrmFormat, AddFbInterface), AccessError>
 where
     D: drm::control::Device + DevPath,
 {
     let modifier = bo.modifier();
-    let flags = if bo.modifier().is_some() {
-        FbCmd2Flags::MODIFIERS
-    } else {
-        FbCmd2Flags::empty()
-    };
 
     let ret = if use_opaque {
         let opaque_wrapper = OpaqueBufferWrapper(&bo);
-        drm.add_planar_framebuffer(&opaque_wrapper, flags).map(|fb| {
+        add_planar_framebuffer(drm, &opaque_wrapper).map(|(fb, interface)| {
             (
                 fb,
                 drm_fourcc::DrmFormat {
                     code: opaque_wrapper.format(),
                     modifier: modifier.unwrap_or(DrmModifier::Invalid),
                 },
+                interface,
             )
         })
     } else {
-        drm.add_planar_framebuffer(&bo, flags).map(|fb| {
+        add_planar_framebuffer(drm, &bo).map(|(fb, interface)| {
             (
                 fb,
                 drm_fourcc::DrmFormat {
                     code: bo.format(),
                     modifier: modifier.unwrap_or(DrmModifier::Invalid),
                 },
+                interface,
             )
         })
     };
 
-    let (fb, format) = match ret {
+    let (fb, format, interface) = match ret {
         Ok(fb) => fb,
         Err(source) => {
             if !allow_legacy {
@@ -374,8 +383,9 @@ where
                     code: fourcc,
                     modifier: drm_fourcc::DrmModifier::Invalid,
                 },
+                AddFbInterface::AddFb,
             )
         }
     };
-    Ok((fb, format))
+    Ok((fb, format, interface))
 }
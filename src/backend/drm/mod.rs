@@ -69,36 +69,45 @@
 //! Generally [`DrmNode`] is primarily used by clients (such as the output backends) which need
 //! to allocate buffers for use in X11 or Wayland. If you need to do mode setting, you should use
 //! [`DrmDevice`] instead.
+//!
+//! The type of a node is given by [`DrmNode::ty`]. [`render_node`] and [`primary_node`] map between
+//! the nodes of the same device, and [`open_render_node`] opens a render node without requiring drm master,
+//! e.g. to render on a secondary gpu in a multi-gpu setup.
 
 #[cfg(all(feature = "wayland_frontend", feature = "backend_gbm"))]
 pub mod compositor;
+pub mod connector;
 pub(crate) mod device;
 #[cfg(feature = "backend_drm")]
 pub mod dumb;
 mod error;
 #[cfg(feature = "backend_gbm")]
 pub mod gbm;
+mod node;
+pub mod refresh_rate;
 
 mod surface;
 
-use std::sync::Once;
+use std::{collections::HashMap, sync::Once};
 
 use crate::utils::{DevPath, Physical, Size};
 pub use device::{
-    DrmDevice, DrmDeviceFd, DrmDeviceNotifier, DrmEvent, EventMetadata as DrmEventMetadata, PlaneClaim,
-    Time as DrmEventTime,
+    DrmCapabilities, DrmDevice, DrmDeviceFd, DrmDeviceNotifier, DrmEvent, EventMetadata as DrmEventMetadata,
+    PlaneClaim, Time as DrmEventTime,
 };
 pub use drm::node::{CreateDrmNodeError, DrmNode, NodeType};
 use drm_fourcc::{DrmFormat, DrmFourcc, DrmModifier};
 pub use error::AccessError as DrmAccessError;
 pub use error::Error as DrmError;
 use indexmap::IndexSet;
+pub use node::{open_render_node, primary_node, render_node};
 #[cfg(feature = "backend_gbm")]
 pub use surface::gbm::{Error as GbmBufferedSurfaceError, GbmBufferedSurface};
 pub use surface::{DrmSurface, PlaneConfig, PlaneDamageClips, PlaneState};
 
 use drm::{
-    control::{crtc, framebuffer, plane, Device as ControlDevice, PlaneType},
+    buffer::PlanarBuffer,
+    control::{crtc, framebuffer, plane, Device as ControlDevice, FbCmd2Flags, PlaneType},
     DriverCapability,
 };
 use tracing::trace;
@@ -114,6 +123,83 @@ fn warn_legacy_fb_export() {
     });
 }
 
+/// Interface used to add a framebuffer to a drm device
+///
+/// Buffers with explicit modifiers are added with `drmModeAddFB2WithModifiers`, falling back to
+/// `drmModeAddFB2` for linear buffers and to the legacy `drmModeAddFB` as a last resort,
+/// if the kernel or driver do not support the former.
+/// Allocation strategies can use this to e.g. stop allocating with modifiers other than linear.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AddFbInterface {
+    /// `drmModeAddFB2WithModifiers`
+    AddFb2Modifiers,
+    /// `drmModeAddFB2`, no explicit modifier was passed to the kernel
+    AddFb2,
+    /// Legacy `drmModeAddFB`, no explicit modifier was passed to the kernel
+    AddFb,
+}
+
+fn add_planar_framebuffer<D, B>(drm: &D, buffer: &B) -> std::io::Result<(framebuffer::Handle, AddFbInterface)>
+where
+    D: ControlDevice,
+    B: PlanarBuffer,
+{
+    match buffer.modifier() {
+        Some(modifier) => match drm.add_planar_framebuffer(buffer, FbCmd2Flags::MODIFIERS) {
+            Ok(fb) => Ok((fb, AddFbInterface::AddFb2Modifiers)),
+            // The kernel only accepts the linear modifier without the modifiers flag,
+            // which is also the layout it assumes in that case.
+            Err(err) if modifier == DrmModifier::Linear => {
+                trace!(?err, "failed to add framebuffer with modifiers, retrying without");
+                drm.add_planar_framebuffer(&ImplicitModifierBufferWrapper(buffer), FbCmd2Flags::empty())
+                    .map(|fb| (fb, AddFbInterface::AddFb2))
+                    .map_err(|_| err)
+            }
+            Err(err) => Err(err),
+        },
+        None => drm
+            .add_planar_framebuffer(buffer, FbCmd2Flags::empty())
+            .map(|fb| (fb, AddFbInterface::AddFb2)),
+    }
+}
+
+// drm-rs asserts that buffers passed without the modifiers flag have no modifier
+struct ImplicitModifierBufferWrapper<'a, B>(&'a B);
+impl<'a, B> PlanarBuffer for ImplicitModifierBufferWrapper<'a, B>
+where
+    B: PlanarBuffer,
+{
+    #[inline]
+    fn size(&self) -> (u32, u32) {
+        self.0.size()
+    }
+
+    #[inline]
+    fn format(&self) -> DrmFourcc {
+        self.0.format()
+    }
+
+    #[inline]
+    fn modifier(&self) -> Option<DrmModifier> {
+        None
+    }
+
+    #[inline]
+    fn pitches(&self) -> [u32; 4] {
+        self.0.pitches()
+    }
+
+    #[inline]
+    fn handles(&self) -> [Option<drm::buffer::Handle>; 4] {
+        self.0.handles()
+    }
+
+    #[inline]
+    fn offsets(&self) -> [u32; 4] {
+        self.0.offsets()
+    }
+}
+
 /// Common framebuffer operations
 pub trait Framebuffer: AsRef<framebuffer::Handle> {
     /// Retrieve the format of the framebuffer
@@ -139,6 +225,9 @@ pub struct PlaneInfo {
     /// Type of the plane
     pub type_: PlaneType,
     /// z-position of the plane if available
+    ///
+    /// If the driver reports the same z-position for multiple planes, planes with a mutable
+    /// z-position get assigned unique values, which are set on commit.
     pub zpos: Option<i32>,
     /// Formats supported by this plane
     pub formats: FormatSet,
@@ -151,9 +240,7 @@ fn planes(
     crtc: &crtc::Handle,
     has_universal_planes: bool,
 ) -> Result<Planes, DrmError> {
-    let mut primary = Vec::with_capacity(1);
-    let mut cursor = Vec::new();
-    let mut overlay = Vec::new();
+    let mut all = Vec::new();
 
     let planes = dev.plane_handles().map_err(|source| {
         DrmError::Access(AccessError {
@@ -188,24 +275,45 @@ fn planes(
             let plane_info = PlaneInfo {
                 handle: plane,
                 type_,
-                zpos,
+                zpos: zpos.map(|zpos| zpos.value as i32),
                 formats,
                 size_hints,
             };
-            match type_ {
-                PlaneType::Primary => {
-                    primary.push(plane_info);
-                }
-                PlaneType::Cursor => {
-                    cursor.push(plane_info);
-                }
-                PlaneType::Overlay => {
-                    overlay.push(plane_info);
-                }
-            };
+            all.push((plane_info, zpos));
         }
     }
 
+    // Some drivers report the same zpos for several planes, leaving their stacking order undefined.
+    // If possible, assign unique values to planes with a mutable zpos, which are then set on commit.
+    let mut zpos = all
+        .iter()
+        .filter_map(|(info, zpos)| zpos.map(|zpos| (info.type_, u32::from(info.handle), zpos)))
+        .collect::<Vec<_>>();
+    if normalize_zpos(&mut zpos) {
+        for (info, _) in all.iter_mut() {
+            if let Some((_, _, normalized)) = zpos.iter().find(|(_, h, _)| *h == u32::from(info.handle)) {
+                info.zpos = Some(normalized.value as i32);
+            }
+        }
+    }
+
+    let mut primary = Vec::with_capacity(1);
+    let mut cursor = Vec::new();
+    let mut overlay = Vec::new();
+    for (plane_info, _) in all {
+        match plane_info.type_ {
+            PlaneType::Primary => {
+                primary.push(plane_info);
+            }
+            PlaneType::Cursor => {
+                cursor.push(plane_info);
+            }
+            PlaneType::Overlay => {
+                overlay.push(plane_info);
+            }
+        };
+    }
+
     Ok(Planes {
         primary,
         cursor: if has_universal_planes { cursor } else { Vec::new() },
@@ -241,7 +349,97 @@ fn plane_type(dev: &(impl ControlDevice + DevPath), plane: plane::Handle) -> Res
     unreachable!()
 }
 
-fn plane_zpos(dev: &(impl ControlDevice + DevPath), plane: plane::Handle) -> Result<Option<i32>, DrmError> {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ZposProperty {
+    value: i64,
+    mutable: bool,
+    signed: bool,
+    min: i64,
+    max: i64,
+}
+
+impl ZposProperty {
+    fn to_value(self) -> drm::control::property::Value<'static> {
+        if self.signed {
+            drm::control::property::Value::SignedRange(self.value)
+        } else {
+            drm::control::property::Value::UnsignedRange(self.value as u64)
+        }
+    }
+}
+
+// Assign unique zpos values to planes sharing the same zpos, keeping the existing order otherwise.
+// Ties are broken by placing primary planes below overlay planes and those below cursor planes.
+// Immutable values are kept as they are. Returns `false` if nothing had to be or could be changed.
+fn normalize_zpos(planes: &mut [(PlaneType, u32, ZposProperty)]) -> bool {
+    let mut values = planes.iter().map(|(_, _, zpos)| zpos.value).collect::<Vec<_>>();
+    values.sort_unstable();
+    values.dedup();
+    if values.len() == planes.len() {
+        return false;
+    }
+
+    let rank = |type_: PlaneType| match type_ {
+        PlaneType::Primary => 0,
+        PlaneType::Overlay => 1,
+        PlaneType::Cursor => 2,
+    };
+    let mut order = (0..planes.len()).collect::<Vec<_>>();
+    order.sort_by_key(|idx| {
+        let (type_, handle, zpos) = planes[*idx];
+        (zpos.value, rank(type_), handle)
+    });
+
+    let mut normalized = planes.iter().map(|(_, _, zpos)| zpos.value).collect::<Vec<_>>();
+    let mut next = i64::MIN;
+    for idx in order {
+        let zpos = planes[idx].2;
+        let value = if zpos.mutable {
+            next.max(zpos.min)
+        } else {
+            zpos.value
+        };
+        if value < next || value > zpos.max {
+            tracing::debug!("Unable to normalize plane zpos values, keeping the ones reported by the driver");
+            return false;
+        }
+        normalized[idx] = value;
+        next = value + 1;
+    }
+
+    for ((_, _, zpos), value) in planes.iter_mut().zip(normalized) {
+        zpos.value = value;
+    }
+    true
+}
+
+// Returns the zpos values of all planes with a mutable zpos, that have to be set on commit
+pub(crate) fn plane_zpos_values(
+    dev: &(impl ControlDevice + DevPath),
+    planes: &Planes,
+) -> HashMap<plane::Handle, drm::control::property::Value<'static>> {
+    planes
+        .primary
+        .iter()
+        .chain(planes.overlay.iter())
+        .chain(planes.cursor.iter())
+        .filter_map(|info| {
+            let zpos = plane_zpos(dev, info.handle).ok().flatten()?;
+            zpos.mutable.then(|| {
+                let value = ZposProperty {
+                    value: info.zpos.map(i64::from).unwrap_or(zpos.value),
+                    ..zpos
+                };
+                (info.handle, value.to_value())
+            })
+        })
+        .collect()
+}
+
+fn plane_zpos(
+    dev: &(impl ControlDevice + DevPath),
+    plane: plane::Handle,
+) -> Result<Option<ZposProperty>, DrmError> {
     let props = dev.get_properties(plane).map_err(|source| {
         DrmError::Access(AccessError {
             errmsg: "Failed to get properties of plane",
@@ -259,15 +457,26 @@ fn plane_zpos(dev: &(impl ControlDevice + DevPath), plane: plane::Handle) -> Res
             })
         })?;
         if info.name().to_str().map(|x| x == "zpos").unwrap_or(false) {
-            let plane_zpos = match info.value_type().convert_value(val) {
-                drm::control::property::Value::UnsignedRange(u) => Some(u as i32),
-                drm::control::property::Value::SignedRange(i) => Some(i as i32),
+            let (signed, min, max) = match info.value_type() {
+                drm::control::property::ValueType::UnsignedRange(min, max) => (false, min as i64, max as i64),
+                drm::control::property::ValueType::SignedRange(min, max) => (true, min, max),
+                _ => (false, 0, 1),
+            };
+            let value = match info.value_type().convert_value(val) {
+                drm::control::property::Value::UnsignedRange(u) => Some(u as i64),
+                drm::control::property::Value::SignedRange(i) => Some(i),
                 // A range from [0,1] will be interpreted as Boolean in drm-rs
                 // TODO: Once that has been changed we can remove this special handling here
                 drm::control::property::Value::Boolean(b) => Some(b.into()),
                 _ => None,
             };
-            return Ok(plane_zpos);
+            return Ok(value.map(|value| ZposProperty {
+                value,
+                mutable: info.mutable(),
+                signed,
+                min,
+                max,
+            }));
         }
     }
     Ok(None)
@@ -379,6 +588,15 @@ fn plane_formats(dev: &(impl ControlDevice + DevPath), plane: plane::Handle) ->
                     }
                 }
             }
+        } else {
+            // Some drivers (e.g. older versions of vkms) announce modifier support without
+            // exposing IN_FORMATS. The kernel treats these planes as supporting linear buffers only.
+            for format in formats.clone() {
+                formats.insert(DrmFormat {
+                    code: format.code,
+                    modifier: DrmModifier::Linear,
+                });
+            }
         }
     } else if plane_type(dev, plane)? == PlaneType::Cursor {
         // Force a LINEAR layout for the cursor if the driver doesn't support modifiers
@@ -513,3 +731,50 @@ fn plane_size_hints(
     }
     Ok(None)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zpos(value: i64, mutable: bool) -> ZposProperty {
+        ZposProperty {
+            value,
+            mutable,
+            signed: false,
+            min: 0,
+            max: 255,
+        }
+    }
+
+    #[test]
+    fn normalize_duplicate_zpos() {
+        let mut planes = [
+            (PlaneType::Cursor, 4, zpos(0, true)),
+            (PlaneType::Overlay, 3, zpos(0, true)),
+            (PlaneType::Overlay, 2, zpos(0, true)),
+            (PlaneType::Primary, 1, zpos(0, false)),
+        ];
+        assert!(normalize_zpos(&mut planes));
+        assert_eq!(
+            planes.iter().map(|(_, _, zpos)| zpos.value).collect::<Vec<_>>(),
+            [3, 2, 1, 0]
+        );
+
+        // unique values are kept as they are
+        let mut planes = [
+            (PlaneType::Primary, 1, zpos(2, true)),
+            (PlaneType::Overlay, 2, zpos(1, true)),
+        ];
+        assert!(!normalize_zpos(&mut planes));
+        assert_eq!(planes[0].2.value, 2);
+
+        // immutable values below already assigned ones can't be fixed up
+        let mut planes = [
+            (PlaneType::Primary, 1, zpos(0, true)),
+            (PlaneType::Overlay, 2, zpos(0, true)),
+            (PlaneType::Cursor, 3, zpos(1, false)),
+        ];
+        assert!(!normalize_zpos(&mut planes));
+        assert_eq!(planes[1].2.value, 0);
+    }
+}
@@ -0,0 +1,44 @@
+use std::{io, os::unix::io::OwnedFd};
+
+use rustix::fs::{Mode, OFlags};
+
+use super::{DrmNode, NodeType};
+
+/// Returns the render node belonging to the same device as `node`
+///
+/// Shorthand for [`DrmNode::node_with_type`], returning `None` if the device does not expose
+/// a render node (e.g. display-only devices) or it could not be determined.
+pub fn render_node(node: &DrmNode) -> Option<DrmNode> {
+    node.node_with_type(NodeType::Render)?.ok()
+}
+
+/// Returns the primary node belonging to the same device as `node`
+///
+/// Shorthand for [`DrmNode::node_with_type`], returning `None` if the primary node
+/// could not be determined.
+pub fn primary_node(node: &DrmNode) -> Option<DrmNode> {
+    node.node_with_type(NodeType::Primary)?.ok()
+}
+
+/// Open the render node belonging to the same device as `node`
+///
+/// Render nodes can be opened by unprivileged processes and never require drm master,
+/// making them suitable for rendering and buffer allocation on a device, that might be
+/// driven by another process or not used for scanout at all.
+///
+/// The returned file descriptor can be wrapped via [`DrmDeviceFd::new_unprivileged`](super::DrmDeviceFd::new_unprivileged).
+///
+/// Returns an error of kind [`io::ErrorKind::NotFound`], if the device has no render node.
+pub fn open_render_node(node: &DrmNode) -> io::Result<(DrmNode, OwnedFd)> {
+    let render_node = render_node(node).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No render node available for {:?}", node.dev_path()),
+        )
+    })?;
+    let path = render_node
+        .dev_path()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Render node has no device path"))?;
+    let fd = rustix::fs::open(path, OFlags::RDWR | OFlags::CLOEXEC, Mode::empty())?;
+    Ok((render_node, fd))
+}
@@ -0,0 +1,138 @@
+//! Helpers to match the refresh rate of an output to its content
+//!
+//! Video content is usually produced at rates like 23.976, 24 or 25 frames per second,
+//! which can not be presented evenly on a 60Hz output and results in visible judder.
+//! Many displays offer additional modes with the same resolution, but a refresh rate
+//! that is a multiple of common content rates.
+//!
+//! [`find_mode_for_content`] selects such a mode, preferring modes that only differ in their
+//! vertical blanking, which a lot of drivers are able to switch to without blanking the display.
+//! Whether a switch is actually desired is up to the compositor, which can express this via a
+//! [`RefreshRatePolicy`] passed to [`DrmCompositor::use_content_refresh_rate`](super::compositor::DrmCompositor::use_content_refresh_rate).
+//!
+//! All refresh rates are given in millihertz, e.g. `23976` for 23.976 frames per second.
+
+use drm::control::{Mode, ModeFlags};
+
+use crate::output::Mode as OutputMode;
+
+/// Policy deciding if the refresh rate of an output may be changed to match its content
+pub trait RefreshRatePolicy {
+    /// Returns `true`, if switching from `current` to `candidate` is allowed
+    /// to display content at `content_rate` (in millihertz).
+    fn allow_switch(&mut self, current: &Mode, candidate: &Mode, content_rate: u32) -> bool;
+}
+
+impl<F> RefreshRatePolicy for F
+where
+    F: FnMut(&Mode, &Mode, u32) -> bool,
+{
+    #[inline]
+    fn allow_switch(&mut self, current: &Mode, candidate: &Mode, content_rate: u32) -> bool {
+        self(current, candidate, content_rate)
+    }
+}
+
+/// Returns the refresh rate of a mode in millihertz
+pub fn refresh_rate(mode: &Mode) -> u32 {
+    OutputMode::from(*mode).refresh as u32
+}
+
+/// Returns `true` if two modes only differ in their vertical blanking
+///
+/// Drivers supporting variable refresh rates are usually able to switch between these
+/// modes without a full modeset, so the display does not go blank.
+pub fn is_seamless(a: &Mode, b: &Mode) -> bool {
+    a.size() == b.size()
+        && a.clock() == b.clock()
+        && a.hsync() == b.hsync()
+        && a.flags() == b.flags()
+        && a.vscan() == b.vscan()
+}
+
+/// Returns `true` if content at `content_rate` can be shown evenly on `mode`
+///
+/// This is the case if the refresh rate of the mode is a multiple of the content rate.
+pub fn matches_content_rate(mode: &Mode, content_rate: u32) -> bool {
+    if content_rate == 0 || mode.flags().contains(ModeFlags::INTERLACE) {
+        return false;
+    }
+
+    let refresh = refresh_rate(mode) as u64;
+    let content_rate = content_rate as u64;
+    let multiple = (refresh + content_rate / 2) / content_rate;
+    if multiple == 0 {
+        return false;
+    }
+
+    // allow for rounding errors of the mode timings, but still tell 23.976 and 24 apart
+    let tolerance = (refresh / 2000).max(1);
+    refresh.abs_diff(multiple * content_rate) <= tolerance
+}
+
+/// Find the best mode to display content at `content_rate`
+///
+/// Only modes with the same resolution as `current` are considered.
+/// If `current` already matches the content rate it is returned as is, otherwise
+/// modes allowing a seamless switch are preferred over higher refresh rates.
+///
+/// Returns `None` if no suitable mode is found.
+pub fn find_mode_for_content(current: &Mode, modes: &[Mode], content_rate: u32) -> Option<Mode> {
+    if matches_content_rate(current, content_rate) {
+        return Some(*current);
+    }
+
+    modes
+        .iter()
+        .filter(|mode| mode.size() == current.size() && matches_content_rate(mode, content_rate))
+        .max_by_key(|mode| (is_seamless(current, mode), refresh_rate(mode)))
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mode(clock: u32, htotal: u16, vtotal: u16) -> Mode {
+        let mut info: drm_ffi::drm_mode_modeinfo = unsafe { std::mem::zeroed() };
+        info.clock = clock;
+        info.hdisplay = 1920;
+        info.hsync_start = 2008;
+        info.hsync_end = 2052;
+        info.htotal = htotal;
+        info.vdisplay = 1080;
+        info.vsync_start = 1084;
+        info.vsync_end = 1089;
+        info.vtotal = vtotal;
+        Mode::from(info)
+    }
+
+    #[test]
+    fn content_rate_matching() {
+        let m60 = mode(148500, 2200, 1125);
+        let m24 = mode(74250, 2750, 1125);
+        let m23_976 = mode(74176, 2750, 1125);
+
+        assert_eq!(refresh_rate(&m60), 60000);
+        assert!(matches_content_rate(&m60, 30000));
+        assert!(!matches_content_rate(&m60, 23976));
+        assert!(matches_content_rate(&m24, 24000));
+        assert!(!matches_content_rate(&m24, 23976));
+        assert!(matches_content_rate(&m23_976, 23976));
+        assert!(!matches_content_rate(&m23_976, 24000));
+    }
+
+    #[test]
+    fn prefer_seamless_modes() {
+        let m60 = mode(148500, 2200, 1125);
+        // 120Hz with a different pixel clock requires a full modeset
+        let m120 = mode(297000, 2200, 1125);
+        // 24Hz by only extending the vertical blanking
+        let m24_vrr = mode(148500, 2200, 2813);
+
+        let modes = [m60, m120, m24_vrr];
+        assert_eq!(find_mode_for_content(&m60, &modes, 24000), Some(m24_vrr));
+        assert_eq!(find_mode_for_content(&m60, &modes, 30000), Some(m60));
+        assert_eq!(find_mode_for_content(&m60, &modes, 25000), None);
+    }
+}
@@ -4,7 +4,7 @@ use drm::control::{
     connector, crtc, dumbbuffer::DumbBuffer, framebuffer, plane, property, AtomicCommitFlags, Mode, PlaneType,
 };
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::os::unix::io::AsRawFd;
 use std::sync::Mutex;
 use std::sync::{
@@ -13,12 +13,12 @@ use std::sync::{
 };
 
 use crate::backend::drm::error::AccessError;
-use crate::utils::{Coordinate, Point, Rectangle, Transform};
+use crate::utils::{Buffer, Coordinate, Physical, Point, Rectangle, Transform};
 use crate::{
     backend::{
         allocator::format::{get_bpp, get_depth},
         drm::{
-            device::atomic::{map_props, PropMapping},
+            device::atomic::{map_props, PropMapping, PropertyCache, PropertyList},
             device::DrmDeviceInternal,
             error::Error,
             plane_type, DrmDeviceFd,
@@ -37,12 +37,16 @@ pub struct State {
     pub mode: Mode,
     pub blob: property::Value<'static>,
     pub connectors: HashSet<connector::Handle>,
+    pub connector_props: HashMap<(connector::Handle, property::Handle), property::RawValue>,
 }
 
 impl PartialEq for State {
     #[inline]
     fn eq(&self, other: &Self) -> bool {
-        self.active == other.active && self.mode == other.mode && self.connectors == other.connectors
+        self.active == other.active
+            && self.mode == other.mode
+            && self.connectors == other.connectors
+            && self.connector_props == other.connector_props
     }
 }
 
@@ -132,6 +136,8 @@ impl State {
             mode: current_mode,
             blob: current_blob,
             connectors: current_connectors,
+            // we only track properties we changed ourselves
+            connector_props: HashMap::new(),
         })
     }
 
@@ -143,14 +149,40 @@ impl State {
     }
 }
 
+// Configuration of a plane as last committed, used to restore it after a session switch.
+#[derive(Debug, Clone, Copy)]
+struct CommittedPlane {
+    src: Rectangle<f64, Buffer>,
+    dst: Rectangle<i32, Physical>,
+    transform: Transform,
+    alpha: f32,
+    fb: framebuffer::Handle,
+}
+
+impl<'a> From<&PlaneConfig<'a>> for CommittedPlane {
+    #[inline]
+    fn from(config: &PlaneConfig<'a>) -> Self {
+        CommittedPlane {
+            src: config.src,
+            dst: config.dst,
+            transform: config.transform,
+            alpha: config.alpha,
+            fb: config.fb,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct AtomicDrmSurface {
     pub(in crate::backend::drm) fd: Arc<DrmDeviceInternal>,
     pub(super) active: Arc<AtomicBool>,
     crtc: crtc::Handle,
     plane: plane::Handle,
-    used_planes: Mutex<HashSet<plane::Handle>>,
+    used_planes: Mutex<HashMap<plane::Handle, CommittedPlane>>,
+    // normalized zpos of planes with a mutable zpos
+    zpos: HashMap<plane::Handle, property::Value<'static>>,
     prop_mapping: Arc<RwLock<PropMapping>>,
+    prop_cache: Arc<Mutex<PropertyCache>>,
     state: RwLock<State>,
     pending: RwLock<State>,
     pub(super) span: tracing::Span,
@@ -163,7 +195,9 @@ impl AtomicDrmSurface {
         active: Arc<AtomicBool>,
         crtc: crtc::Handle,
         plane: plane::Handle,
+        zpos: HashMap<plane::Handle, property::Value<'static>>,
         prop_mapping: Arc<RwLock<PropMapping>>,
+        prop_cache: Arc<Mutex<PropertyCache>>,
         mode: Mode,
         connectors: &[connector::Handle],
     ) -> Result<Self, Error> {
@@ -187,6 +221,7 @@ impl AtomicDrmSurface {
             mode,
             blob,
             connectors: connectors.iter().copied().collect(),
+            connector_props: HashMap::new(),
         };
 
         drop(_guard);
@@ -195,8 +230,10 @@ impl AtomicDrmSurface {
             active,
             crtc,
             plane,
-            used_planes: Mutex::new(HashSet::new()),
+            used_planes: Mutex::new(HashMap::new()),
+            zpos,
             prop_mapping,
+            prop_cache,
             state: RwLock::new(state),
             pending: RwLock::new(pending),
             span,
@@ -342,7 +379,7 @@ impl AtomicDrmSurface {
             self.fd
                 .atomic_commit(
                     AtomicCommitFlags::ALLOW_MODESET | AtomicCommitFlags::TEST_ONLY,
-                    req,
+                    self.to_request(&req),
                 )
                 .map_err(|_| Error::TestFailed(self.crtc))?;
 
@@ -355,6 +392,17 @@ impl AtomicDrmSurface {
         }
     }
 
+    // Connectors behind a DisplayPort MST hub are destroyed by the kernel once the hub
+    // (or the branch leading to it) vanishes. Any request referencing them will fail,
+    // so we need to be able to tell them apart from connectors that are just disconnected.
+    fn connector_exists(&self, conn: connector::Handle) -> bool {
+        self.fd
+            .resource_handles()
+            .map(|res_handles| res_handles.connectors().contains(&conn))
+            // if we can not tell, assume it is still there and let the driver decide
+            .unwrap_or(true)
+    }
+
     #[instrument(parent = &self.span, skip(self))]
     pub fn remove_connector(&self, conn: connector::Handle) -> Result<(), Error> {
         if !self.active.load(Ordering::SeqCst) {
@@ -368,6 +416,18 @@ impl AtomicDrmSurface {
             return Err(Error::SurfaceWithoutConnectors(self.crtc));
         }
 
+        if !self.connector_exists(conn) {
+            // The kernel already unbound the connector from our crtc when destroying it,
+            // testing a request removing it would always fail.
+            debug!(connector = ?conn, "Removing vanished connector");
+            // keep the lock order of `commit`
+            std::mem::drop(pending);
+            let mut current = self.state.write().unwrap();
+            current.connectors.remove(&conn);
+            self.pending.write().unwrap().connectors.remove(&conn);
+            return Ok(());
+        }
+
         // check if new config is supported (should be)
         let test_buffer = self.create_test_buffer(pending.mode.size(), self.plane)?;
 
@@ -394,7 +454,7 @@ impl AtomicDrmSurface {
         self.fd
             .atomic_commit(
                 AtomicCommitFlags::ALLOW_MODESET | AtomicCommitFlags::TEST_ONLY,
-                req,
+                self.to_request(&req),
             )
             .map_err(|_| Error::TestFailed(self.crtc))?;
 
@@ -449,7 +509,7 @@ impl AtomicDrmSurface {
         self.fd
             .atomic_commit(
                 AtomicCommitFlags::ALLOW_MODESET | AtomicCommitFlags::TEST_ONLY,
-                req,
+                self.to_request(&req),
             )
             .map_err(|_| Error::TestFailed(self.crtc))?;
 
@@ -501,7 +561,7 @@ impl AtomicDrmSurface {
             .fd
             .atomic_commit(
                 AtomicCommitFlags::ALLOW_MODESET | AtomicCommitFlags::TEST_ONLY,
-                req,
+                self.to_request(&req),
             )
             .map_err(|_| Error::TestFailed(self.crtc))
         {
@@ -516,6 +576,24 @@ impl AtomicDrmSurface {
         Ok(())
     }
 
+    pub fn set_connector_property(
+        &self,
+        conn: connector::Handle,
+        prop: property::Handle,
+        value: property::RawValue,
+    ) -> Result<(), Error> {
+        if !self.active.load(Ordering::SeqCst) {
+            return Err(Error::DeviceInactive);
+        }
+
+        self.pending
+            .write()
+            .unwrap()
+            .connector_props
+            .insert((conn, prop), value);
+        Ok(())
+    }
+
     pub fn commit_pending(&self) -> bool {
         *self.pending.read().unwrap() != *self.state.read().unwrap()
     }
@@ -541,20 +619,23 @@ impl AtomicDrmSurface {
         let mut removed = current_conns.difference(&pending_conns);
         let mut added = pending_conns.difference(&current_conns);
 
-        let req = self.build_request(&mut added, &mut removed, &*planes, Some(pending.blob))?;
+        let mut req = self.build_request(&mut added, &mut removed, &*planes, Some(pending.blob))?;
+        append_connector_props(&mut req, &pending);
 
         let flags = if allow_modeset {
             AtomicCommitFlags::ALLOW_MODESET | AtomicCommitFlags::TEST_ONLY
         } else {
             AtomicCommitFlags::TEST_ONLY
         };
-        self.fd.atomic_commit(flags, req).map_err(|source| {
-            Error::Access(AccessError {
-                errmsg: "Error testing state",
-                dev: self.fd.dev_path(),
-                source,
+        self.fd
+            .atomic_commit(flags, self.to_request(&req))
+            .map_err(|source| {
+                Error::Access(AccessError {
+                    errmsg: "Error testing state",
+                    dev: self.fd.dev_path(),
+                    source,
+                })
             })
-        })
     }
 
     #[instrument(level = "trace", parent = &self.span, skip(self, planes))]
@@ -575,6 +656,18 @@ impl AtomicDrmSurface {
 
         debug!(current = ?*current, pending = ?*pending, ?planes, "Preparing Commit",);
 
+        // connectors that vanished in the meantime (e.g. unplugged MST docks) were already
+        // detached by the kernel and can not be referenced anymore.
+        current.connectors.retain(|conn| {
+            pending.connectors.contains(conn) || {
+                let exists = self.connector_exists(*conn);
+                if !exists {
+                    info!("Dropping vanished connector: {:?}", conn);
+                }
+                exists
+            }
+        });
+
         // we need the differences to know, which connectors need to change properties
         let current_conns = current.connectors.clone();
         let pending_conns = pending.connectors.clone();
@@ -604,8 +697,10 @@ impl AtomicDrmSurface {
         trace!("Testing screen config");
 
         // test the new config and return the request if it would be accepted by the driver.
+        let mut props = self.build_request(&mut added, &mut removed, &*planes, Some(pending.blob))?;
+        append_connector_props(&mut props, &pending);
         let req = {
-            let req = self.build_request(&mut added, &mut removed, &*planes, Some(pending.blob))?;
+            let req = self.to_request(&props);
 
             if let Err(err) = self.fd.atomic_commit(
                 AtomicCommitFlags::ALLOW_MODESET | AtomicCommitFlags::TEST_ONLY,
@@ -656,9 +751,10 @@ impl AtomicDrmSurface {
 
         if result.is_ok() {
             *current = pending.clone();
+            self.prop_cache.lock().unwrap().update(&props);
             for plane in planes.iter() {
-                if plane.config.is_some() {
-                    used_planes.insert(plane.handle);
+                if let Some(config) = plane.config.as_ref() {
+                    used_planes.insert(plane.handle, config.into());
                 } else {
                     used_planes.remove(&plane.handle);
                 }
@@ -683,7 +779,8 @@ impl AtomicDrmSurface {
         let planes = planes.into_iter().collect::<Vec<_>>();
 
         // page flips work just like commits with fewer parameters..
-        let req = self.build_request(&mut [].iter(), &mut [].iter(), &*planes, None)?;
+        let props = self.build_request(&mut [].iter(), &mut [].iter(), &*planes, None)?;
+        let req = self.to_request(&props);
 
         // .. and without `AtomicCommitFlags::AllowModeset`.
         // If we would set anything here, that would require a modeset, this would fail,
@@ -708,9 +805,10 @@ impl AtomicDrmSurface {
             });
 
         if res.is_ok() {
+            self.prop_cache.lock().unwrap().update(&props);
             for plane in planes.iter() {
-                if plane.config.is_some() {
-                    used_planes.insert(plane.handle);
+                if let Some(config) = plane.config.as_ref() {
+                    used_planes.insert(plane.handle, config.into());
                 } else {
                     used_planes.remove(&plane.handle);
                 }
@@ -729,11 +827,13 @@ impl AtomicDrmSurface {
         removed_connectors: &mut dyn Iterator<Item = &connector::Handle>,
         planes: impl IntoIterator<Item = &'a PlaneState<'a>>,
         blob: Option<property::Value<'static>>,
-    ) -> Result<AtomicModeReq, Error> {
+    ) -> Result<PropertyList, Error> {
         let prop_mapping = self.prop_mapping.read().unwrap();
 
         // okay, here we build the actual requests used by the surface.
-        let mut req = AtomicModeReq::new();
+        // properties that did not change since the last commit will be skipped
+        // when turning this into a request (see `to_request`).
+        let mut req = PropertyList::default();
 
         // requests consist out of a set of properties and their new values
         // for different drm objects (crtc, plane, connector, ...).
@@ -769,7 +869,9 @@ impl AtomicDrmSurface {
         }
 
         // we also need to set this crtc active
-        req.add_property(
+        // (this is never skipped, otherwise the crtc might not be part of the
+        // request at all and we would not receive a page-flip event)
+        req.add_uncached_property(
             self.crtc,
             prop_mapping.crtc_prop_handle(self.crtc, "ACTIVE")?,
             property::Value::Boolean(true),
@@ -780,14 +882,17 @@ impl AtomicDrmSurface {
 
             if let Some(config) = plane_state.config.as_ref() {
                 // connect the plane to the CRTC
-                req.add_property(
+                //
+                // crtc and fb are always set, removing a framebuffer implicitly
+                // disables the plane, so we can not rely on our cached values for those.
+                req.add_uncached_property(
                     *handle,
                     prop_mapping.plane_prop_handle(*handle, "CRTC_ID")?,
                     property::Value::CRTC(Some(self.crtc)),
                 );
 
                 // Set the fb for the plane
-                req.add_property(
+                req.add_uncached_property(
                     *handle,
                     prop_mapping.plane_prop_handle(*handle, "FB_ID")?,
                     property::Value::Framebuffer(Some(config.fb)),
@@ -867,18 +972,23 @@ impl AtomicDrmSurface {
                         name: "alpha",
                     });
                 }
+                if let Some(zpos) = self.zpos.get(handle) {
+                    if let Ok(prop) = prop_mapping.plane_prop_handle(*handle, "zpos") {
+                        req.add_property(*handle, prop, *zpos);
+                    }
+                }
                 if let Ok(prop) = prop_mapping.plane_prop_handle(*handle, "FB_DAMAGE_CLIPS") {
                     if let Some(damage) = config.damage_clips.as_ref() {
-                        req.add_property(*handle, prop, *damage);
+                        req.add_uncached_property(*handle, prop, *damage);
                     } else {
-                        req.add_property(*handle, prop, property::Value::Blob(0));
+                        req.add_uncached_property(*handle, prop, property::Value::Blob(0));
                     }
                 }
                 if let Ok(prop) = prop_mapping.plane_prop_handle(*handle, "IN_FENCE_FD") {
                     if let Some(fence) = config.fence.as_ref().map(|f| f.as_raw_fd()) {
-                        req.add_property(*handle, prop, property::Value::SignedRange(fence as i64));
+                        req.add_uncached_property(*handle, prop, property::Value::SignedRange(fence as i64));
                     } else {
-                        req.add_property(*handle, prop, property::Value::SignedRange(-1));
+                        req.add_uncached_property(*handle, prop, property::Value::SignedRange(-1));
                     }
                 } else if config.fence.is_some() {
                     return Err(Error::UnknownProperty {
@@ -894,6 +1004,46 @@ impl AtomicDrmSurface {
         Ok(req)
     }
 
+    // Creates a request re-applying the last committed state of this surface,
+    // including the framebuffers of all planes.
+    //
+    // Returns `None` if the surface is currently disabled.
+    pub(crate) fn restore_request(&self) -> Result<Option<PropertyList>, Error> {
+        let current = self.state.read().unwrap();
+        if !current.active || current.connectors.is_empty() {
+            return Ok(None);
+        }
+
+        let planes = self
+            .used_planes
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(handle, plane)| PlaneState {
+                handle: *handle,
+                config: Some(PlaneConfig {
+                    src: plane.src,
+                    dst: plane.dst,
+                    transform: plane.transform,
+                    alpha: plane.alpha,
+                    damage_clips: None,
+                    fb: plane.fb,
+                    fence: None,
+                }),
+            })
+            .collect::<Vec<_>>();
+
+        debug!(current = ?*current, ?planes, "Restoring state");
+        let mut req = self.build_request(
+            &mut current.connectors.iter(),
+            &mut [].iter(),
+            &*planes,
+            Some(current.blob),
+        )?;
+        append_connector_props(&mut req, &current);
+        Ok(Some(req))
+    }
+
     // this helper function disconnects the plane.
     // this is mostly used to remove the contents quickly, e.g. on tty switch,
     // as other compositors might not make use of other planes,
@@ -903,12 +1053,12 @@ impl AtomicDrmSurface {
             return Err(Error::DeviceInactive);
         }
 
-        let mut req = AtomicModeReq::new();
+        let mut req = PropertyList::default();
         self.append_reset_plane_state(&mut req, plane)?;
 
         let result = self
             .fd
-            .atomic_commit(AtomicCommitFlags::empty(), req)
+            .atomic_commit(AtomicCommitFlags::empty(), self.to_request(&req))
             .map_err(|source| {
                 Error::Access(AccessError {
                     errmsg: "Failed to commit on clear_plane",
@@ -918,6 +1068,7 @@ impl AtomicDrmSurface {
             });
 
         if result.is_ok() {
+            self.prop_cache.lock().unwrap().update(&req);
             self.used_planes.lock().unwrap().remove(&plane);
         }
 
@@ -931,9 +1082,9 @@ impl AtomicDrmSurface {
         }
 
         let _guard = self.span.enter();
-        let mut req = AtomicModeReq::new();
+        let mut req = PropertyList::default();
         // reset all planes we used
-        for plane in self.used_planes.lock().unwrap().iter() {
+        for plane in self.used_planes.lock().unwrap().keys() {
             self.append_reset_plane_state(&mut req, *plane)?;
         }
 
@@ -968,7 +1119,7 @@ impl AtomicDrmSurface {
 
         let res = self
             .fd
-            .atomic_commit(AtomicCommitFlags::ALLOW_MODESET, req)
+            .atomic_commit(AtomicCommitFlags::ALLOW_MODESET, self.to_request(&req))
             .map_err(|source| {
                 Error::Access(AccessError {
                     errmsg: "Failed to commit on clear_state",
@@ -978,6 +1129,7 @@ impl AtomicDrmSurface {
             });
 
         if res.is_ok() {
+            self.prop_cache.lock().unwrap().update(&req);
             self.used_planes.lock().unwrap().clear();
             self.state.write().unwrap().clear();
         }
@@ -985,7 +1137,7 @@ impl AtomicDrmSurface {
         res
     }
 
-    fn append_reset_plane_state(&self, req: &mut AtomicModeReq, plane: plane::Handle) -> Result<(), Error> {
+    fn append_reset_plane_state(&self, req: &mut PropertyList, plane: plane::Handle) -> Result<(), Error> {
         let prop_mapping = self.prop_mapping.read().unwrap();
 
         req.add_property(
@@ -1070,6 +1222,8 @@ impl AtomicDrmSurface {
         &self,
         fd: Option<&B>,
     ) -> Result<(), Error> {
+        // the state might have been modified elsewhere, so we can not trust our cache anymore
+        self.prop_cache.lock().unwrap().clear();
         *self.state.write().unwrap() = if let Some(fd) = fd {
             State::current_state(fd, self.crtc, &mut self.prop_mapping.write().unwrap())?
         } else {
@@ -1082,6 +1236,15 @@ impl AtomicDrmSurface {
         self.fd.device_fd()
     }
 
+    pub(crate) fn crtc(&self) -> crtc::Handle {
+        self.crtc
+    }
+
+    // turns a list of properties into a request, skipping everything that is unchanged
+    fn to_request(&self, props: &PropertyList) -> AtomicModeReq {
+        props.to_request(Some(&self.prop_cache.lock().unwrap()))
+    }
+
     pub fn clear(&self) -> Result<(), Error> {
         self.clear_state()
     }
@@ -1123,6 +1286,15 @@ impl Drop for AtomicDrmSurface {
     }
 }
 
+// connector properties set by the user only apply to connectors driven by this surface
+fn append_connector_props(req: &mut PropertyList, state: &State) {
+    for ((conn, prop), value) in state.connector_props.iter() {
+        if state.connectors.contains(conn) {
+            req.add_property(*conn, *prop, property::Value::Unknown(*value));
+        }
+    }
+}
+
 #[inline]
 fn to_fixed<N: Coordinate>(n: N) -> u32 {
     f64::round(n.to_f64() * (1 << 16) as f64) as u32
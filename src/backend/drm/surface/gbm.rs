@@ -6,9 +6,9 @@ use drm::{Device, DriverCapability};
 use indexmap::IndexSet;
 
 use crate::backend::allocator::dmabuf::{AsDmabuf, Dmabuf};
-use crate::backend::allocator::format::get_opaque;
+use crate::backend::allocator::format::{get_opaque, negotiate_modifiers};
 use crate::backend::allocator::gbm::{GbmBuffer, GbmConvertError};
-use crate::backend::allocator::{Allocator, Format, Fourcc, Modifier, Slot, Swapchain};
+use crate::backend::allocator::{Allocator, Format, Fourcc, Slot, Swapchain};
 use crate::backend::drm::error::AccessError;
 use crate::backend::drm::gbm::{framebuffer_from_bo, GbmFramebuffer};
 use crate::backend::drm::{plane_has_property, DrmError, DrmSurface};
@@ -151,35 +151,8 @@ where
             return Err((allocator, Error::NoSupportedRendererFormat));
         }
 
-        let formats = {
-            // Special case: if a format supports explicit LINEAR (but no implicit Modifiers)
-            // and the other doesn't support any modifier, force Implicit.
-            // This should at least result in a working pipeline possibly with a linear buffer,
-            // but we cannot be sure.
-            if (plane_formats.len() == 1
-                && plane_formats.iter().next().unwrap().modifier == Modifier::Invalid
-                && renderer_formats.iter().all(|x| x.modifier != Modifier::Invalid)
-                && renderer_formats.iter().any(|x| x.modifier == Modifier::Linear))
-                || (renderer_formats.len() == 1
-                    && renderer_formats.first().unwrap().modifier == Modifier::Invalid
-                    && plane_formats.iter().all(|x| x.modifier != Modifier::Invalid)
-                    && plane_formats.iter().any(|x| x.modifier == Modifier::Linear))
-            {
-                vec![Format {
-                    code,
-                    modifier: Modifier::Invalid,
-                }]
-            } else {
-                plane_modifiers
-                    .intersection(&renderer_modifiers)
-                    .cloned()
-                    .map(|modifier| Format { code, modifier })
-                    .collect::<Vec<_>>()
-            }
-        };
-        debug!("Testing Formats: {:?}", formats);
-
-        let modifiers = formats.iter().map(|x| x.modifier).collect::<Vec<_>>();
+        let modifiers = negotiate_modifiers(renderer_modifiers, plane_modifiers);
+        debug!("Testing modifiers: {:?}", modifiers);
         let mode = drm.pending_mode();
 
         let mut swapchain: Swapchain<A> = Swapchain::new(
@@ -87,6 +87,8 @@ pub struct LegacyDrmSurface {
     state: RwLock<State>,
     pending: RwLock<State>,
     dpms: Mutex<bool>,
+    // last framebuffer we successfully scanned out, used to restore the state
+    framebuffer: Mutex<Option<framebuffer::Handle>>,
     pub(super) span: tracing::Span,
 }
 
@@ -116,6 +118,7 @@ impl LegacyDrmSurface {
             state: RwLock::new(state),
             pending: RwLock::new(pending),
             dpms: Mutex::new(true),
+            framebuffer: Mutex::new(None),
             span,
         };
 
@@ -310,6 +313,7 @@ impl LegacyDrmSurface {
             })?;
 
         *current = pending.clone();
+        *self.framebuffer.lock().unwrap() = Some(framebuffer);
 
         if event {
             // set crtc does not trigger page_flip events, so we immediately queue a flip
@@ -364,7 +368,10 @@ impl LegacyDrmSurface {
                 dev: self.fd.dev_path(),
                 source,
             })
-        })
+        })?;
+        *self.framebuffer.lock().unwrap() = Some(framebuffer);
+
+        Ok(())
     }
 
     #[instrument(level = "trace", parent = &self.span, skip(self))]
@@ -469,6 +476,48 @@ impl LegacyDrmSurface {
         self.fd.device_fd()
     }
 
+    pub(crate) fn crtc(&self) -> crtc::Handle {
+        self.crtc
+    }
+
+    // re-applies the last committed mode, connectors and framebuffer,
+    // legacy surfaces only drive the primary plane, so there is no cursor state to restore
+    #[instrument(level = "debug", parent = &self.span, skip(self))]
+    pub(crate) fn restore_state(&self) -> Result<(), Error> {
+        let current = self.state.read().unwrap();
+        let framebuffer = *self.framebuffer.lock().unwrap();
+        let dpms = *self.dpms.lock().unwrap();
+
+        let Some(framebuffer) = framebuffer else {
+            // we never scanned out anything, so there is nothing to restore
+            return Ok(());
+        };
+        if current.connectors.is_empty() || !dpms {
+            return Ok(());
+        }
+
+        set_connector_state(&*self.fd, current.connectors.iter().copied(), true)?;
+        self.fd
+            .set_crtc(
+                self.crtc,
+                Some(framebuffer),
+                (0, 0),
+                &current
+                    .connectors
+                    .iter()
+                    .copied()
+                    .collect::<Vec<connector::Handle>>(),
+                Some(current.mode),
+            )
+            .map_err(|source| {
+                Error::Access(AccessError {
+                    errmsg: "Error restoring crtc",
+                    dev: self.fd.dev_path(),
+                    source,
+                })
+            })
+    }
+
     pub fn clear(&self) -> Result<(), Error> {
         let current = self.state.read().unwrap();
         let mut dpms = self.dpms.lock().unwrap();
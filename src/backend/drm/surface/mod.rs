@@ -13,8 +13,10 @@ pub(super) mod atomic;
 pub(super) mod gbm;
 pub(super) mod legacy;
 use super::{
-    device::PlaneClaimStorage, error::Error, plane_type, DrmDeviceFd, PlaneClaim, PlaneInfo, PlaneType,
-    Planes,
+    connector::{connector_enum_value, set_connector_enum_property, ContentType},
+    device::PlaneClaimStorage,
+    error::Error,
+    plane_type, DrmDeviceFd, PlaneClaim, PlaneInfo, PlaneType, Planes,
 };
 use crate::utils::DevPath;
 use crate::utils::{Buffer, Physical, Point, Rectangle, Transform};
@@ -293,6 +295,28 @@ impl DrmSurface {
         }
     }
 
+    /// Sets the [`ContentType`] signaled to the sinks of all pending connectors
+    ///
+    /// On atomic devices the change is applied with the next commit,
+    /// legacy devices apply it immediately.
+    ///
+    /// Errors with [`Error::UnknownProperty`] if any connector does not support the `content type` property.
+    pub fn set_content_type(&self, content_type: ContentType) -> Result<(), Error> {
+        for conn in self.pending_connectors() {
+            match &*self.internal {
+                DrmSurfaceInternal::Atomic(surf) => {
+                    let (prop, value) =
+                        connector_enum_value(self, conn, "content type", content_type.name())?;
+                    surf.set_connector_property(conn, prop, value)?;
+                }
+                DrmSurfaceInternal::Legacy(_) => {
+                    set_connector_enum_property(self, conn, "content type", content_type.name())?
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Disables the given plane.
     ///
     /// Errors if the plane is not supported by this crtc or if the underlying
@@ -310,6 +334,7 @@ impl DrmSurface {
     /// - [`add_connector`](DrmSurface::add_connector)
     /// - [`remove_connector`](DrmSurface::remove_connector)
     /// - [`use_mode`](DrmSurface::use_mode)
+    /// - [`set_content_type`](DrmSurface::set_content_type)
     pub fn commit_pending(&self) -> bool {
         match &*self.internal {
             DrmSurfaceInternal::Atomic(surf) => surf.commit_pending(),
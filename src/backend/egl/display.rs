@@ -606,6 +606,12 @@ impl EGLDisplay {
         self.supports_damage_impl().supported()
     }
 
+    /// Returns when the display supports `EGL_KHR_partial_update`,
+    /// see [`EGLSurface::set_damage_region`](super::EGLSurface::set_damage_region).
+    pub fn supports_partial_update(&self) -> bool {
+        self.extensions.iter().any(|ext| ext == "EGL_KHR_partial_update")
+    }
+
     pub(super) fn supports_damage_impl(&self) -> DamageSupport {
         if self.extensions.iter().any(|ext| ext == "EGL_EXT_buffer_age") {
             if self
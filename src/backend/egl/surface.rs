@@ -10,7 +10,7 @@ use crate::backend::egl::{
     display::{DamageSupport, EGLDisplay, EGLDisplayHandle, PixelFormat},
     ffi,
     native::EGLNativeSurface,
-    EGLError, SwapBuffersError,
+    wrap_egl_call_bool, EGLError, SwapBuffersError,
 };
 use crate::utils::{Physical, Rectangle, Size};
 
@@ -24,6 +24,7 @@ pub struct EGLSurface {
     config_id: ffi::egl::types::EGLConfig,
     pixel_format: PixelFormat,
     damage_impl: DamageSupport,
+    partial_update: bool,
     span: tracing::Span,
 }
 
@@ -86,6 +87,7 @@ impl EGLSurface {
             config_id: config,
             pixel_format,
             damage_impl: display.supports_damage_impl(),
+            partial_update: display.supports_partial_update(),
             span,
         })
     }
@@ -118,6 +120,38 @@ impl EGLSurface {
         }
     }
 
+    /// Limits the region of the back buffer, that is going to be updated in the current frame
+    ///
+    /// Using `EGL_KHR_partial_update` the driver only has to preserve or load the contents outside
+    /// of the given damage, which saves memory bandwidth on tiled GPUs. This has to be called after
+    /// querying the [buffer age](EGLSurface::buffer_age) and before rendering, the damage has to contain
+    /// all regions rendered to in this frame. Like the damage passed to [`EGLSurface::swap_buffers`],
+    /// the rectangles are relative to the bottom-left corner of the surface.
+    ///
+    /// Does nothing if the display does not support `EGL_KHR_partial_update`.
+    #[instrument(level = "trace", parent = &self.span, skip(self), err)]
+    #[profiling::function]
+    pub fn set_damage_region(&self, damage: &[Rectangle<i32, Physical>]) -> Result<(), EGLError> {
+        if !self.partial_update {
+            return Ok(());
+        }
+
+        let surface = self.surface.load(Ordering::SeqCst);
+        let mut rects = damage
+            .iter()
+            .flat_map(|rect| [rect.loc.x, rect.loc.y, rect.size.w, rect.size.h])
+            .collect::<Vec<_>>();
+        wrap_egl_call_bool(|| unsafe {
+            ffi::egl::SetDamageRegionKHR(
+                **self.display,
+                surface as *const _,
+                rects.as_mut_ptr(),
+                damage.len() as i32,
+            )
+        })
+        .map(|_| ())
+    }
+
     /// Returns the size of the underlying back buffer
     #[profiling::function]
     pub fn get_size(&self) -> Option<Size<i32, Physical>> {
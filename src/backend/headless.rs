@@ -0,0 +1,225 @@
+//! Frame clock for headless and virtual outputs
+//!
+//! Outputs backed by a drm device are paced by the vblank events of the display,
+//! which are used to send frame callbacks and presentation feedback to clients.
+//! Headless, virtual or remote outputs have no such events. The [`HeadlessFrameClock`] generates them
+//! from a timer at the refresh rate of the output instead, so clients are throttled and receive
+//! presentation feedback exactly like on outputs backed by a display.
+//!
+//! ```no_run
+//! use smithay::backend::headless::HeadlessFrameClock;
+//! use smithay::desktop::utils::OutputPresentationFeedback;
+//! use smithay::output::{Mode, Output, PhysicalProperties, Subpixel};
+//! use smithay::reexports::wayland_protocols::wp::presentation_time::server::wp_presentation_feedback;
+//!
+//! # let event_loop = calloop::EventLoop::<()>::try_new().unwrap();
+//! let output = Output::new(
+//!     "HEADLESS-1".into(),
+//!     PhysicalProperties {
+//!         size: (0, 0).into(),
+//!         subpixel: Subpixel::Unknown,
+//!         make: "Smithay".into(),
+//!         model: "Headless".into(),
+//!     },
+//! );
+//! let mode = Mode {
+//!     size: (1920, 1080).into(),
+//!     refresh: 60_000,
+//! };
+//! output.change_current_state(Some(mode), None, None, None);
+//!
+//! event_loop
+//!     .handle()
+//!     .insert_source(HeadlessFrameClock::from_mode(mode), move |frame, _, _state| {
+//!         // render the output, send frame callbacks with `frame.time`
+//!         // and collect the presentation feedback of the rendered surfaces
+//!         let mut feedback = OutputPresentationFeedback::new(&output);
+//!         feedback.presented(
+//!             frame.time,
+//!             frame.refresh,
+//!             frame.sequence,
+//!             wp_presentation_feedback::Kind::Vsync,
+//!         );
+//!     })
+//!     .unwrap();
+//! ```
+
+use std::time::{Duration, Instant};
+
+use calloop::{
+    timer::{TimeoutAction, Timer},
+    EventSource, Poll, PostAction, Readiness, Token, TokenFactory,
+};
+
+use crate::{
+    output::Mode,
+    utils::{Clock, Monotonic, Time},
+};
+
+// used for modes without a valid refresh rate
+const DEFAULT_REFRESH: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+/// A frame generated by a [`HeadlessFrameClock`]
+#[derive(Debug, Clone, Copy)]
+pub struct HeadlessFrame {
+    /// Presentation time of the frame
+    pub time: Time<Monotonic>,
+    /// Refresh interval of the clock
+    pub refresh: Duration,
+    /// Sequence number of the frame, increasing by one per refresh interval
+    ///
+    /// Refresh intervals missed because the event loop was busy are skipped and
+    /// still counted, like vblanks of a display.
+    pub sequence: u64,
+}
+
+/// Timer based frame clock for headless and virtual outputs
+///
+/// See the [module documentation](self) for details.
+#[derive(Debug)]
+pub struct HeadlessFrameClock {
+    timer: Timer,
+    refresh: Duration,
+    sequence: u64,
+    clock: Clock<Monotonic>,
+}
+
+impl HeadlessFrameClock {
+    /// Create a new frame clock with the given refresh interval
+    ///
+    /// The first frame is generated after one refresh interval.
+    pub fn new(refresh: Duration) -> Self {
+        let refresh = if refresh.is_zero() {
+            DEFAULT_REFRESH
+        } else {
+            refresh
+        };
+        HeadlessFrameClock {
+            timer: Timer::from_duration(refresh),
+            refresh,
+            sequence: 0,
+            clock: Clock::new(),
+        }
+    }
+
+    /// Create a new frame clock running at the refresh rate of the given mode
+    pub fn from_mode(mode: Mode) -> Self {
+        Self::new(refresh_interval(mode))
+    }
+
+    /// Refresh interval of the clock
+    pub fn refresh(&self) -> Duration {
+        self.refresh
+    }
+
+    /// Change the refresh interval, e.g. after the mode of the output changed
+    ///
+    /// This takes effect after the next frame.
+    pub fn set_refresh(&mut self, refresh: Duration) {
+        self.refresh = if refresh.is_zero() {
+            DEFAULT_REFRESH
+        } else {
+            refresh
+        };
+    }
+}
+
+/// Refresh interval of a mode, 60Hz if the mode has no valid refresh rate
+pub fn refresh_interval(mode: Mode) -> Duration {
+    if mode.refresh > 0 {
+        Duration::from_nanos(1_000_000_000_000 / mode.refresh as u64)
+    } else {
+        DEFAULT_REFRESH
+    }
+}
+
+impl EventSource for HeadlessFrameClock {
+    type Event = HeadlessFrame;
+    type Metadata = ();
+    type Ret = ();
+    type Error = std::io::Error;
+
+    fn process_events<F>(
+        &mut self,
+        readiness: Readiness,
+        token: Token,
+        mut callback: F,
+    ) -> Result<PostAction, Self::Error>
+    where
+        F: FnMut(Self::Event, &mut Self::Metadata) -> Self::Ret,
+    {
+        let HeadlessFrameClock {
+            timer,
+            refresh,
+            sequence,
+            clock,
+        } = self;
+
+        timer.process_events(readiness, token, |deadline, _| {
+            let now = Instant::now();
+            let missed = (now.saturating_duration_since(deadline).as_nanos() / refresh.as_nanos()) as u32;
+            *sequence += 1 + missed as u64;
+
+            callback(
+                HeadlessFrame {
+                    time: clock.now(),
+                    refresh: *refresh,
+                    sequence: *sequence,
+                },
+                &mut (),
+            );
+
+            TimeoutAction::ToInstant(deadline + *refresh * (missed + 1))
+        })
+    }
+
+    fn register(&mut self, poll: &mut Poll, token_factory: &mut TokenFactory) -> calloop::Result<()> {
+        self.timer.register(poll, token_factory)
+    }
+
+    fn reregister(&mut self, poll: &mut Poll, token_factory: &mut TokenFactory) -> calloop::Result<()> {
+        self.timer.reregister(poll, token_factory)
+    }
+
+    fn unregister(&mut self, poll: &mut Poll) -> calloop::Result<()> {
+        self.timer.unregister(poll)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refresh_from_mode() {
+        let mode = |refresh| Mode {
+            size: (1920, 1080).into(),
+            refresh,
+        };
+        assert_eq!(refresh_interval(mode(60_000)), Duration::from_nanos(16_666_666));
+        assert_eq!(refresh_interval(mode(144_000)), Duration::from_nanos(6_944_444));
+        assert_eq!(refresh_interval(mode(0)), DEFAULT_REFRESH);
+    }
+
+    #[test]
+    fn frames() {
+        let mut event_loop = calloop::EventLoop::<Vec<HeadlessFrame>>::try_new().unwrap();
+        event_loop
+            .handle()
+            .insert_source(
+                HeadlessFrameClock::new(Duration::from_millis(1)),
+                |frame, _, frames| frames.push(frame),
+            )
+            .unwrap();
+
+        let mut frames = Vec::new();
+        while frames.len() < 2 {
+            event_loop
+                .dispatch(Some(Duration::from_millis(10)), &mut frames)
+                .unwrap();
+        }
+        assert!(frames[0].sequence < frames[1].sequence);
+        assert!(frames[0].time <= frames[1].time);
+        assert_eq!(frames[0].refresh, Duration::from_millis(1));
+    }
+}
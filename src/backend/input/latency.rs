@@ -0,0 +1,236 @@
+use std::time::Duration;
+
+use crate::utils::{Monotonic, Time};
+
+const BUCKETS: usize = 25;
+
+/// Histogram of latency samples
+///
+/// Samples are sorted into buckets growing by powers of two, starting at one microsecond,
+/// which keeps the histogram small while retaining enough precision for percentiles.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    buckets: [u64; BUCKETS],
+    count: u64,
+    sum: Duration,
+    max: Duration,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self {
+            buckets: [0; BUCKETS],
+            count: 0,
+            sum: Duration::ZERO,
+            max: Duration::ZERO,
+        }
+    }
+}
+
+impl LatencyHistogram {
+    /// Record a sample
+    pub fn record(&mut self, latency: Duration) {
+        let micros = latency.as_micros().max(1);
+        let bucket = (u128::BITS - 1 - micros.leading_zeros()) as usize;
+        self.buckets[bucket.min(BUCKETS - 1)] += 1;
+        self.count += 1;
+        self.sum += latency;
+        self.max = self.max.max(latency);
+    }
+
+    /// Number of recorded samples
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Mean of all recorded samples
+    pub fn mean(&self) -> Option<Duration> {
+        (self.count != 0).then(|| self.sum / self.count as u32)
+    }
+
+    /// Largest recorded sample
+    pub fn max(&self) -> Option<Duration> {
+        (self.count != 0).then_some(self.max)
+    }
+
+    /// Upper bound of the given percentile (`0.0..=100.0`) of the recorded samples
+    ///
+    /// The result is precise up to the bucket size, meaning the actual value lies
+    /// within the upper half of the returned duration.
+    pub fn percentile(&self, percentile: f64) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+
+        let rank = ((percentile.clamp(0.0, 100.0) / 100.0) * self.count as f64).ceil() as u64;
+        let mut seen = 0;
+        for (bound, count) in self.buckets() {
+            seen += count;
+            if seen >= rank.max(1) {
+                return Some(bound.min(self.max));
+            }
+        }
+        Some(self.max)
+    }
+
+    /// Iterate over the buckets of the histogram
+    ///
+    /// Yields the (exclusive) upper bound of each bucket together with the number of samples in it.
+    /// The last bucket additionally contains all samples exceeding its bound.
+    pub fn buckets(&self) -> impl Iterator<Item = (Duration, u64)> + '_ {
+        self.buckets
+            .iter()
+            .enumerate()
+            .map(|(i, count)| (Duration::from_micros(2u64 << i), *count))
+    }
+
+    /// Clear all recorded samples
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Stages of the event chain measured by [`InputLatencyTracker`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LatencyStage {
+    /// From the hardware timestamp of an event until it was dispatched to the seat
+    Dispatch,
+    /// From the hardware timestamp of an event until the next client commit
+    Commit,
+    /// From the hardware timestamp of an event until the next presentation of a frame
+    Presentation,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PendingEvent {
+    time: Time<Monotonic>,
+    committed: bool,
+}
+
+/// Measures the latency of input events
+///
+/// The tracker follows the chain from the timestamp of an input event, over dispatching it to the seat
+/// and the following commit of a client, up to the presentation of the resulting frame. The latencies
+/// of all stages are measured relative to the timestamp of the event and collected in a
+/// [`LatencyHistogram`] per [`LatencyStage`].
+///
+/// Events happening before the frame triggered by an earlier event was presented don't start
+/// a new chain, so the presentation latency reflects the oldest unpresented event.
+///
+/// Event timestamps of libinput use the monotonic clock and can be converted
+/// using `Time::from(Duration::from_micros(event.time()))`.
+#[derive(Debug, Default, Clone)]
+pub struct InputLatencyTracker {
+    pending: Option<PendingEvent>,
+    dispatch: LatencyHistogram,
+    commit: LatencyHistogram,
+    presentation: LatencyHistogram,
+}
+
+impl InputLatencyTracker {
+    /// Create a new tracker without any samples
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that an event with the given timestamp got dispatched to the seat at `now`
+    pub fn event_dispatched(&mut self, event_time: Time<Monotonic>, now: Time<Monotonic>) {
+        self.dispatch.record(Time::elapsed(&event_time, now));
+        if self.pending.is_none() {
+            self.pending = Some(PendingEvent {
+                time: event_time,
+                committed: false,
+            });
+        }
+    }
+
+    /// Record that a client committed at `now`
+    ///
+    /// This should be called for commits of the surface the events were dispatched to.
+    pub fn surface_committed(&mut self, now: Time<Monotonic>) {
+        if let Some(pending) = self.pending.as_mut().filter(|pending| !pending.committed) {
+            pending.committed = true;
+            self.commit.record(Time::elapsed(&pending.time, now));
+        }
+    }
+
+    /// Record that a frame was presented at `presentation_time`
+    ///
+    /// Frames presented before a client committed in response to the pending event are ignored.
+    pub fn frame_presented(&mut self, presentation_time: Time<Monotonic>) {
+        if let Some(pending) = self.pending.filter(|pending| pending.committed) {
+            self.pending = None;
+            self.presentation
+                .record(Time::elapsed(&pending.time, presentation_time));
+        }
+    }
+
+    /// Returns the histogram of the given stage
+    pub fn histogram(&self, stage: LatencyStage) -> &LatencyHistogram {
+        match stage {
+            LatencyStage::Dispatch => &self.dispatch,
+            LatencyStage::Commit => &self.commit,
+            LatencyStage::Presentation => &self.presentation,
+        }
+    }
+
+    /// Clear all recorded samples and the pending event chain
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(millis: u64) -> Time<Monotonic> {
+        Time::from(Duration::from_millis(millis))
+    }
+
+    #[test]
+    fn histogram_percentiles() {
+        let mut histogram = LatencyHistogram::default();
+        assert_eq!(histogram.percentile(50.0), None);
+
+        for millis in 1..=100 {
+            histogram.record(Duration::from_millis(millis));
+        }
+        assert_eq!(histogram.count(), 100);
+        assert_eq!(histogram.max(), Some(Duration::from_millis(100)));
+        assert_eq!(histogram.mean(), Some(Duration::from_micros(50_500)));
+
+        let median = histogram.percentile(50.0).unwrap();
+        assert!(median >= Duration::from_millis(50) && median < Duration::from_millis(100));
+        assert_eq!(histogram.percentile(100.0), Some(Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn tracks_oldest_event_until_presented() {
+        let mut tracker = InputLatencyTracker::new();
+
+        tracker.frame_presented(time(5));
+        tracker.event_dispatched(time(10), time(11));
+        tracker.event_dispatched(time(12), time(13));
+        tracker.frame_presented(time(14));
+        tracker.surface_committed(time(15));
+        tracker.surface_committed(time(16));
+        tracker.frame_presented(time(26));
+
+        assert_eq!(tracker.histogram(LatencyStage::Dispatch).count(), 2);
+        assert_eq!(
+            tracker.histogram(LatencyStage::Commit).max(),
+            Some(Duration::from_millis(5))
+        );
+        assert_eq!(tracker.histogram(LatencyStage::Commit).count(), 1);
+        assert_eq!(
+            tracker.histogram(LatencyStage::Presentation).max(),
+            Some(Duration::from_millis(16))
+        );
+
+        tracker.event_dispatched(time(30), time(31));
+        tracker.surface_committed(time(32));
+        tracker.frame_presented(time(40));
+        assert_eq!(tracker.histogram(LatencyStage::Presentation).count(), 2);
+    }
+}
@@ -4,17 +4,21 @@ use std::path::PathBuf;
 
 pub use xkbcommon::xkb::Keycode;
 
+mod latency;
 mod tablet;
 
+pub use latency::{InputLatencyTracker, LatencyHistogram, LatencyStage};
 pub use tablet::{
-    ProximityState, TabletToolAxisEvent, TabletToolButtonEvent, TabletToolCapabilities, TabletToolDescriptor,
-    TabletToolEvent, TabletToolProximityEvent, TabletToolTipEvent, TabletToolTipState, TabletToolType,
+    ProximityState, TabletPadAxisSource, TabletPadButtonEvent, TabletPadDescriptor, TabletPadEvent,
+    TabletPadGroupDescriptor, TabletPadRingEvent, TabletPadStripEvent, TabletToolAxisEvent,
+    TabletToolButtonEvent, TabletToolCapabilities, TabletToolDescriptor, TabletToolEvent,
+    TabletToolProximityEvent, TabletToolTipEvent, TabletToolTipState, TabletToolType,
 };
 
 #[cfg(feature = "wayland_frontend")]
 use wayland_server::protocol::wl_pointer;
 
-use crate::utils::{Logical, Point, Raw, Size};
+use crate::utils::{Logical, Point, Raw, Rectangle, Size, Transform};
 
 /// Trait for generic functions every input device does provide
 pub trait Device: PartialEq + Eq + std::hash::Hash {
@@ -488,6 +492,24 @@ pub trait AbsolutePositionEvent<B: InputBackend>: Event<B> {
     /// Device y position converted to the targets coordinate space's height.
     /// E.g. the focused output's height.
     fn y_transformed(&self, height: i32) -> f64;
+
+    /// Device position mapped onto an output, converted to the global compositor space
+    ///
+    /// `output_geometry` is the geometry of the output in the global compositor space and
+    /// `transform` its current transform. The device is expected to be mounted in the native
+    /// orientation of the output, like the touchscreen of a rotated laptop or tablet.
+    ///
+    /// Devices requiring calibration, like misaligned touchscreens, can be calibrated
+    /// beforehand via `input::Device::config_calibration_set_matrix` on the libinput backend.
+    fn position_in_output(
+        &self,
+        output_geometry: Rectangle<i32, Logical>,
+        transform: Transform,
+    ) -> Point<f64, Logical> {
+        let size = transform.invert().transform_size(output_geometry.size);
+        transform.transform_point_in(self.position_transformed(size), &size.to_f64())
+            + output_geometry.loc.to_f64()
+    }
 }
 
 impl<B: InputBackend> AbsolutePositionEvent<B> for UnusedEvent {
@@ -662,6 +684,12 @@ pub trait InputBackend: Sized {
     type TabletToolTipEvent: TabletToolTipEvent<Self>;
     /// Type representing button events on tablet tool devices
     type TabletToolButtonEvent: TabletToolButtonEvent<Self>;
+    /// Type representing button events on tablet pad devices
+    type TabletPadButtonEvent: TabletPadButtonEvent<Self>;
+    /// Type representing ring events on tablet pad devices
+    type TabletPadRingEvent: TabletPadRingEvent<Self>;
+    /// Type representing strip events on tablet pad devices
+    type TabletPadStripEvent: TabletPadStripEvent<Self>;
     /// Type representing switch toggle events
     type SwitchToggleEvent: SwitchToggleEvent<Self>;
 
@@ -800,6 +828,24 @@ pub enum InputEvent<B: InputBackend> {
         event: B::TabletToolButtonEvent,
     },
 
+    /// A tablet pad button was pressed or released
+    TabletPadButton {
+        /// The tablet pad button event
+        event: B::TabletPadButtonEvent,
+    },
+
+    /// A tablet pad ring changed its position
+    TabletPadRing {
+        /// The tablet pad ring event
+        event: B::TabletPadRingEvent,
+    },
+
+    /// A tablet pad strip changed its position
+    TabletPadStrip {
+        /// The tablet pad strip event
+        event: B::TabletPadStripEvent,
+    },
+
     /// A switch was toggled
     SwitchToggle {
         /// The switch toggle event
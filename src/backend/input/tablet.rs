@@ -1,6 +1,7 @@
 use super::{ButtonState, Event, InputBackend, UnusedEvent};
 use crate::utils::{Logical, Point, Raw, Size};
 use bitflags::bitflags;
+use std::path::PathBuf;
 
 /// Description of physical tablet tool
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
@@ -369,3 +370,145 @@ impl<B: InputBackend> TabletToolButtonEvent<B> for UnusedEvent {
         match *self {}
     }
 }
+
+/// Source of a ring or strip event on a tablet pad
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TabletPadAxisSource {
+    /// The source is unknown
+    Unknown,
+    /// The event was caused by a finger on the ring or strip
+    Finger,
+}
+
+/// Description of a physical tablet pad
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct TabletPadDescriptor {
+    /// Pad device name
+    pub name: String,
+    /// Path to the device
+    pub syspath: Option<PathBuf>,
+    /// Number of buttons on the pad
+    pub buttons: u32,
+    /// Mode groups of the pad
+    ///
+    /// Every pad has at least one mode group.
+    pub groups: Vec<TabletPadGroupDescriptor>,
+}
+
+/// Description of a mode group of a tablet pad
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct TabletPadGroupDescriptor {
+    /// Buttons belonging to this group
+    pub buttons: Vec<u32>,
+    /// Rings belonging to this group
+    pub rings: Vec<u32>,
+    /// Strips belonging to this group
+    pub strips: Vec<u32>,
+    /// Number of modes this group can be switched between
+    pub modes: u32,
+}
+
+/// Trait for generic functions of all tablet pad events
+///
+/// Tablet pads are the buttons, rings and strips on the tablet itself, usually
+/// found next to the drawing area. The functions of the pad can be switched via
+/// mode groups, each covering a subset of the buttons, rings and strips of the pad.
+pub trait TabletPadEvent<B: InputBackend>: Event<B> {
+    /// Returns the index of the mode group this event belongs to
+    fn mode_group(&self) -> u32;
+
+    /// Returns the mode the mode group of this event is in
+    ///
+    /// Mode switches are reported via a [`TabletPadButtonEvent`] of the button
+    /// toggling the mode, carrying the new mode.
+    fn mode(&self) -> u32;
+}
+
+impl<B: InputBackend> TabletPadEvent<B> for UnusedEvent {
+    fn mode_group(&self) -> u32 {
+        match *self {}
+    }
+
+    fn mode(&self) -> u32 {
+        match *self {}
+    }
+}
+
+/// Signals that a button on a device with the `DeviceCapability::TabletPad` capability changed its state.
+pub trait TabletPadButtonEvent<B: InputBackend>: TabletPadEvent<B> {
+    /// Return the button that triggered this event.
+    ///
+    /// Unlike other button events, pad buttons are numbered sequentially starting at 0.
+    fn button(&self) -> u32;
+
+    /// Return the button state of the event.
+    fn button_state(&self) -> ButtonState;
+}
+
+impl<B: InputBackend> TabletPadButtonEvent<B> for UnusedEvent {
+    fn button(&self) -> u32 {
+        match *self {}
+    }
+
+    fn button_state(&self) -> ButtonState {
+        match *self {}
+    }
+}
+
+/// Signals that a ring on a device with the `DeviceCapability::TabletPad` capability changed its position.
+pub trait TabletPadRingEvent<B: InputBackend>: TabletPadEvent<B> {
+    /// Return the index of the ring that triggered this event.
+    fn number(&self) -> u32;
+
+    /// Return the current absolute position of the ring in degrees.
+    ///
+    /// 0 is the northern-most point of the ring, increasing clockwise.
+    /// A value of -1 signals, that the finger was lifted from the ring.
+    fn position(&self) -> f64;
+
+    /// Return the source of the interaction with the ring.
+    fn source(&self) -> TabletPadAxisSource;
+}
+
+impl<B: InputBackend> TabletPadRingEvent<B> for UnusedEvent {
+    fn number(&self) -> u32 {
+        match *self {}
+    }
+
+    fn position(&self) -> f64 {
+        match *self {}
+    }
+
+    fn source(&self) -> TabletPadAxisSource {
+        match *self {}
+    }
+}
+
+/// Signals that a strip on a device with the `DeviceCapability::TabletPad` capability changed its position.
+pub trait TabletPadStripEvent<B: InputBackend>: TabletPadEvent<B> {
+    /// Return the index of the strip that triggered this event.
+    fn number(&self) -> u32;
+
+    /// Return the current absolute position of the strip, normalized to the range `[0, 1]`.
+    ///
+    /// 0 is the top or left-most point of the strip. A value of -1 signals,
+    /// that the finger was lifted from the strip.
+    fn position(&self) -> f64;
+
+    /// Return the source of the interaction with the strip.
+    fn source(&self) -> TabletPadAxisSource;
+}
+
+impl<B: InputBackend> TabletPadStripEvent<B> for UnusedEvent {
+    fn number(&self) -> u32 {
+        match *self {}
+    }
+
+    fn position(&self) -> f64 {
+        match *self {}
+    }
+
+    fn source(&self) -> TabletPadAxisSource {
+        match *self {}
+    }
+}
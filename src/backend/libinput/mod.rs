@@ -4,7 +4,7 @@ use crate::backend::input::{
     self as backend, Axis, AxisRelativeDirection, AxisSource, InputBackend, InputEvent,
 };
 #[cfg(feature = "backend_session")]
-use crate::backend::session::{AsErrno, Session};
+use crate::backend::session::{AsErrno, Event as SessionEvent, Session};
 use input as libinput;
 use input::event;
 
@@ -54,8 +54,38 @@ impl LibinputInputBackend {
     pub fn context(&self) -> &libinput::Libinput {
         &self.context
     }
+
+    /// Suspend or resume the libinput context following a session event
+    ///
+    /// On [`SessionEvent::PauseSession`] all devices are closed, on [`SessionEvent::ActivateSession`]
+    /// they are opened again through the [`LibinputInterface`](libinput::LibinputInterface) of the
+    /// context, e.g. a [`LibinputSessionInterface`], so every device gets a fresh file descriptor
+    /// from the session and no revoked descriptors are kept across session switches.
+    ///
+    /// Returns an error if the context could not be resumed.
+    #[cfg(feature = "backend_session")]
+    pub fn handle_session_event(&mut self, event: &SessionEvent) -> Result<(), ResumeError> {
+        let _guard = self.span.enter();
+        match event {
+            SessionEvent::PauseSession => {
+                info!("Suspending libinput context");
+                self.context.suspend();
+                Ok(())
+            }
+            SessionEvent::ActivateSession => {
+                info!("Resuming libinput context");
+                self.context.resume().map_err(|()| ResumeError)
+            }
+        }
+    }
 }
 
+/// Error resuming the libinput context of a [`LibinputInputBackend`]
+#[cfg(feature = "backend_session")]
+#[derive(Debug, thiserror::Error)]
+#[error("Failed to resume the libinput context")]
+pub struct ResumeError;
+
 impl backend::Device for libinput::Device {
     fn id(&self) -> String {
         self.sysname().into()
@@ -606,6 +636,9 @@ impl InputBackend for LibinputInputBackend {
     type TabletToolProximityEvent = event::tablet_tool::TabletToolProximityEvent;
     type TabletToolTipEvent = event::tablet_tool::TabletToolTipEvent;
     type TabletToolButtonEvent = event::tablet_tool::TabletToolButtonEvent;
+    type TabletPadButtonEvent = event::tablet_pad::TabletPadButtonEvent;
+    type TabletPadRingEvent = event::tablet_pad::TabletPadRingEvent;
+    type TabletPadStripEvent = event::tablet_pad::TabletPadStripEvent;
 
     type SwitchToggleEvent = event::switch::SwitchToggleEvent;
 
@@ -857,6 +890,20 @@ impl EventSource for LibinputInputBackend {
                             trace!("Unknown libinput tablet event");
                         }
                     },
+                    libinput::Event::TabletPad(tablet_pad_event) => match tablet_pad_event {
+                        event::TabletPadEvent::Button(event) => {
+                            callback(InputEvent::TabletPadButton { event }, &mut ());
+                        }
+                        event::TabletPadEvent::Ring(event) => {
+                            callback(InputEvent::TabletPadRing { event }, &mut ());
+                        }
+                        event::TabletPadEvent::Strip(event) => {
+                            callback(InputEvent::TabletPadStrip { event }, &mut ());
+                        }
+                        _ => {
+                            trace!("Unknown libinput tablet pad event");
+                        }
+                    },
                     libinput::Event::Switch(switch_event) => match switch_event {
                         event::SwitchEvent::Toggle(event) => {
                             callback(InputEvent::SwitchToggle { event }, &mut ());
@@ -1,10 +1,11 @@
 use crate::backend::input::{
-    self as backend, TabletToolCapabilities, TabletToolDescriptor, TabletToolTipState, TabletToolType,
+    self as backend, TabletPadDescriptor, TabletPadGroupDescriptor, TabletToolCapabilities,
+    TabletToolDescriptor, TabletToolTipState, TabletToolType,
 };
 
 use input as libinput;
 use input::event;
-use input::event::{tablet_tool, EventTrait};
+use input::event::{tablet_pad, tablet_tool, EventTrait};
 
 use super::LibinputInputBackend;
 
@@ -187,3 +188,99 @@ impl backend::TabletToolButtonEvent<LibinputInputBackend> for tablet_tool::Table
         tablet_tool::TabletToolButtonEvent::button_state(self).into()
     }
 }
+
+impl From<&libinput::Device> for TabletPadDescriptor {
+    fn from(device: &libinput::Device) -> Self {
+        let buttons = device.tablet_pad_number_of_buttons().max(0) as u32;
+        let rings = device.tablet_pad_number_of_rings().max(0) as u32;
+        let strips = device.tablet_pad_number_of_strips().max(0) as u32;
+        let groups = (0..device.tablet_pad_number_of_mode_groups().max(0) as u32)
+            .filter_map(|index| device.tablet_pad_mode_group(index))
+            .map(|group| TabletPadGroupDescriptor {
+                buttons: (0..buttons).filter(|b| group.has_button(*b)).collect(),
+                rings: (0..rings).filter(|r| group.has_ring(*r)).collect(),
+                strips: (0..strips).filter(|s| group.has_strip(*s)).collect(),
+                modes: group.number_of_modes(),
+            })
+            .collect();
+
+        TabletPadDescriptor {
+            name: backend::Device::name(device),
+            syspath: backend::Device::syspath(device),
+            buttons,
+            groups,
+        }
+    }
+}
+
+macro_rules! tablet_pad_event_impl {
+    ($ty:ty) => {
+        impl backend::Event<LibinputInputBackend> for $ty {
+            fn time(&self) -> u64 {
+                tablet_pad::TabletPadEventTrait::time_usec(self)
+            }
+
+            fn device(&self) -> libinput::Device {
+                event::EventTrait::device(self)
+            }
+        }
+
+        impl backend::TabletPadEvent<LibinputInputBackend> for $ty {
+            fn mode_group(&self) -> u32 {
+                tablet_pad::TabletPadEventTrait::mode_group(self).index()
+            }
+
+            fn mode(&self) -> u32 {
+                tablet_pad::TabletPadEventTrait::mode(self)
+            }
+        }
+    };
+}
+
+tablet_pad_event_impl!(tablet_pad::TabletPadButtonEvent);
+tablet_pad_event_impl!(tablet_pad::TabletPadRingEvent);
+tablet_pad_event_impl!(tablet_pad::TabletPadStripEvent);
+
+impl backend::TabletPadButtonEvent<LibinputInputBackend> for tablet_pad::TabletPadButtonEvent {
+    fn button(&self) -> u32 {
+        tablet_pad::TabletPadButtonEvent::button_number(self)
+    }
+
+    fn button_state(&self) -> backend::ButtonState {
+        tablet_pad::TabletPadButtonEvent::button_state(self).into()
+    }
+}
+
+impl backend::TabletPadRingEvent<LibinputInputBackend> for tablet_pad::TabletPadRingEvent {
+    fn number(&self) -> u32 {
+        tablet_pad::TabletPadRingEvent::number(self)
+    }
+
+    fn position(&self) -> f64 {
+        tablet_pad::TabletPadRingEvent::position(self)
+    }
+
+    fn source(&self) -> backend::TabletPadAxisSource {
+        match tablet_pad::TabletPadRingEvent::source(self) {
+            tablet_pad::RingAxisSource::Finger => backend::TabletPadAxisSource::Finger,
+            _ => backend::TabletPadAxisSource::Unknown,
+        }
+    }
+}
+
+impl backend::TabletPadStripEvent<LibinputInputBackend> for tablet_pad::TabletPadStripEvent {
+    fn number(&self) -> u32 {
+        tablet_pad::TabletPadStripEvent::number(self)
+    }
+
+    fn position(&self) -> f64 {
+        tablet_pad::TabletPadStripEvent::position(self)
+    }
+
+    fn source(&self) -> backend::TabletPadAxisSource {
+        match tablet_pad::TabletPadStripEvent::source(self) {
+            tablet_pad::StripAxisSource::Finger => backend::TabletPadAxisSource::Finger,
+            _ => backend::TabletPadAxisSource::Unknown,
+        }
+    }
+}
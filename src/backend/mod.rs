@@ -26,7 +26,8 @@
 //! that input data, with a generic API provided by the traits and types defined in the
 //! [`input`] module. An input provider following this API based on `libinput` is given in the
 //! [`libinput`] module, gated by the `backend_libinput` cargo feature. The winit backend
-//! (see below) also provides an input provider.
+//! (see below) also provides an input provider. For testing, the [`simulated`] module provides
+//! an input provider whose events are injected programmatically.
 //!
 //! ### Graphics
 //!
@@ -58,6 +59,9 @@
 //! moment, even clients using dma-buf still require that the `wl_drm` infrastructure is
 //! initialized to have hardware-acceleration.
 //!
+//! Outputs without a display, like headless or virtual outputs, can be paced using the frame clock
+//! of the [`headless`] module.
+//!
 //! ## X11 backend
 //!
 //! Alongside this infrastructure, Smithay also provides an alternative backend based on
@@ -80,8 +84,10 @@
 //!
 
 pub mod allocator;
+pub mod headless;
 pub mod input;
 pub mod renderer;
+pub mod simulated;
 
 #[cfg(feature = "backend_drm")]
 pub mod drm;
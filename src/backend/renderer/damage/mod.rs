@@ -221,6 +221,8 @@ use super::{
 use super::{Renderer, Texture};
 
 mod shaper;
+mod throttle;
+pub use throttle::FrameThrottle;
 
 use shaper::DamageShaper;
 
@@ -0,0 +1,147 @@
+use std::time::Duration;
+
+use crate::utils::{Monotonic, Time};
+
+/// Helper deciding when to deliver new frames to a capture consumer
+///
+/// Screencasts of mostly static content do not need to be re-rendered and encoded
+/// at the full refresh rate of the output. Using one [`FrameThrottle`] per consumer,
+/// frames are only delivered if the captured region was damaged since the last frame,
+/// no more often than the configured minimum interval allows. Optionally a frame is
+/// delivered after a maximum idle time regardless of damage, to keep consumers alive,
+/// that expect a steady stream of frames.
+///
+/// The damage of a region can be obtained from the result of
+/// [`OutputDamageTracker::damage_output`](super::OutputDamageTracker::damage_output)
+/// using a dedicated damage tracker per consumer.
+#[derive(Debug, Clone, Default)]
+pub struct FrameThrottle {
+    min_interval: Option<Duration>,
+    max_idle: Option<Duration>,
+    last_frame: Option<Time<Monotonic>>,
+    pending_damage: bool,
+}
+
+impl FrameThrottle {
+    /// Create a new throttle without any frame rate limit or keepalive
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the minimum interval between two frames
+    ///
+    /// `None` disables the frame rate limit.
+    pub fn set_min_interval(&mut self, interval: Option<Duration>) {
+        self.min_interval = interval;
+    }
+
+    /// Limit the frame rate to the given frames per second
+    ///
+    /// A value of `0` disables the frame rate limit.
+    pub fn set_max_frame_rate(&mut self, fps: u32) {
+        self.min_interval = (fps != 0).then(|| Duration::from_secs(1) / fps);
+    }
+
+    /// Set the maximum time without a new frame
+    ///
+    /// After this time a frame will be delivered even without any damage.
+    /// `None` disables the keepalive.
+    pub fn set_max_idle(&mut self, max_idle: Option<Duration>) {
+        self.max_idle = max_idle;
+    }
+
+    /// Returns `true` if a frame should be delivered at `now`
+    ///
+    /// `damaged` indicates if the captured region was damaged since the last call.
+    /// Damage is remembered until a frame is delivered, so damage throttled by the
+    /// frame rate limit is not lost.
+    pub fn should_deliver(&mut self, now: Time<Monotonic>, damaged: bool) -> bool {
+        self.pending_damage |= damaged;
+
+        let Some(last_frame) = self.last_frame else {
+            return true;
+        };
+        let elapsed = Time::elapsed(&last_frame, now);
+
+        if self.min_interval.map(|min| elapsed < min).unwrap_or(false) {
+            return false;
+        }
+
+        self.pending_damage || self.max_idle.map(|max| elapsed >= max).unwrap_or(false)
+    }
+
+    /// Record that a frame was delivered at `now`
+    pub fn frame_delivered(&mut self, now: Time<Monotonic>) {
+        self.last_frame = Some(now);
+        self.pending_damage = false;
+    }
+
+    /// Returns the duration after which [`FrameThrottle::should_deliver`] might return `true`
+    /// without any new damage
+    ///
+    /// This can be used to schedule a timer to deliver throttled damage or the keepalive frame.
+    /// Returns `None` if no frame is due without new damage.
+    pub fn time_to_next_frame(&self, now: Time<Monotonic>) -> Option<Duration> {
+        let Some(last_frame) = self.last_frame else {
+            return Some(Duration::ZERO);
+        };
+        let elapsed = Time::elapsed(&last_frame, now);
+
+        let due = if self.pending_damage {
+            self.min_interval.unwrap_or(Duration::ZERO)
+        } else {
+            self.max_idle?
+        };
+        Some(due.saturating_sub(elapsed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(ms: u64) -> Time<Monotonic> {
+        Duration::from_millis(ms).into()
+    }
+
+    #[test]
+    fn deliver_only_damage() {
+        let mut throttle = FrameThrottle::new();
+        assert!(throttle.should_deliver(ms(0), false));
+        throttle.frame_delivered(ms(0));
+
+        assert!(!throttle.should_deliver(ms(16), false));
+        assert!(throttle.should_deliver(ms(32), true));
+        throttle.frame_delivered(ms(32));
+        assert!(!throttle.should_deliver(ms(48), false));
+        assert_eq!(throttle.time_to_next_frame(ms(48)), None);
+    }
+
+    #[test]
+    fn frame_rate_limit_keeps_damage() {
+        let mut throttle = FrameThrottle::new();
+        throttle.set_max_frame_rate(10);
+        throttle.frame_delivered(ms(0));
+
+        assert!(!throttle.should_deliver(ms(50), true));
+        assert_eq!(
+            throttle.time_to_next_frame(ms(50)),
+            Some(Duration::from_millis(50))
+        );
+        assert!(throttle.should_deliver(ms(100), false));
+    }
+
+    #[test]
+    fn keepalive() {
+        let mut throttle = FrameThrottle::new();
+        throttle.set_max_idle(Some(Duration::from_secs(1)));
+        throttle.frame_delivered(ms(0));
+
+        assert!(!throttle.should_deliver(ms(500), false));
+        assert_eq!(
+            throttle.time_to_next_frame(ms(500)),
+            Some(Duration::from_millis(500))
+        );
+        assert!(throttle.should_deliver(ms(1000), false));
+    }
+}
@@ -0,0 +1,252 @@
+//! Element to render a static output background
+//!
+//! Outputs without any client surface covering them, e.g. before a wallpaper client started,
+//! show whatever was left in the framebuffer. A [`Background`] fills the output with a solid color
+//! and optionally an image, so basic compositors don't need a separate wallpaper client.
+//!
+//! Backgrounds are per output, create one for every output and keep its size up to date with
+//! [`Background::resize`] when the mode or scale of the output changes. The elements of the background
+//! should be rendered below everything else.
+//!
+//! ```no_run
+//! use smithay::backend::allocator::Fourcc;
+//! use smithay::backend::renderer::element::background::{Background, BackgroundMode};
+//! use smithay::backend::renderer::element::memory::MemoryRenderBuffer;
+//! use smithay::utils::Transform;
+//!
+//! # let pixels: Vec<u8> = Vec::new();
+//! let image =
+//!     MemoryRenderBuffer::from_slice(&pixels, Fourcc::Argb8888, (3840, 2160), 1, Transform::Normal, None);
+//!
+//! let mut background = Background::new([0.1, 0.1, 0.1, 1.0]);
+//! background.set_image(Some(image), BackgroundMode::Fill);
+//! // logical size of the output
+//! background.resize((1920, 1080));
+//! ```
+
+use tracing::warn;
+
+use crate::{
+    backend::renderer::{Color32F, ImportMem, Renderer, Texture},
+    utils::{Logical, Physical, Point, Rectangle, Scale, Size},
+};
+
+use super::{
+    memory::{MemoryRenderBuffer, MemoryRenderBufferRenderElement},
+    solid::{SolidColorBuffer, SolidColorRenderElement},
+    AsRenderElements, Kind,
+};
+
+/// Scaling of the image of a [`Background`] to the output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum BackgroundMode {
+    /// Scale the image preserving its aspect ratio to cover the whole output, cropping it if necessary
+    #[default]
+    Fill,
+    /// Scale the image preserving its aspect ratio to fit into the output, showing the color around it
+    Fit,
+    /// Scale the image to the size of the output, ignoring its aspect ratio
+    Stretch,
+    /// Show the image unscaled in the center of the output, cropping it if necessary
+    Center,
+}
+
+/// Static background of an output, a solid color and an optional image
+///
+/// The color is rendered below the image, so it is visible around images not covering the output and
+/// through transparent parts of the image. Set opaque regions on the [`MemoryRenderBuffer`] of opaque
+/// images, so the color can be skipped below them.
+#[derive(Debug, Clone)]
+pub struct Background {
+    output_size: Size<i32, Logical>,
+    color: SolidColorBuffer,
+    image: Option<(MemoryRenderBuffer, BackgroundMode)>,
+}
+
+impl Background {
+    /// Create a new background with the given color and no image
+    ///
+    /// The background is empty until its size is set with [`Background::resize`].
+    pub fn new(color: impl Into<Color32F>) -> Self {
+        Background {
+            output_size: Size::default(),
+            color: SolidColorBuffer::new((0, 0), color),
+            image: None,
+        }
+    }
+
+    /// Set the color of the background
+    pub fn set_color(&mut self, color: impl Into<Color32F>) {
+        self.color.set_color(color);
+    }
+
+    /// Set or remove the image of the background
+    pub fn set_image(&mut self, image: Option<MemoryRenderBuffer>, mode: BackgroundMode) {
+        self.image = image.map(|image| (image, mode));
+    }
+
+    /// Change the scaling of the image
+    pub fn set_mode(&mut self, mode: BackgroundMode) {
+        if let Some((_, current)) = self.image.as_mut() {
+            *current = mode;
+        }
+    }
+
+    /// Current scaling of the image, `None` if the background has no image
+    pub fn mode(&self) -> Option<BackgroundMode> {
+        self.image.as_ref().map(|(_, mode)| *mode)
+    }
+
+    /// Set the logical size of the output
+    pub fn resize(&mut self, output_size: impl Into<Size<i32, Logical>>) {
+        self.output_size = output_size.into();
+        self.color.resize(self.output_size);
+    }
+}
+
+// part of the image to show and where to show it on the output
+fn background_geometry(
+    output_size: Size<i32, Logical>,
+    image_size: Size<i32, Logical>,
+    mode: BackgroundMode,
+) -> (Rectangle<f64, Logical>, Rectangle<i32, Logical>) {
+    let output = Rectangle::from_loc_and_size((0, 0), output_size);
+    let image = Rectangle::from_loc_and_size((0.0, 0.0), image_size.to_f64());
+    if output_size.is_empty() || image_size.is_empty() {
+        return (image, Rectangle::default());
+    }
+
+    let scale_x = output_size.w as f64 / image_size.w as f64;
+    let scale_y = output_size.h as f64 / image_size.h as f64;
+    match mode {
+        BackgroundMode::Stretch => (image, output),
+        BackgroundMode::Fill => {
+            let scale = f64::max(scale_x, scale_y);
+            let size = Size::from((output_size.w as f64 / scale, output_size.h as f64 / scale));
+            let loc = Point::from(((image.size.w - size.w) / 2.0, (image.size.h - size.h) / 2.0));
+            (Rectangle::from_loc_and_size(loc, size), output)
+        }
+        BackgroundMode::Fit => {
+            let scale = f64::min(scale_x, scale_y);
+            let size = Size::from((
+                (image_size.w as f64 * scale).round() as i32,
+                (image_size.h as f64 * scale).round() as i32,
+            ));
+            let loc = Point::from(((output_size.w - size.w) / 2, (output_size.h - size.h) / 2));
+            (image, Rectangle::from_loc_and_size(loc, size))
+        }
+        BackgroundMode::Center => {
+            let size = Size::from((
+                i32::min(image_size.w, output_size.w),
+                i32::min(image_size.h, output_size.h),
+            ));
+            let src_loc = Point::from(((image_size.w - size.w) / 2, (image_size.h - size.h) / 2));
+            let dst_loc = Point::from(((output_size.w - size.w) / 2, (output_size.h - size.h) / 2));
+            (
+                Rectangle::from_loc_and_size(src_loc, size).to_f64(),
+                Rectangle::from_loc_and_size(dst_loc, size),
+            )
+        }
+    }
+}
+
+crate::backend::renderer::element::render_elements! {
+    /// Render elements of a [`Background`]
+    pub BackgroundRenderElement<R> where
+        R: ImportMem;
+    /// The background color
+    Solid=SolidColorRenderElement,
+    /// The background image
+    Image=MemoryRenderBufferRenderElement<R>,
+}
+
+impl<R: Renderer> std::fmt::Debug for BackgroundRenderElement<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Solid(arg0) => f.debug_tuple("Solid").field(arg0).finish(),
+            Self::Image(arg0) => f.debug_tuple("Image").field(arg0).finish(),
+            Self::_GenericCatcher(_) => unreachable!(),
+        }
+    }
+}
+
+impl<T, R> AsRenderElements<R> for Background
+where
+    T: Texture + Clone + Send + 'static,
+    R: Renderer<TextureId = T> + ImportMem,
+{
+    type RenderElement = BackgroundRenderElement<R>;
+
+    fn render_elements<C: From<Self::RenderElement>>(
+        &self,
+        renderer: &mut R,
+        location: Point<i32, Physical>,
+        scale: Scale<f64>,
+        alpha: f32,
+    ) -> Vec<C> {
+        let mut elements = Vec::with_capacity(2);
+
+        if let Some((image, mode)) = self.image.as_ref() {
+            let (src, dst) = background_geometry(self.output_size, image.logical_size(), *mode);
+            if !dst.is_empty() {
+                match MemoryRenderBufferRenderElement::from_buffer(
+                    renderer,
+                    (location + dst.loc.to_physical_precise_round(scale)).to_f64(),
+                    image,
+                    Some(alpha),
+                    Some(src),
+                    Some(dst.size),
+                    Kind::Unspecified,
+                ) {
+                    Ok(element) => elements.push(BackgroundRenderElement::Image(element).into()),
+                    Err(err) => warn!("Failed to import background image: {}", err),
+                }
+            }
+        }
+
+        if !self.output_size.is_empty() {
+            elements.push(
+                BackgroundRenderElement::Solid(SolidColorRenderElement::from_buffer(
+                    &self.color,
+                    location,
+                    scale,
+                    alpha,
+                    Kind::Unspecified,
+                ))
+                .into(),
+            );
+        }
+
+        elements
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn background_modes() {
+        let output = Size::from((1920, 1080));
+        let full_output = Rectangle::from_loc_and_size((0, 0), output);
+
+        let (src, dst) = background_geometry(output, (1000, 1000).into(), BackgroundMode::Stretch);
+        assert_eq!(src, Rectangle::from_loc_and_size((0.0, 0.0), (1000.0, 1000.0)));
+        assert_eq!(dst, full_output);
+
+        let (src, dst) = background_geometry(output, (960, 1080).into(), BackgroundMode::Fill);
+        assert_eq!(src, Rectangle::from_loc_and_size((0.0, 270.0), (960.0, 540.0)));
+        assert_eq!(dst, full_output);
+
+        let (src, dst) = background_geometry(output, (960, 1080).into(), BackgroundMode::Fit);
+        assert_eq!(src, Rectangle::from_loc_and_size((0.0, 0.0), (960.0, 1080.0)));
+        assert_eq!(dst, Rectangle::from_loc_and_size((480, 0), (960, 1080)));
+
+        let (src, dst) = background_geometry(output, (2000, 500).into(), BackgroundMode::Center);
+        assert_eq!(src, Rectangle::from_loc_and_size((40.0, 0.0), (1920.0, 500.0)));
+        assert_eq!(dst, Rectangle::from_loc_and_size((0, 290), (1920, 500)));
+
+        let (_, dst) = background_geometry((0, 0).into(), (960, 1080).into(), BackgroundMode::Fill);
+        assert!(dst.is_empty());
+    }
+}
@@ -509,6 +509,12 @@ impl MemoryRenderBuffer {
         }
     }
 
+    /// Size of the buffer in logical coordinates, taking its scale and transform into account
+    pub fn logical_size(&self) -> Size<i32, Logical> {
+        let inner = self.inner.lock().unwrap();
+        inner.mem.size().to_logical(inner.scale, inner.transform)
+    }
+
     /// Render to the memory buffer
     pub fn render(&mut self) -> RenderContext<'_> {
         let guard = self.inner.lock().unwrap();
@@ -12,6 +12,7 @@
 //! - [`texture`] - Texture based render element
 //! - [`surface`] - Wayland surface render element
 //! - [`solid`] - Solid color render element
+//! - [`background`] - Static output background of a solid color and an optional image
 //!
 //! The [`render_elements!`] macro provides an easy way to aggregate multiple different [RenderElement]s
 //! into a single enum.
@@ -36,6 +37,7 @@ use super::{
     Renderer,
 };
 
+pub mod background;
 pub mod memory;
 pub mod solid;
 #[cfg(feature = "wayland_frontend")]
@@ -334,6 +336,12 @@ pub enum Kind {
     /// Not marking a cursor element as `Cursor` may result in lower performance and increased power usage.
     /// In contrast, marking elements that change frequently as `Cursor` can degrade performance significantly.
     Cursor,
+    /// The element is a good candidate for direct scan-out
+    ///
+    /// This should be used for elements that are expected to change frequently and to benefit from being
+    /// offloaded to a plane, like video or game content. Backends may reserve planes for these elements
+    /// instead of using them for other elements.
+    ScanoutCandidate,
     /// The element kind is unspecified
     #[default]
     Unspecified,
@@ -1,5 +1,7 @@
 //! Element to render a solid color
 //!
+//! [`Letterbox`] uses solid colors to fill the area around fullscreen content not covering the whole output.
+//!
 //! # How to use it
 //!
 //! ```no_run
@@ -356,3 +358,131 @@ where
         vec![SolidColorRenderElement::from_buffer(self, location, scale, alpha, Kind::Unspecified).into()]
     }
 }
+
+/// Solid color bars around fullscreen content not covering the whole output
+///
+/// If the buffer of a fullscreen surface has a different aspect ratio or is smaller than the output,
+/// [`Letterbox::update`] centers the content on the output and sizes the bars to cover the remaining
+/// area. The bars keep their ids and only increment their commit on changes, so they integrate with
+/// damage tracking and the content can still be scanned out directly on an overlay plane.
+/// The bars should be rendered below the content.
+#[derive(Debug, Clone)]
+pub struct Letterbox {
+    bars: [(Point<i32, Logical>, SolidColorBuffer); 4],
+}
+
+impl Letterbox {
+    /// Create new letterbox bars with the given color
+    pub fn new(color: impl Into<Color32F>) -> Self {
+        let color = color.into();
+        Letterbox {
+            bars: std::array::from_fn(|_| (Point::default(), SolidColorBuffer::new((0, 0), color))),
+        }
+    }
+
+    /// Set the color of the bars
+    pub fn set_color(&mut self, color: impl Into<Color32F>) {
+        let color = color.into();
+        for (_, buffer) in &mut self.bars {
+            buffer.set_color(color);
+        }
+    }
+
+    /// Update the bars for content of `content_size` on an output of `output_size`
+    ///
+    /// Returns the location of the content relative to the output. Content larger than
+    /// the output in one dimension is centered in that dimension as well.
+    pub fn update(
+        &mut self,
+        output_size: impl Into<Size<i32, Logical>>,
+        content_size: impl Into<Size<i32, Logical>>,
+    ) -> Point<i32, Logical> {
+        let (location, bars) = letterbox_geometry(output_size.into(), content_size.into());
+        for ((loc, buffer), bar) in self.bars.iter_mut().zip(bars) {
+            *loc = bar.loc;
+            buffer.resize(bar.size);
+        }
+        location
+    }
+}
+
+// content location and the top, bottom, left and right bars
+fn letterbox_geometry(
+    output_size: Size<i32, Logical>,
+    content_size: Size<i32, Logical>,
+) -> (Point<i32, Logical>, [Rectangle<i32, Logical>; 4]) {
+    let location = Point::from((
+        (output_size.w - content_size.w) / 2,
+        (output_size.h - content_size.h) / 2,
+    ));
+    let top = location.y.clamp(0, output_size.h);
+    let bottom = (location.y + content_size.h).clamp(top, output_size.h);
+    let left = location.x.clamp(0, output_size.w);
+    let right = (location.x + content_size.w).clamp(left, output_size.w);
+
+    let bars = [
+        Rectangle::from_loc_and_size((0, 0), (output_size.w, top)),
+        Rectangle::from_loc_and_size((0, bottom), (output_size.w, output_size.h - bottom)),
+        Rectangle::from_loc_and_size((0, top), (left, bottom - top)),
+        Rectangle::from_loc_and_size((right, top), (output_size.w - right, bottom - top)),
+    ];
+    (location, bars)
+}
+
+impl<R> AsRenderElements<R> for Letterbox
+where
+    R: Renderer,
+{
+    type RenderElement = SolidColorRenderElement;
+
+    fn render_elements<C: From<Self::RenderElement>>(
+        &self,
+        _renderer: &mut R,
+        location: Point<i32, Physical>,
+        scale: Scale<f64>,
+        alpha: f32,
+    ) -> Vec<C> {
+        self.bars
+            .iter()
+            .filter(|(_, buffer)| !buffer.size.is_empty())
+            .map(|(loc, buffer)| {
+                // round outwards, so no gaps are left to the content with fractional scales
+                let mut geometry =
+                    Rectangle::from_loc_and_size(*loc, buffer.size).to_physical_precise_up(scale);
+                geometry.loc += location;
+                SolidColorRenderElement::new(
+                    buffer.id.clone(),
+                    geometry,
+                    buffer.commit,
+                    buffer.color * alpha,
+                    Kind::Unspecified,
+                )
+                .into()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn letterbox() {
+        let (location, bars) = letterbox_geometry((1920, 1080).into(), (1440, 1080).into());
+        assert_eq!(location, (240, 0).into());
+        assert!(bars[0].is_empty() && bars[1].is_empty());
+        assert_eq!(bars[2], Rectangle::from_loc_and_size((0, 0), (240, 1080)));
+        assert_eq!(bars[3], Rectangle::from_loc_and_size((1680, 0), (240, 1080)));
+
+        let (location, bars) = letterbox_geometry((1920, 1080).into(), (1920, 800).into());
+        assert_eq!(location, (0, 140).into());
+        assert_eq!(bars[0], Rectangle::from_loc_and_size((0, 0), (1920, 140)));
+        assert_eq!(bars[1], Rectangle::from_loc_and_size((0, 940), (1920, 140)));
+        assert!(bars[2].is_empty() && bars[3].is_empty());
+
+        let (location, bars) = letterbox_geometry((1920, 1080).into(), (2000, 1080).into());
+        assert_eq!(location, (-40, 0).into());
+        assert!(bars.iter().all(|bar| bar.is_empty()));
+    }
+}
@@ -221,7 +221,8 @@
 
 use std::fmt;
 
-use tracing::{instrument, warn};
+use tracing::{instrument, trace, warn};
+use wayland_protocols::wp::content_type::v1::server::wp_content_type_v1;
 use wayland_server::protocol::wl_surface;
 
 use crate::{
@@ -236,6 +237,7 @@ use crate::{
     wayland::{
         alpha_modifier::AlphaModifierSurfaceCachedState,
         compositor::{self, SurfaceData, TraversalAction},
+        content_type::ContentTypeSurfaceCachedState,
     },
 };
 
@@ -347,6 +349,10 @@ impl<R: Renderer> fmt::Debug for WaylandSurfaceRenderElement<R> {
 
 impl<R: Renderer + ImportAll> WaylandSurfaceRenderElement<R> {
     /// Create a render element from a surface
+    ///
+    /// If `kind` is [`Kind::Unspecified`] and the client hints video or game content through the
+    /// [`content_type`](crate::wayland::content_type) protocol, the element is marked as
+    /// [`Kind::ScanoutCandidate`].
     #[profiling::function]
     pub fn from_surface(
         renderer: &mut R,
@@ -365,6 +371,19 @@ impl<R: Renderer + ImportAll> WaylandSurfaceRenderElement<R> {
         let mut alpha_modifier_state = states.cached_state.get::<AlphaModifierSurfaceCachedState>();
         let alpha_multiplier = alpha_modifier_state.current().multiplier_f32().unwrap_or(1.0);
 
+        let kind = if kind == Kind::Unspecified {
+            let mut content_type_state = states.cached_state.get::<ContentTypeSurfaceCachedState>();
+            match content_type_state.current().content_type() {
+                content_type @ (wp_content_type_v1::Type::Video | wp_content_type_v1::Type::Game) => {
+                    trace!(?surface, ?content_type, "surface is a scan-out candidate");
+                    Kind::ScanoutCandidate
+                }
+                _ => kind,
+            }
+        } else {
+            kind
+        };
+
         let Some(data_ref) = states.data_map.get::<RendererSurfaceStateUserData>() else {
             return Ok(None);
         };
@@ -0,0 +1,326 @@
+use std::collections::HashSet;
+#[cfg(feature = "wayland_frontend")]
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[cfg(feature = "wayland_frontend")]
+use wayland_server::protocol::wl_surface::WlSurface;
+
+#[cfg(feature = "wayland_frontend")]
+use crate::wayland::compositor::{with_states, with_surface_tree_downward, SurfaceData, TraversalAction};
+use crate::{
+    backend::renderer::{
+        element::{Element, Id, Kind, RenderElement, UnderlyingStorage},
+        utils::{CommitCounter, DamageSet, OpaqueRegions},
+        Color32F, Frame, Renderer,
+    },
+    utils::{Buffer, Physical, Point, Rectangle, Scale, Transform},
+};
+
+/// Filter for the render elements of a single capture session
+///
+/// Screencopy- and screencast-implementations usually render the same elements as the
+/// output being captured. A [`CaptureFilter`] allows to exclude individual elements from
+/// the captured frames, e.g. password prompts or windows marked as secure, independent of
+/// the protocol used for capturing.
+///
+/// Instead of excluding an element completely, it can also be obscured, which replaces its
+/// contents by black in the captured frames, see [`CaptureFilter::capture_elements`].
+/// Surfaces flagged via [`set_obscured_from_capture`] can be obscured by calling
+/// [`CaptureFilter::obscure_flagged_surfaces`] for every surface tree being captured.
+///
+/// Overlays, like recording indicators or watermarks, can be added by the compositor
+/// by chaining its own elements in front of the filtered elements.
+#[derive(Debug, Default, Clone)]
+pub struct CaptureFilter {
+    excluded: HashSet<Id>,
+    obscured: HashSet<Id>,
+}
+
+impl CaptureFilter {
+    /// Create a new filter not excluding any elements
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Exclude the element with the given id from the capture
+    pub fn exclude(&mut self, id: impl Into<Id>) {
+        self.excluded.insert(id.into());
+    }
+
+    /// Stop excluding the element with the given id from the capture
+    pub fn include(&mut self, id: impl Into<Id>) {
+        self.excluded.remove(&id.into());
+    }
+
+    /// Obscure the element with the given id in the capture
+    pub fn obscure(&mut self, id: impl Into<Id>) {
+        self.obscured.insert(id.into());
+    }
+
+    /// Stop obscuring the element with the given id in the capture
+    pub fn reveal(&mut self, id: impl Into<Id>) {
+        self.obscured.remove(&id.into());
+    }
+
+    /// Obscure all surfaces of a surface tree flagged via [`set_obscured_from_capture`]
+    ///
+    /// A flag applies to the surface and all of its subsurfaces.
+    /// Surfaces not flagged (anymore) are revealed again.
+    #[cfg(feature = "wayland_frontend")]
+    pub fn obscure_flagged_surfaces(&mut self, surface: &WlSurface) {
+        with_surface_tree_downward(
+            surface,
+            false,
+            |_, states, parent_obscured| TraversalAction::DoChildren(*parent_obscured || is_flagged(states)),
+            |surface, states, parent_obscured| {
+                let id = Id::from_wayland_resource(surface);
+                if *parent_obscured || is_flagged(states) {
+                    self.obscured.insert(id);
+                } else {
+                    self.obscured.remove(&id);
+                }
+            },
+            |_, _, _| true,
+        );
+    }
+
+    /// Exclude all surfaces of a surface tree from the capture
+    ///
+    /// Only the surfaces currently part of the tree are excluded, so this
+    /// should be called again if subsurfaces are added to the tree.
+    /// Popups are not part of the surface tree and have to be excluded separately.
+    #[cfg(feature = "wayland_frontend")]
+    pub fn exclude_surface_tree(&mut self, surface: &WlSurface) {
+        with_surface_tree_downward(
+            surface,
+            (),
+            |_, _, _| TraversalAction::DoChildren(()),
+            |surface, _, _| {
+                self.excluded.insert(Id::from_wayland_resource(surface));
+            },
+            |_, _, _| true,
+        );
+    }
+
+    /// Stop excluding all surfaces of a surface tree from the capture
+    #[cfg(feature = "wayland_frontend")]
+    pub fn include_surface_tree(&mut self, surface: &WlSurface) {
+        with_surface_tree_downward(
+            surface,
+            (),
+            |_, _, _| TraversalAction::DoChildren(()),
+            |surface, _, _| {
+                self.excluded.remove(&Id::from_wayland_resource(surface));
+            },
+            |_, _, _| true,
+        );
+    }
+
+    /// Remove all exclusions and obscured elements
+    pub fn clear(&mut self) {
+        self.excluded.clear();
+        self.obscured.clear();
+    }
+
+    /// Returns `true` if the element with the given id is excluded from the capture
+    pub fn is_excluded(&self, id: &Id) -> bool {
+        self.excluded.contains(id)
+    }
+
+    /// Returns `true` if the element with the given id is obscured in the capture
+    pub fn is_obscured(&self, id: &Id) -> bool {
+        self.obscured.contains(id)
+    }
+
+    /// Returns `true` if no elements are excluded from or obscured in the capture
+    pub fn is_empty(&self) -> bool {
+        self.excluded.is_empty() && self.obscured.is_empty()
+    }
+
+    /// Filter the elements of a frame to be captured
+    pub fn filter_elements<'a, E, I>(&'a self, elements: I) -> impl Iterator<Item = E> + 'a
+    where
+        E: Element + 'a,
+        I: IntoIterator<Item = E>,
+        I::IntoIter: 'a,
+    {
+        elements
+            .into_iter()
+            .filter(move |element| !self.is_excluded(element.id()))
+    }
+
+    /// Filter the elements of a frame to be captured and obscure elements as requested
+    pub fn capture_elements<'a, E, I>(
+        &'a self,
+        elements: I,
+    ) -> impl Iterator<Item = CaptureRenderElement<E>> + 'a
+    where
+        E: Element + 'a,
+        I: IntoIterator<Item = E>,
+        I::IntoIter: 'a,
+    {
+        self.filter_elements(elements)
+            .map(move |element| CaptureRenderElement {
+                obscured: self.is_obscured(element.id()),
+                element,
+            })
+    }
+}
+
+#[cfg(feature = "wayland_frontend")]
+#[derive(Debug, Default)]
+struct ObscuredFromCapture(AtomicBool);
+
+#[cfg(feature = "wayland_frontend")]
+fn is_flagged(states: &SurfaceData) -> bool {
+    states
+        .data_map
+        .get::<ObscuredFromCapture>()
+        .map(|flag| flag.0.load(Ordering::Relaxed))
+        .unwrap_or(false)
+}
+
+/// Flag a surface to be obscured from any capture
+///
+/// The flag is not enforced by itself, but applied to capture sessions via
+/// [`CaptureFilter::obscure_flagged_surfaces`], so that screencopy, screencasts,
+/// screenshots and thumbnails can share the same policy.
+#[cfg(feature = "wayland_frontend")]
+pub fn set_obscured_from_capture(surface: &WlSurface, obscured: bool) {
+    with_states(surface, |states| {
+        states
+            .data_map
+            .insert_if_missing_threadsafe(ObscuredFromCapture::default);
+        states
+            .data_map
+            .get::<ObscuredFromCapture>()
+            .unwrap()
+            .0
+            .store(obscured, Ordering::Relaxed);
+    });
+}
+
+/// Returns `true` if the surface was flagged via [`set_obscured_from_capture`]
+///
+/// This does not take flags of parent surfaces into account.
+#[cfg(feature = "wayland_frontend")]
+pub fn is_obscured_from_capture(surface: &WlSurface) -> bool {
+    with_states(surface, is_flagged)
+}
+
+// Obscured elements are static, they report a fixed commit, so changes
+// of the obscured state can be detected by the damage tracker.
+fn obscured_commit() -> CommitCounter {
+    CommitCounter::from(usize::MAX)
+}
+
+/// A element of a captured frame, see [`CaptureFilter::capture_elements`]
+///
+/// Obscured elements are drawn solid black, covering their whole geometry.
+/// Obscuring or revealing an element damages its whole geometry.
+#[derive(Debug)]
+pub struct CaptureRenderElement<E> {
+    element: E,
+    obscured: bool,
+}
+
+impl<E> CaptureRenderElement<E> {
+    /// Returns `true` if the element is obscured
+    pub fn is_obscured(&self) -> bool {
+        self.obscured
+    }
+
+    /// Returns the wrapped element
+    pub fn into_inner(self) -> E {
+        self.element
+    }
+}
+
+impl<E: Element> Element for CaptureRenderElement<E> {
+    fn id(&self) -> &Id {
+        self.element.id()
+    }
+
+    fn current_commit(&self) -> CommitCounter {
+        if self.obscured {
+            obscured_commit()
+        } else {
+            self.element.current_commit()
+        }
+    }
+
+    fn location(&self, scale: Scale<f64>) -> Point<i32, Physical> {
+        self.element.location(scale)
+    }
+
+    fn src(&self) -> Rectangle<f64, Buffer> {
+        self.element.src()
+    }
+
+    fn transform(&self) -> Transform {
+        self.element.transform()
+    }
+
+    fn geometry(&self, scale: Scale<f64>) -> Rectangle<i32, Physical> {
+        self.element.geometry(scale)
+    }
+
+    fn damage_since(&self, scale: Scale<f64>, commit: Option<CommitCounter>) -> DamageSet<i32, Physical> {
+        let was_obscured = commit == Some(obscured_commit());
+        if self.obscured && was_obscured {
+            DamageSet::default()
+        } else if self.obscured || was_obscured {
+            let size = self.element.geometry(scale).size;
+            DamageSet::from_slice(&[Rectangle::from_loc_and_size((0, 0), size)])
+        } else {
+            self.element.damage_since(scale, commit)
+        }
+    }
+
+    fn opaque_regions(&self, scale: Scale<f64>) -> OpaqueRegions<i32, Physical> {
+        if self.obscured {
+            let size = self.element.geometry(scale).size;
+            OpaqueRegions::from_slice(&[Rectangle::from_loc_and_size((0, 0), size)])
+        } else {
+            self.element.opaque_regions(scale)
+        }
+    }
+
+    fn alpha(&self) -> f32 {
+        if self.obscured {
+            1.0
+        } else {
+            self.element.alpha()
+        }
+    }
+
+    fn kind(&self) -> Kind {
+        self.element.kind()
+    }
+}
+
+impl<R: Renderer, E: RenderElement<R>> RenderElement<R> for CaptureRenderElement<E> {
+    fn draw(
+        &self,
+        frame: &mut <R as Renderer>::Frame<'_>,
+        src: Rectangle<f64, Buffer>,
+        dst: Rectangle<i32, Physical>,
+        damage: &[Rectangle<i32, Physical>],
+        opaque_regions: &[Rectangle<i32, Physical>],
+    ) -> Result<(), <R as Renderer>::Error> {
+        if self.obscured {
+            frame.draw_solid(dst, damage, Color32F::BLACK)
+        } else {
+            self.element.draw(frame, src, dst, damage, opaque_regions)
+        }
+    }
+
+    #[inline]
+    fn underlying_storage(&self, renderer: &mut R) -> Option<UnderlyingStorage<'_>> {
+        if self.obscured {
+            None
+        } else {
+            self.element.underlying_storage(renderer)
+        }
+    }
+}
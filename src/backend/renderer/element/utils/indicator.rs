@@ -0,0 +1,186 @@
+use std::time::Duration;
+
+use crate::{
+    backend::renderer::{
+        element::{solid::SolidColorRenderElement, Id, Kind},
+        utils::CommitCounter,
+        Color32F,
+    },
+    utils::{Logical, Monotonic, Physical, Point, Rectangle, Scale, Size, Time},
+};
+
+use super::CaptureFilter;
+
+/// Built-in visual feedback for capture sessions
+///
+/// Provides a flash overlay covering the whole output after a screenshot was taken
+/// and a recording indicator shown while at least one capture session is active.
+/// Both are plain [`SolidColorRenderElement`]s, carrying their own damage, so they
+/// can be placed in front of the other elements of an output.
+///
+/// The indicator is driven by the capture implementation of the compositor via
+/// [`CaptureIndicator::session_started`], [`CaptureIndicator::session_stopped`]
+/// and [`CaptureIndicator::flash`]. Colors, durations and geometry can be styled freely.
+/// To keep the indicator out of the captured frames, use [`CaptureIndicator::exclude_from`].
+#[derive(Debug)]
+pub struct CaptureIndicator {
+    flash_id: Id,
+    flash_commit: CommitCounter,
+    flash_color: Color32F,
+    flash_duration: Duration,
+    flash_started: Option<Time<Monotonic>>,
+    flash_alpha: f32,
+
+    dot_id: Id,
+    dot_commit: CommitCounter,
+    dot_color: Color32F,
+    dot_size: i32,
+    dot_margin: i32,
+    active_sessions: usize,
+}
+
+impl Default for CaptureIndicator {
+    fn default() -> Self {
+        Self {
+            flash_id: Id::new(),
+            flash_commit: CommitCounter::default(),
+            flash_color: Color32F::new(0.6, 0.6, 0.6, 0.6),
+            flash_duration: Duration::from_millis(250),
+            flash_started: None,
+            flash_alpha: 0.0,
+
+            dot_id: Id::new(),
+            dot_commit: CommitCounter::default(),
+            dot_color: Color32F::new(0.9, 0.1, 0.1, 1.0),
+            dot_size: 12,
+            dot_margin: 8,
+            active_sessions: 0,
+        }
+    }
+}
+
+impl CaptureIndicator {
+    /// Create a new indicator with the default style
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the (pre-multiplied) color of the screenshot flash at the start of the flash
+    ///
+    /// The flash fades out linearly over its duration.
+    pub fn set_flash_color(&mut self, color: impl Into<Color32F>) {
+        self.flash_color = color.into();
+        self.flash_commit.increment();
+    }
+
+    /// Set the duration of the screenshot flash
+    pub fn set_flash_duration(&mut self, duration: Duration) {
+        self.flash_duration = duration;
+    }
+
+    /// Set the (pre-multiplied) color of the recording indicator
+    pub fn set_recording_color(&mut self, color: impl Into<Color32F>) {
+        self.dot_color = color.into();
+        self.dot_commit.increment();
+    }
+
+    /// Set the size of the recording indicator and its distance to the top-right corner of the output
+    pub fn set_recording_geometry(&mut self, size: i32, margin: i32) {
+        self.dot_size = size;
+        self.dot_margin = margin;
+        self.dot_commit.increment();
+    }
+
+    /// Start a flash, e.g. after a screenshot was taken
+    pub fn flash(&mut self, now: Time<Monotonic>) {
+        self.flash_started = Some(now);
+    }
+
+    /// Record the start of a capture session
+    pub fn session_started(&mut self) {
+        self.active_sessions += 1;
+    }
+
+    /// Record the end of a capture session
+    pub fn session_stopped(&mut self) {
+        self.active_sessions = self.active_sessions.saturating_sub(1);
+    }
+
+    /// Returns `true` if at least one capture session is active
+    pub fn is_recording(&self) -> bool {
+        self.active_sessions > 0
+    }
+
+    /// Returns `true` if a flash is still in progress at `now`
+    ///
+    /// Compositors should keep scheduling frames while this returns `true`.
+    pub fn is_animating(&self, now: Time<Monotonic>) -> bool {
+        self.flash_started
+            .map(|started| Time::elapsed(&started, now) < self.flash_duration)
+            .unwrap_or(false)
+    }
+
+    /// Exclude the elements of this indicator from the given capture
+    pub fn exclude_from(&self, filter: &mut CaptureFilter) {
+        filter.exclude(self.flash_id.clone());
+        filter.exclude(self.dot_id.clone());
+    }
+
+    /// Returns the elements to render on an output of the given size at `now`
+    ///
+    /// The elements are positioned relative to the origin of the output.
+    pub fn render_elements(
+        &mut self,
+        output_size: Size<i32, Logical>,
+        scale: impl Into<Scale<f64>>,
+        now: Time<Monotonic>,
+    ) -> Vec<SolidColorRenderElement> {
+        let scale = scale.into();
+        let mut elements = Vec::with_capacity(2);
+
+        if self.is_recording() {
+            let size = Size::<i32, Logical>::from((self.dot_size, self.dot_size));
+            let loc = Point::<i32, Logical>::from((
+                output_size.w - self.dot_margin - self.dot_size,
+                self.dot_margin,
+            ));
+            let geometry: Rectangle<i32, Physical> =
+                Rectangle::from_loc_and_size(loc, size).to_physical_precise_round(scale);
+            elements.push(SolidColorRenderElement::new(
+                self.dot_id.clone(),
+                geometry,
+                self.dot_commit,
+                self.dot_color,
+                Kind::Unspecified,
+            ));
+        }
+
+        let progress = self.flash_started.map(|started| {
+            Time::elapsed(&started, now).as_secs_f32() / self.flash_duration.as_secs_f32().max(f32::EPSILON)
+        });
+        let alpha = match progress {
+            Some(progress) if progress < 1.0 => 1.0 - progress,
+            _ => {
+                self.flash_started = None;
+                0.0
+            }
+        };
+        if alpha != self.flash_alpha {
+            self.flash_alpha = alpha;
+            self.flash_commit.increment();
+        }
+        if alpha > 0.0 {
+            let geometry: Rectangle<i32, Physical> =
+                Rectangle::from_loc_and_size((0, 0), output_size).to_physical_precise_round(scale);
+            elements.push(SolidColorRenderElement::new(
+                self.flash_id.clone(),
+                geometry,
+                self.flash_commit,
+                self.flash_color * alpha,
+                Kind::Unspecified,
+            ));
+        }
+
+        elements
+    }
+}
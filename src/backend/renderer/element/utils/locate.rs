@@ -0,0 +1,193 @@
+use std::time::Duration;
+
+use crate::{
+    backend::{
+        input::KeyState,
+        renderer::{
+            element::{solid::SolidColorRenderElement, Id, Kind},
+            utils::CommitCounter,
+            Color32F,
+        },
+    },
+    input::keyboard::Keysym,
+    utils::{Logical, Monotonic, Physical, Point, Rectangle, Scale, Size, Time},
+};
+
+/// Accessibility helper highlighting the pointer location
+///
+/// Once triggered, a frame contracting towards the pointer location is shown,
+/// making it easy to find a small cursor on large or multiple outputs.
+/// The highlight is made of plain [`SolidColorRenderElement`]s, carrying their own damage,
+/// which should be placed in front of the other elements of an output.
+///
+/// By default the highlight is triggered by tapping a Control key without pressing
+/// any other key in between, which can be detected by passing all keys through
+/// [`LocatePointer::filter_key`], e.g. from the filter of [`KeyboardHandle::input`](crate::input::keyboard::KeyboardHandle::input).
+#[derive(Debug)]
+pub struct LocatePointer {
+    ids: [Id; 4],
+    commit: CommitCounter,
+    color: Color32F,
+    duration: Duration,
+    radius: f64,
+    thickness: i32,
+    trigger_keys: Vec<Keysym>,
+
+    started: Option<Time<Monotonic>>,
+    last_progress: Option<f64>,
+    pending_tap: Option<Keysym>,
+}
+
+impl Default for LocatePointer {
+    fn default() -> Self {
+        Self {
+            ids: [Id::new(), Id::new(), Id::new(), Id::new()],
+            commit: CommitCounter::default(),
+            color: Color32F::new(0.2, 0.5, 0.9, 0.9),
+            duration: Duration::from_millis(500),
+            radius: 120.0,
+            thickness: 4,
+            trigger_keys: vec![Keysym::Control_L, Keysym::Control_R],
+
+            started: None,
+            last_progress: None,
+            pending_tap: None,
+        }
+    }
+}
+
+impl LocatePointer {
+    /// Create a new helper with the default style, triggered by tapping Control
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the (pre-multiplied) color of the highlight
+    pub fn set_color(&mut self, color: impl Into<Color32F>) {
+        self.color = color.into();
+        self.commit.increment();
+    }
+
+    /// Set the duration of the highlight animation
+    pub fn set_duration(&mut self, duration: Duration) {
+        self.duration = duration;
+    }
+
+    /// Set the initial distance of the highlight to the pointer and its line thickness
+    pub fn set_geometry(&mut self, radius: f64, thickness: i32) {
+        self.radius = radius;
+        self.thickness = thickness;
+        self.commit.increment();
+    }
+
+    /// Set the keys triggering the highlight when tapped
+    ///
+    /// An empty list disables the trigger via [`LocatePointer::filter_key`].
+    pub fn set_trigger_keys(&mut self, keys: impl IntoIterator<Item = Keysym>) {
+        self.trigger_keys = keys.into_iter().collect();
+        self.pending_tap = None;
+    }
+
+    /// Start the highlight at `now`
+    pub fn trigger(&mut self, now: Time<Monotonic>) {
+        self.started = Some(now);
+    }
+
+    /// Track a key event to detect taps of the trigger keys
+    ///
+    /// Returns `true` if the highlight was triggered by this event. The key event
+    /// itself should still be forwarded to clients, as the trigger keys usually are modifiers.
+    pub fn filter_key(&mut self, keysym: Keysym, state: KeyState, now: Time<Monotonic>) -> bool {
+        match state {
+            KeyState::Pressed if self.trigger_keys.contains(&keysym) => {
+                self.pending_tap = Some(keysym);
+                false
+            }
+            KeyState::Pressed => {
+                self.pending_tap = None;
+                false
+            }
+            KeyState::Released if self.pending_tap == Some(keysym) => {
+                self.pending_tap = None;
+                self.trigger(now);
+                true
+            }
+            KeyState::Released => false,
+        }
+    }
+
+    /// Cancel a pending tap of a trigger key
+    ///
+    /// This should be called when pointer buttons are pressed or scrolled,
+    /// as the trigger keys are usually used as modifiers for those.
+    pub fn cancel_tap(&mut self) {
+        self.pending_tap = None;
+    }
+
+    /// Returns `true` if the highlight is still in progress at `now`
+    ///
+    /// Compositors should keep scheduling frames while this returns `true`.
+    pub fn is_animating(&self, now: Time<Monotonic>) -> bool {
+        self.started
+            .map(|started| Time::elapsed(&started, now) < self.duration)
+            .unwrap_or(false)
+    }
+
+    /// Returns the elements to render for a pointer at `location` at `now`
+    ///
+    /// The location is expected to be relative to the origin of the output
+    /// the elements are rendered on.
+    pub fn render_elements(
+        &mut self,
+        location: Point<f64, Logical>,
+        scale: impl Into<Scale<f64>>,
+        now: Time<Monotonic>,
+    ) -> Vec<SolidColorRenderElement> {
+        let progress = self.started.map(|started| {
+            Time::elapsed(&started, now).as_secs_f64() / self.duration.as_secs_f64().max(f64::EPSILON)
+        });
+        let progress = match progress {
+            Some(progress) if progress < 1.0 => progress,
+            _ => {
+                self.started = None;
+                self.last_progress = None;
+                return Vec::new();
+            }
+        };
+        if self.last_progress != Some(progress) {
+            self.last_progress = Some(progress);
+            self.commit.increment();
+        }
+
+        let scale = scale.into();
+        // ease-out, contracting towards the pointer and fading out at the end
+        let radius = self.radius * (1.0 - progress).powi(2) + self.thickness as f64;
+        let alpha = (1.0 - progress).sqrt() as f32;
+        let color = self.color * alpha;
+
+        let outer = Rectangle::<f64, Logical>::from_loc_and_size(
+            location - Point::from((radius, radius)),
+            Size::from((radius * 2.0, radius * 2.0)),
+        )
+        .to_i32_round::<i32>();
+        let t = self.thickness;
+        let sides = [
+            Rectangle::from_loc_and_size(outer.loc, (outer.size.w, t)),
+            Rectangle::from_loc_and_size((outer.loc.x, outer.loc.y + outer.size.h - t), (outer.size.w, t)),
+            Rectangle::from_loc_and_size((outer.loc.x, outer.loc.y + t), (t, outer.size.h - 2 * t)),
+            Rectangle::from_loc_and_size(
+                (outer.loc.x + outer.size.w - t, outer.loc.y + t),
+                (t, outer.size.h - 2 * t),
+            ),
+        ];
+
+        self.ids
+            .iter()
+            .zip(sides)
+            .map(|(id, side)| {
+                let geometry: Rectangle<i32, Physical> = side.to_physical_precise_round(scale);
+                SolidColorRenderElement::new(id.clone(), geometry, self.commit, color, Kind::Unspecified)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,246 @@
+use std::time::Duration;
+
+use crate::{
+    backend::renderer::element::Element,
+    utils::{Logical, Monotonic, Physical, Point, Rectangle, Scale, Size, Time},
+};
+
+use super::{CropRenderElement, RescaleRenderElement};
+
+/// Element type returned by [`Magnifier::render_elements`]
+pub type MagnifierRenderElement<E> = CropRenderElement<RescaleRenderElement<E>>;
+
+#[derive(Debug, Clone, Copy)]
+struct ZoomAnimation {
+    from: f64,
+    started: Time<Monotonic>,
+}
+
+/// Output-level magnification
+///
+/// A [`Magnifier`] scales up all elements of an output around a focal point, usually
+/// the pointer location or the location of the keyboard focus. The viewport is panned
+/// proportionally, so the focal point stays at the same position on the output while
+/// the content around it is magnified. This keeps the (equally magnified) cursor right
+/// at the actual pointer location and needs no offscreen rendering.
+///
+/// Changes of the zoom level are animated. Compositors should keep scheduling frames for
+/// the output while [`Magnifier::is_animating`] returns `true`.
+#[derive(Debug)]
+pub struct Magnifier {
+    level: f64,
+    target: f64,
+    animation: Option<ZoomAnimation>,
+    duration: Duration,
+    focus: Point<f64, Logical>,
+}
+
+impl Default for Magnifier {
+    fn default() -> Self {
+        Self {
+            level: 1.0,
+            target: 1.0,
+            animation: None,
+            duration: Duration::from_millis(150),
+            focus: Point::default(),
+        }
+    }
+}
+
+impl Magnifier {
+    /// Create a new magnifier, that is not zoomed in
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the duration of zoom animations
+    ///
+    /// A duration of zero disables animations.
+    pub fn set_animation_duration(&mut self, duration: Duration) {
+        self.duration = duration;
+        if duration.is_zero() {
+            self.animation = None;
+        }
+    }
+
+    /// Zoom to the given level, starting at `now`
+    ///
+    /// A level of `1.0` disables the magnification, levels below `1.0` are clamped.
+    pub fn set_zoom(&mut self, level: f64, now: Time<Monotonic>) {
+        let level = level.max(1.0);
+        if level == self.target {
+            return;
+        }
+
+        self.update(now);
+        self.target = level;
+        self.animation = (!self.duration.is_zero()).then_some(ZoomAnimation {
+            from: self.level,
+            started: now,
+        });
+        if self.animation.is_none() {
+            self.level = level;
+        }
+    }
+
+    /// Returns the zoom level the magnifier is set to
+    ///
+    /// While animating, this differs from the level currently rendered, see [`Magnifier::zoom`].
+    pub fn target_zoom(&self) -> f64 {
+        self.target
+    }
+
+    /// Returns the zoom level at `now`
+    pub fn zoom(&mut self, now: Time<Monotonic>) -> f64 {
+        self.update(now);
+        self.level
+    }
+
+    /// Set the focal point to magnify around, relative to the output origin
+    pub fn set_focus(&mut self, focus: impl Into<Point<f64, Logical>>) {
+        self.focus = focus.into();
+    }
+
+    /// Returns the focal point of the magnifier
+    pub fn focus(&self) -> Point<f64, Logical> {
+        self.focus
+    }
+
+    /// Returns `true` if the output is currently magnified or a zoom animation is running
+    pub fn is_active(&self) -> bool {
+        self.level != 1.0 || self.target != 1.0
+    }
+
+    /// Returns `true` if a zoom animation is still in progress at `now`
+    pub fn is_animating(&mut self, now: Time<Monotonic>) -> bool {
+        self.update(now);
+        self.animation.is_some()
+    }
+
+    /// Returns the part of the output visible at `now`, relative to the output origin
+    pub fn viewport(
+        &mut self,
+        output_size: Size<f64, Logical>,
+        now: Time<Monotonic>,
+    ) -> Rectangle<f64, Logical> {
+        let level = self.zoom(now);
+        let focus = self.clamped_focus(output_size);
+        let size = output_size.downscale(level);
+        let loc = focus - focus.downscale(level);
+        Rectangle::from_loc_and_size(loc, size)
+    }
+
+    /// Magnify the elements of an output
+    ///
+    /// The elements are expected to be positioned relative to the output origin,
+    /// as they would be passed to the damage tracker of the output. Elements
+    /// outside of the visible viewport are dropped.
+    pub fn render_elements<E, I>(
+        &mut self,
+        elements: I,
+        output_size: Size<i32, Physical>,
+        scale: impl Into<Scale<f64>>,
+        now: Time<Monotonic>,
+    ) -> Vec<MagnifierRenderElement<E>>
+    where
+        E: Element,
+        I: IntoIterator<Item = E>,
+    {
+        let scale = scale.into();
+        let level = self.zoom(now);
+        let logical_size = output_size.to_f64().to_logical(scale);
+        let origin = self.clamped_focus(logical_size).to_physical_precise_round(scale);
+        let output_rect = Rectangle::from_loc_and_size((0, 0), output_size);
+
+        // Scaling relative to the focal point keeps it in place, which results in
+        // a viewport panned proportionally to the position of the focal point.
+        elements
+            .into_iter()
+            .filter_map(|element| {
+                let element = RescaleRenderElement::from_element(element, origin, level);
+                CropRenderElement::from_element(element, scale, output_rect)
+            })
+            .collect()
+    }
+
+    fn clamped_focus(&self, output_size: Size<f64, Logical>) -> Point<f64, Logical> {
+        Point::from((
+            self.focus.x.clamp(0.0, output_size.w),
+            self.focus.y.clamp(0.0, output_size.h),
+        ))
+    }
+
+    fn update(&mut self, now: Time<Monotonic>) {
+        let Some(animation) = self.animation else {
+            self.level = self.target;
+            return;
+        };
+
+        let progress = Time::elapsed(&animation.started, now).as_secs_f64() / self.duration.as_secs_f64();
+        if progress >= 1.0 {
+            self.level = self.target;
+            self.animation = None;
+        } else {
+            // ease-out cubic
+            let t = 1.0 - (1.0 - progress).powi(3);
+            self.level = animation.from + (self.target - animation.from) * t;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn viewport() {
+        let now = Time::<Monotonic>::from(Duration::from_secs(1));
+        let output = Size::from((1920.0, 1080.0));
+        let mut magnifier = Magnifier::new();
+        magnifier.set_animation_duration(Duration::ZERO);
+
+        magnifier.set_focus((480.0, 270.0));
+        assert_eq!(
+            magnifier.viewport(output, now),
+            Rectangle::from_loc_and_size((0.0, 0.0), output)
+        );
+
+        // the focal point stays at the same position on the output
+        magnifier.set_zoom(2.0, now);
+        assert_eq!(
+            magnifier.viewport(output, now),
+            Rectangle::from_loc_and_size((240.0, 135.0), (960.0, 540.0))
+        );
+
+        // focal points outside of the output are clamped to its edges
+        magnifier.set_focus((-100.0, 2000.0));
+        assert_eq!(
+            magnifier.viewport(output, now),
+            Rectangle::from_loc_and_size((0.0, 540.0), (960.0, 540.0))
+        );
+
+        magnifier.set_zoom(0.5, now);
+        assert_eq!(magnifier.zoom(now), 1.0);
+        assert!(!magnifier.is_active());
+    }
+
+    #[test]
+    fn zoom_animation() {
+        let start = Time::<Monotonic>::from(Duration::from_secs(1));
+        let mut magnifier = Magnifier::new();
+        magnifier.set_animation_duration(Duration::from_millis(100));
+
+        magnifier.set_zoom(3.0, start);
+        assert_eq!(magnifier.zoom(start), 1.0);
+        assert_eq!(magnifier.target_zoom(), 3.0);
+
+        let halfway = Time::from(Duration::from_millis(1050));
+        let level = magnifier.zoom(halfway);
+        assert!(level > 2.0 && level < 3.0);
+        assert!(magnifier.is_animating(halfway));
+
+        let end = Time::from(Duration::from_millis(1100));
+        assert_eq!(magnifier.zoom(end), 3.0);
+        assert!(!magnifier.is_animating(end));
+    }
+}
@@ -1,9 +1,17 @@
 //! Utilities and helpers around the `Element` trait.
 
+mod capture;
 mod elements;
+mod indicator;
+mod locate;
+mod magnifier;
 #[cfg(feature = "wayland_frontend")]
 mod wayland;
 
+pub use capture::*;
 pub use elements::*;
+pub use indicator::*;
+pub use locate::*;
+pub use magnifier::*;
 #[cfg(feature = "wayland_frontend")]
 pub use wayland::*;
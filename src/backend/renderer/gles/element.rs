@@ -5,10 +5,15 @@ use crate::{
         element::{texture::TextureRenderElement, Element, Id, Kind, RenderElement, UnderlyingStorage},
         utils::{CommitCounter, DamageSet, OpaqueRegions},
     },
-    utils::{Buffer, Logical, Physical, Point, Rectangle, Scale, Transform},
+    utils::{Buffer, Logical, Monotonic, Physical, Point, Rectangle, Scale, Time, Transform},
 };
 
-use super::{GlesError, GlesFrame, GlesPixelProgram, GlesRenderer, GlesTexProgram, GlesTexture, Uniform};
+use super::{
+    GlesError, GlesFrame, GlesPixelProgram, GlesRenderer, GlesTexProgram, GlesTexture, Uniform, UniformName,
+    UniformType, UniformValue, COLOR_FILTER_SHADER,
+};
+
+use std::time::Duration;
 
 /// Render element for drawing with a gles2 pixel shader
 #[derive(Debug, Clone)]
@@ -223,3 +228,337 @@ impl RenderElement<GlesRenderer> for TextureShaderElement {
         )
     }
 }
+
+/// Accessibility color filters applied when rendering textures
+///
+/// Filters are applied in the order grayscale, contrast, color matrix, invert.
+/// They can either be applied to individual elements using a [`ColorFilterRenderElement`]
+/// or to a whole output by passing [`ColorFilter::uniforms`] together with the program from
+/// [`ColorFilter::compile_program`] to [`GlesFrame::override_default_tex_program`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColorFilter {
+    /// Invert all colors
+    pub invert: bool,
+    /// Convert all colors to grayscale
+    pub grayscale: bool,
+    /// Contrast factor, `1.0` leaves the contrast unchanged
+    pub contrast: f32,
+    /// 3x3 matrix in row-major order applied to the rgb channels, e.g. for red-shift
+    pub matrix: [f32; 9],
+    /// Blend factor between the unfiltered (`0.0`) and the filtered (`1.0`) colors
+    ///
+    /// Mostly useful for transitions, see [`OutputColorFilter`].
+    pub strength: f32,
+}
+
+impl Default for ColorFilter {
+    fn default() -> Self {
+        Self {
+            invert: false,
+            grayscale: false,
+            contrast: 1.0,
+            matrix: Self::IDENTITY_MATRIX,
+            strength: 1.0,
+        }
+    }
+}
+
+impl ColorFilter {
+    /// Color matrix not changing any colors
+    pub const IDENTITY_MATRIX: [f32; 9] = [1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0];
+
+    /// Returns `true` if this filter does not change any colors
+    pub fn is_identity(&self) -> bool {
+        self.strength == 0.0
+            || (!self.invert
+                && !self.grayscale
+                && self.contrast == 1.0
+                && self.matrix == Self::IDENTITY_MATRIX)
+    }
+
+    /// Names and types of the additional uniforms used by [`COLOR_FILTER_SHADER`]
+    pub fn uniform_names() -> [UniformName<'static>; 5] {
+        [
+            UniformName::new("invert", UniformType::_1f),
+            UniformName::new("grayscale", UniformType::_1f),
+            UniformName::new("contrast", UniformType::_1f),
+            UniformName::new("color_matrix", UniformType::Matrix3x3),
+            UniformName::new("strength", UniformType::_1f),
+        ]
+    }
+
+    /// Compile the texture shader implementing color filters
+    ///
+    /// The program can be shared between all filters and should be cached by the compositor.
+    pub fn compile_program(renderer: &mut GlesRenderer) -> Result<GlesTexProgram, GlesError> {
+        renderer.compile_custom_texture_shader(COLOR_FILTER_SHADER, &Self::uniform_names())
+    }
+
+    /// Uniform values representing this filter
+    pub fn uniforms(&self) -> Vec<Uniform<'static>> {
+        vec![
+            Uniform::new("invert", if self.invert { 1.0f32 } else { 0.0 }),
+            Uniform::new("grayscale", if self.grayscale { 1.0f32 } else { 0.0 }),
+            Uniform::new("contrast", self.contrast),
+            Uniform::new(
+                "color_matrix",
+                UniformValue::Matrix3x3 {
+                    // gles2 does not support transposing, so upload in column major order
+                    matrices: vec![[
+                        self.matrix[0],
+                        self.matrix[3],
+                        self.matrix[6],
+                        self.matrix[1],
+                        self.matrix[4],
+                        self.matrix[7],
+                        self.matrix[2],
+                        self.matrix[5],
+                        self.matrix[8],
+                    ]],
+                    transpose: false,
+                },
+            ),
+            Uniform::new("strength", self.strength.clamp(0.0, 1.0)),
+        ]
+    }
+}
+
+/// Color filter of an output, that can be toggled with a smooth transition
+///
+/// Compositors can keep one of these per output, e.g. in the user data of the output, and
+/// apply the filter returned by [`OutputColorFilter::current`] to all elements of the output via
+/// [`ColorFilterRenderElement`] or [`GlesFrame::override_default_tex_program`].
+/// Pass [`OutputColorFilter::current_commit`] to [`ColorFilterRenderElement::new`], so the elements
+/// are damaged whenever the filter changes, including every frame of a transition.
+#[derive(Debug, Clone)]
+pub struct OutputColorFilter {
+    filter: ColorFilter,
+    enabled: bool,
+    duration: Duration,
+    transition: Option<(f32, Time<Monotonic>)>,
+    strength: f32,
+    commit: CommitCounter,
+}
+
+impl OutputColorFilter {
+    /// Create a new disabled output filter applying `filter` once enabled
+    pub fn new(filter: ColorFilter) -> Self {
+        Self {
+            filter,
+            enabled: false,
+            duration: Duration::from_millis(300),
+            transition: None,
+            strength: 0.0,
+            commit: CommitCounter::default(),
+        }
+    }
+
+    /// Set the duration of transitions when enabling or disabling the filter
+    pub fn set_transition_duration(&mut self, duration: Duration) {
+        self.duration = duration;
+    }
+
+    /// Replace the filter applied, without a transition
+    pub fn set_filter(&mut self, filter: ColorFilter) {
+        self.filter = filter;
+        self.commit.increment();
+    }
+
+    /// Returns the filter applied when enabled
+    pub fn filter(&self) -> &ColorFilter {
+        &self.filter
+    }
+
+    /// Enable or disable the filter, starting a transition at `now`
+    pub fn set_enabled(&mut self, enabled: bool, now: Time<Monotonic>) {
+        if enabled == self.enabled {
+            return;
+        }
+
+        let from = self.strength(now);
+        self.enabled = enabled;
+        self.transition = (!self.duration.is_zero()).then_some((from, now));
+        self.commit.increment();
+    }
+
+    /// Returns if the filter is enabled
+    ///
+    /// This is `true` for the whole transition when enabling the filter.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Returns `true` if a transition is still in progress at `now`
+    pub fn is_animating(&self, now: Time<Monotonic>) -> bool {
+        self.transition
+            .map(|(_, started)| Time::elapsed(&started, now) < self.duration)
+            .unwrap_or(false)
+    }
+
+    /// Returns the filter to apply at `now`
+    pub fn current(&self, now: Time<Monotonic>) -> ColorFilter {
+        ColorFilter {
+            strength: self.filter.strength * self.strength(now),
+            ..self.filter
+        }
+    }
+
+    /// Returns the commit of the filter applied at `now`
+    ///
+    /// The commit changes whenever the filter is replaced or toggled and during a transition,
+    /// whenever the strength at `now` differs from the previous call.
+    pub fn current_commit(&mut self, now: Time<Monotonic>) -> CommitCounter {
+        let strength = self.strength(now);
+        if strength != self.strength {
+            self.strength = strength;
+            self.commit.increment();
+        }
+        self.commit
+    }
+
+    fn strength(&self, now: Time<Monotonic>) -> f32 {
+        let target = if self.enabled { 1.0 } else { 0.0 };
+        let Some((from, started)) = self.transition else {
+            return target;
+        };
+
+        let progress =
+            Time::elapsed(&started, now).as_secs_f32() / self.duration.as_secs_f32().max(f32::EPSILON);
+        if progress >= 1.0 {
+            target
+        } else {
+            from + (target - from) * progress
+        }
+    }
+}
+
+/// Render element applying a [`ColorFilter`] to all textures drawn by the wrapped element
+///
+/// This overrides the default texture program of the frame while drawing the wrapped
+/// element, so it works for any element drawing textures without a custom program,
+/// like surfaces of a window. Any override previously set on the frame is cleared.
+///
+/// The element is fully damaged whenever the commit of the filter passed to
+/// [`ColorFilterRenderElement::new`] changes, so it has to be incremented with every change of the filter.
+#[derive(Debug)]
+pub struct ColorFilterRenderElement<E> {
+    element: E,
+    program: GlesTexProgram,
+    filter: ColorFilter,
+    filter_commit: CommitCounter,
+}
+
+// The commit of a `ColorFilterRenderElement` packs the commit of the filter into the upper half
+// and the commit of the wrapped element into the lower half. Commits of the wrapped element
+// exceeding the lower half only result in additional damage, as the truncated commit is older.
+const FILTER_COMMIT_SHIFT: u32 = usize::BITS / 2;
+const ELEMENT_COMMIT_MASK: usize = (1 << FILTER_COMMIT_SHIFT) - 1;
+
+impl<E> ColorFilterRenderElement<E> {
+    /// Wrap an element, applying `filter` using a `program` obtained from [`ColorFilter::compile_program`]
+    ///
+    /// `filter_commit` has to change whenever `filter` changes.
+    pub fn new(
+        element: E,
+        program: GlesTexProgram,
+        filter: ColorFilter,
+        filter_commit: CommitCounter,
+    ) -> Self {
+        Self {
+            element,
+            program,
+            filter,
+            filter_commit,
+        }
+    }
+
+    /// Returns the filter applied to the wrapped element
+    pub fn filter(&self) -> &ColorFilter {
+        &self.filter
+    }
+}
+
+impl<E: Element> Element for ColorFilterRenderElement<E> {
+    fn id(&self) -> &Id {
+        self.element.id()
+    }
+
+    fn current_commit(&self) -> CommitCounter {
+        CommitCounter::from(
+            (self.filter_commit.value() << FILTER_COMMIT_SHIFT)
+                | (self.element.current_commit().value() & ELEMENT_COMMIT_MASK),
+        )
+    }
+
+    fn location(&self, scale: Scale<f64>) -> Point<i32, Physical> {
+        self.element.location(scale)
+    }
+
+    fn src(&self) -> Rectangle<f64, Buffer> {
+        self.element.src()
+    }
+
+    fn transform(&self) -> Transform {
+        self.element.transform()
+    }
+
+    fn geometry(&self, scale: Scale<f64>) -> Rectangle<i32, Physical> {
+        self.element.geometry(scale)
+    }
+
+    fn damage_since(&self, scale: Scale<f64>, commit: Option<CommitCounter>) -> DamageSet<i32, Physical> {
+        let filter_commit = self.filter_commit.value() & (usize::MAX >> FILTER_COMMIT_SHIFT);
+        match commit {
+            Some(commit) if commit.value() >> FILTER_COMMIT_SHIFT == filter_commit => {
+                self.element.damage_since(
+                    scale,
+                    Some(CommitCounter::from(commit.value() & ELEMENT_COMMIT_MASK)),
+                )
+            }
+            _ => DamageSet::from_slice(&[Rectangle::from_loc_and_size((0, 0), self.geometry(scale).size)]),
+        }
+    }
+
+    fn opaque_regions(&self, scale: Scale<f64>) -> OpaqueRegions<i32, Physical> {
+        self.element.opaque_regions(scale)
+    }
+
+    fn alpha(&self) -> f32 {
+        self.element.alpha()
+    }
+
+    fn kind(&self) -> Kind {
+        self.element.kind()
+    }
+}
+
+impl<E: RenderElement<GlesRenderer>> RenderElement<GlesRenderer> for ColorFilterRenderElement<E> {
+    #[profiling::function]
+    fn draw(
+        &self,
+        frame: &mut GlesFrame<'_>,
+        src: Rectangle<f64, Buffer>,
+        dst: Rectangle<i32, Physical>,
+        damage: &[Rectangle<i32, Physical>],
+        opaque_regions: &[Rectangle<i32, Physical>],
+    ) -> Result<(), GlesError> {
+        if self.filter.is_identity() {
+            return self.element.draw(frame, src, dst, damage, opaque_regions);
+        }
+
+        frame.override_default_tex_program(self.program.clone(), self.filter.uniforms());
+        let res = self.element.draw(frame, src, dst, damage, opaque_regions);
+        frame.clear_tex_program_override();
+        res
+    }
+
+    #[inline]
+    fn underlying_storage(&self, renderer: &mut GlesRenderer) -> Option<UnderlyingStorage<'_>> {
+        // direct scanout would bypass the filter
+        if self.filter.is_identity() {
+            self.element.underlying_storage(renderer)
+        } else {
+            None
+        }
+    }
+}
@@ -673,6 +673,19 @@ impl GlesRenderer {
         }
     }
 
+    // fence the pending read-back into a pixel buffer, so mapping it can be deferred until the copy completed
+    fn readback_sync_point(&self) -> SyncPoint {
+        if self.capabilities.contains(&Capability::Fencing) {
+            if let Ok(fence) = EGLFence::create(self.egl.display()) {
+                unsafe {
+                    self.gl.Flush();
+                }
+                return SyncPoint::from(fence);
+            }
+        }
+        SyncPoint::signaled()
+    }
+
     /// Returns the supported [`Capabilities`](Capability) of this renderer.
     pub fn capabilities(&self) -> &[Capability] {
         &self.capabilities
@@ -1217,6 +1230,7 @@ impl ExportMem for GlesRenderer {
 
         match err {
             ffi::NO_ERROR => Ok(GlesMapping {
+                sync: self.readback_sync_point(),
                 pbo,
                 format,
                 layout,
@@ -1291,6 +1305,7 @@ impl ExportMem for GlesRenderer {
 
         match err {
             ffi::NO_ERROR => Ok(GlesMapping {
+                sync: self.readback_sync_point(),
                 pbo,
                 format,
                 layout,
@@ -1798,6 +1813,18 @@ impl GlesRenderer {
         &self.egl
     }
 
+    /// Drop all cached dmabuf imports of this renderer.
+    ///
+    /// Following calls to [`ImportDma::import_dmabuf`] will import the dmabufs again,
+    /// instead of re-using a previously created texture. This is useful if the gpu state
+    /// might have been lost without the dmabufs changing, e.g. after resuming from hibernation.
+    /// See also `DrmCompositor::reset_after_resume`.
+    ///
+    /// Textures still referenced elsewhere are not affected.
+    pub fn clear_dmabuf_cache(&mut self) {
+        self.dmabuf_cache.clear();
+    }
+
     /// Run custom code in the GL context owned by this renderer.
     ///
     /// The OpenGL state of the renderer is considered an implementation detail
@@ -0,0 +1,59 @@
+#version 100
+
+//_DEFINES_
+
+#if defined(EXTERNAL)
+#extension GL_OES_EGL_image_external : require
+#endif
+
+precision mediump float;
+#if defined(EXTERNAL)
+uniform samplerExternalOES tex;
+#else
+uniform sampler2D tex;
+#endif
+
+uniform float alpha;
+varying vec2 v_coords;
+
+#if defined(DEBUG_FLAGS)
+uniform float tint;
+#endif
+
+uniform float invert;
+uniform float grayscale;
+uniform float contrast;
+uniform mat3 color_matrix;
+uniform float strength;
+
+void main() {
+    vec4 color = texture2D(tex, v_coords);
+
+#if defined(NO_ALPHA)
+    color = vec4(color.rgb, 1.0);
+#endif
+
+    // filters operate on straight alpha
+    vec3 rgb = color.a > 0.0 ? color.rgb / color.a : vec3(0.0);
+    vec3 original = rgb;
+
+    if (grayscale == 1.0)
+        rgb = vec3(dot(rgb, vec3(0.2126, 0.7152, 0.0722)));
+
+    rgb = clamp((rgb - 0.5) * contrast + 0.5, 0.0, 1.0);
+    rgb = clamp(color_matrix * rgb, 0.0, 1.0);
+
+    if (invert == 1.0)
+        rgb = vec3(1.0) - rgb;
+
+    rgb = mix(original, rgb, strength);
+
+    color = vec4(rgb * color.a, color.a) * alpha;
+
+#if defined(DEBUG_FLAGS)
+    if (tint == 1.0)
+        color = vec4(0.0, 0.2, 0.0, 0.2) + color * 0.8;
+#endif
+
+    gl_FragColor = color;
+}
@@ -12,6 +12,12 @@ pub const EXTERNAL: &str = "EXTERNAL";
 /// Debug flags shader define
 pub const DEBUG_FLAGS: &str = "DEBUG_FLAGS";
 
+/// Texture shader implementing the filters of a [`ColorFilter`](super::element::ColorFilter)
+///
+/// To be compiled via [`GlesRenderer::compile_custom_texture_shader`] with the uniforms
+/// returned by [`ColorFilter::uniform_names`](super::element::ColorFilter::uniform_names).
+pub const COLOR_FILTER_SHADER: &str = include_str!("./color_filter.frag");
+
 use super::*;
 
 /// Compiles a shader variant.
@@ -105,6 +105,7 @@ pub struct GlesMapping {
     pub(super) has_alpha: bool,
     pub(super) size: Size<i32, BufferCoord>,
     pub(super) mapping: AtomicPtr<std::ffi::c_void>,
+    pub(super) sync: SyncPoint,
     pub(super) destruction_callback_sender: Sender<CleanupResource>,
 }
 
@@ -134,6 +135,9 @@ impl TextureMapping for GlesMapping {
     fn format(&self) -> Fourcc {
         Texture::format(self).expect("Should never happen")
     }
+    fn sync_point(&self) -> SyncPoint {
+        self.sync.clone()
+    }
 }
 
 impl Drop for GlesMapping {
@@ -156,6 +156,14 @@ pub trait TextureMapping: Texture {
     fn format(&self) -> Fourcc {
         Texture::format(self).expect("Texture Mappings need to have a format")
     }
+
+    /// Returns a [`SyncPoint`](sync::SyncPoint) signaled once the copy into this mapping completed
+    ///
+    /// Mapping the texture via [`ExportMem::map_texture`] before that point is reached blocks
+    /// until the copy is done. Renderers, which copy synchronously, return an already signaled sync point.
+    fn sync_point(&self) -> sync::SyncPoint {
+        sync::SyncPoint::signaled()
+    }
 }
 
 /// Helper trait for [`Renderer`], which defines a rendering api for a currently in-progress frame during [`Renderer::render`].
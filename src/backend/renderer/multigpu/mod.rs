@@ -31,6 +31,15 @@
 //! The implementation strives for the best possible performance for a given setup,
 //! when choosing a copy-path.
 //!
+//! Rendering for an output of the target-gpu happens into a buffer allocated on the render-gpu,
+//! which is shared with the target-gpu via PRIME (dmabuf export and import). Only modifiers supported
+//! by both gpus are considered for this buffer, which usually leaves just `Linear` for gpus of
+//! different vendors. If no such buffer can be created or imported on the target-gpu,
+//! the rendered image is copied through system memory instead. The target-gpu then only needs to
+//! be able to composite the shared buffer into its own scanout buffers, e.g. of a `DrmCompositor`.
+//! Whether a device supports PRIME can be checked via
+//! [`DrmCapabilities`](crate::backend::drm::DrmCapabilities).
+//!
 //! Any `ExportMem`-implementations will originate from the render-gpu, which again
 //! needs to support the requested format directly. No paths across other gpus are tested.
 //!
@@ -2587,6 +2596,13 @@ where
             MultiTextureMapping::<A, B>(TextureMappingInternal::Or(x)) => TextureMapping::format(x),
         }
     }
+
+    fn sync_point(&self) -> SyncPoint {
+        match self {
+            MultiTextureMapping::<A, B>(TextureMappingInternal::Either(x)) => x.sync_point(),
+            MultiTextureMapping::<A, B>(TextureMappingInternal::Or(x)) => x.sync_point(),
+        }
+    }
 }
 
 impl<'render, 'target, R: GraphicsApi, T: GraphicsApi> ExportMem for MultiRenderer<'render, 'target, R, T>
@@ -4,6 +4,11 @@
 use crate::utils::{Buffer as BufferCoord, Coordinate, Logical, Physical, Point, Rectangle, Size};
 use std::{collections::VecDeque, fmt, sync::Arc};
 
+mod quality;
+pub use self::quality::{AdaptiveQuality, QualityLevel};
+mod readback;
+pub use self::readback::ReadbackSource;
+
 #[cfg(feature = "wayland_frontend")]
 mod wayland;
 #[cfg(feature = "wayland_frontend")]
@@ -42,6 +47,10 @@ impl CommitCounter {
             .filter(|commit| commit <= self)
             .map(|commit| self.0.wrapping_sub(commit.0))
     }
+
+    pub(crate) fn value(&self) -> usize {
+        self.0
+    }
 }
 
 impl From<usize> for CommitCounter {
@@ -0,0 +1,224 @@
+use std::time::Duration;
+
+use crate::utils::{Monotonic, Time};
+
+/// Composition quality chosen by [`AdaptiveQuality`]
+///
+/// The levels are ordered from the best to the most degraded quality.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum QualityLevel {
+    /// Render everything as usual
+    Full,
+    /// Skip optional effect passes (blur, shadows, animations, ...) and prefer
+    /// direct scanout of elements over compositing them
+    ReducedEffects,
+    /// Additionally lower the refresh rate of the output, if possible
+    ReducedRefresh,
+}
+
+impl QualityLevel {
+    /// Returns `true` if optional effect passes should be skipped
+    pub fn skip_effects(&self) -> bool {
+        *self >= QualityLevel::ReducedEffects
+    }
+
+    /// Returns `true` if elements should rather be scanned out directly than composited
+    pub fn prefer_direct_scanout(&self) -> bool {
+        *self >= QualityLevel::ReducedEffects
+    }
+
+    /// Returns `true` if the refresh rate of the output should be lowered
+    pub fn reduce_refresh_rate(&self) -> bool {
+        *self >= QualityLevel::ReducedRefresh
+    }
+
+    fn degraded(self) -> Self {
+        match self {
+            QualityLevel::Full => QualityLevel::ReducedEffects,
+            _ => QualityLevel::ReducedRefresh,
+        }
+    }
+
+    fn improved(self) -> Self {
+        match self {
+            QualityLevel::ReducedRefresh => QualityLevel::ReducedEffects,
+            _ => QualityLevel::Full,
+        }
+    }
+}
+
+/// Policy degrading the composition quality of an output while frames miss their deadline
+///
+/// Feed the result of every presented frame into [`AdaptiveQuality::frame_presented`] and
+/// adjust rendering according to the returned [`QualityLevel`]. The quality is degraded
+/// by one level once too many of the recent frames missed their deadline, and restored
+/// by one level after a longer streak of frames presented in time. This hysteresis avoids
+/// oscillating between levels, as degrading the quality usually makes the deadline again.
+#[derive(Debug, Clone)]
+pub struct AdaptiveQuality {
+    level: QualityLevel,
+    max_level: QualityLevel,
+    history: u64,
+    window: u32,
+    degrade_threshold: u32,
+    recover_after: u32,
+    on_time_streak: u32,
+    tolerance: Duration,
+}
+
+impl Default for AdaptiveQuality {
+    fn default() -> Self {
+        Self {
+            level: QualityLevel::Full,
+            max_level: QualityLevel::ReducedRefresh,
+            history: 0,
+            window: 30,
+            degrade_threshold: 6,
+            recover_after: 120,
+            on_time_streak: 0,
+            tolerance: Duration::from_micros(500),
+        }
+    }
+}
+
+impl AdaptiveQuality {
+    /// Create a new policy with the default thresholds
+    ///
+    /// By default the quality is degraded, if 6 of the last 30 frames missed their
+    /// deadline, and restored after 120 frames in a row presented in time.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the thresholds of the policy
+    ///
+    /// The quality is degraded once `misses` of the last `window` frames missed their deadline,
+    /// and restored after `recover_after` frames in a row presented in time.
+    /// The window is clamped to 64 frames.
+    pub fn set_thresholds(&mut self, misses: u32, window: u32, recover_after: u32) {
+        self.window = window.clamp(1, 64);
+        self.degrade_threshold = misses.clamp(1, self.window);
+        self.recover_after = recover_after.max(1);
+        self.history = 0;
+        self.on_time_streak = 0;
+    }
+
+    /// Set how late a frame may be presented without being considered a miss
+    pub fn set_tolerance(&mut self, tolerance: Duration) {
+        self.tolerance = tolerance;
+    }
+
+    /// Limit the policy to the given level
+    ///
+    /// E.g. outputs not supporting other refresh rates may limit the
+    /// policy to [`QualityLevel::ReducedEffects`].
+    pub fn set_max_level(&mut self, level: QualityLevel) {
+        self.max_level = level;
+        self.level = self.level.min(level);
+    }
+
+    /// Returns the current quality level
+    pub fn level(&self) -> QualityLevel {
+        self.level
+    }
+
+    /// Record a presented frame
+    ///
+    /// `deadline` is the time the frame was supposed to be presented at, usually the
+    /// estimated time of the next vblank when the frame was scheduled.
+    ///
+    /// Returns the new level, if it changed.
+    pub fn frame_presented(
+        &mut self,
+        deadline: Time<Monotonic>,
+        presented: Time<Monotonic>,
+    ) -> Option<QualityLevel> {
+        let missed = Time::elapsed(&deadline, presented) > self.tolerance;
+        self.record(missed)
+    }
+
+    /// Record a frame that could not be presented at all, e.g. because rendering took too long
+    ///
+    /// Returns the new level, if it changed.
+    pub fn frame_dropped(&mut self) -> Option<QualityLevel> {
+        self.record(true)
+    }
+
+    /// Reset the policy to [`QualityLevel::Full`]
+    ///
+    /// This should be called when the workload changed considerably, e.g. after a mode change.
+    pub fn reset(&mut self) {
+        self.level = QualityLevel::Full;
+        self.history = 0;
+        self.on_time_streak = 0;
+    }
+
+    fn record(&mut self, missed: bool) -> Option<QualityLevel> {
+        let mask = if self.window == 64 {
+            u64::MAX
+        } else {
+            (1u64 << self.window) - 1
+        };
+        self.history = ((self.history << 1) | missed as u64) & mask;
+
+        let previous = self.level;
+        if missed {
+            self.on_time_streak = 0;
+            if self.history.count_ones() >= self.degrade_threshold {
+                self.level = self.level.degraded().min(self.max_level);
+                // give the new level a full window to prove itself
+                self.history = 0;
+            }
+        } else {
+            self.on_time_streak += 1;
+            if self.on_time_streak >= self.recover_after {
+                self.level = self.level.improved();
+                self.on_time_streak = 0;
+            }
+        }
+
+        (self.level != previous).then_some(self.level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn degrades_and_recovers() {
+        let mut policy = AdaptiveQuality::new();
+        policy.set_thresholds(2, 4, 3);
+
+        assert_eq!(policy.frame_dropped(), None);
+        assert_eq!(policy.frame_dropped(), Some(QualityLevel::ReducedEffects));
+        assert_eq!(policy.frame_dropped(), None);
+        assert_eq!(policy.frame_dropped(), Some(QualityLevel::ReducedRefresh));
+        assert_eq!(policy.frame_dropped(), None);
+        assert_eq!(policy.frame_dropped(), None);
+        assert_eq!(policy.level(), QualityLevel::ReducedRefresh);
+
+        let now = Time::<Monotonic>::from(Duration::from_millis(100));
+        assert_eq!(policy.frame_presented(now, now), None);
+        assert_eq!(policy.frame_presented(now, now), None);
+        assert_eq!(
+            policy.frame_presented(now, now),
+            Some(QualityLevel::ReducedEffects)
+        );
+        for _ in 0..2 {
+            assert_eq!(policy.frame_presented(now, now), None);
+        }
+        assert_eq!(policy.frame_presented(now, now), Some(QualityLevel::Full));
+    }
+
+    #[test]
+    fn respects_max_level() {
+        let mut policy = AdaptiveQuality::new();
+        policy.set_thresholds(1, 1, 10);
+        policy.set_max_level(QualityLevel::ReducedEffects);
+
+        assert_eq!(policy.frame_dropped(), Some(QualityLevel::ReducedEffects));
+        assert_eq!(policy.frame_dropped(), None);
+        assert_eq!(policy.level(), QualityLevel::ReducedEffects);
+    }
+}
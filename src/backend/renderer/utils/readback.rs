@@ -0,0 +1,122 @@
+use std::{io, os::unix::io::OwnedFd, time::Duration};
+
+use calloop::{
+    generic::Generic,
+    timer::{TimeoutAction, Timer},
+    EventSource, Interest, Mode, Poll, PostAction, Readiness, Token, TokenFactory,
+};
+
+use crate::backend::renderer::TextureMapping;
+
+// interval used to query fences, that can't be exported as a native fence fd
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+#[derive(Debug)]
+enum ReadbackWait {
+    Fence(Generic<OwnedFd>),
+    Poll(Timer),
+}
+
+/// Event source delivering a [`TextureMapping`] once the copy into it completed
+///
+/// [`ExportMem::copy_framebuffer`](crate::backend::renderer::ExportMem::copy_framebuffer) and
+/// [`ExportMem::copy_texture`](crate::backend::renderer::ExportMem::copy_texture) only queue the copy
+/// on renderers supporting it, but mapping the result blocks until the copy is done.
+/// Inserting the mapping into the event loop instead defers
+/// [`ExportMem::map_texture`](crate::backend::renderer::ExportMem::map_texture) until the
+/// [`TextureMapping::sync_point`] is reached, so the render thread never waits for the readback.
+///
+/// The source generates a single event and is removed afterwards. Multiple readbacks in flight,
+/// e.g. of consecutive frames of a screencast, are delivered in the order their copies complete.
+#[derive(Debug)]
+pub struct ReadbackSource<M> {
+    mapping: Option<M>,
+    wait: ReadbackWait,
+}
+
+impl<M: TextureMapping> ReadbackSource<M> {
+    /// Create a new source for the given mapping
+    pub fn new(mapping: M) -> Self {
+        let sync_point = mapping.sync_point();
+        let wait = match sync_point.export() {
+            Some(fd) if !sync_point.is_reached() => {
+                ReadbackWait::Fence(Generic::new(fd, Interest::READ, Mode::OneShot))
+            }
+            _ => ReadbackWait::Poll(Timer::immediate()),
+        };
+        ReadbackSource {
+            mapping: Some(mapping),
+            wait,
+        }
+    }
+}
+
+impl<M: TextureMapping> EventSource for ReadbackSource<M> {
+    type Event = M;
+    type Metadata = ();
+    type Ret = ();
+    type Error = io::Error;
+
+    fn process_events<C>(
+        &mut self,
+        readiness: Readiness,
+        token: Token,
+        mut callback: C,
+    ) -> Result<PostAction, Self::Error>
+    where
+        C: FnMut(Self::Event, &mut Self::Metadata) -> Self::Ret,
+    {
+        let ready = match &mut self.wait {
+            ReadbackWait::Fence(source) => {
+                let mut ready = false;
+                source.process_events(readiness, token, |_, _| {
+                    ready = true;
+                    Ok(PostAction::Remove)
+                })?;
+                ready
+            }
+            ReadbackWait::Poll(timer) => {
+                let mut ready = false;
+                let mapping = self.mapping.as_ref();
+                timer.process_events(readiness, token, |_, _| {
+                    if mapping.map_or(true, |mapping| mapping.sync_point().is_reached()) {
+                        ready = true;
+                        TimeoutAction::Drop
+                    } else {
+                        TimeoutAction::ToDuration(POLL_INTERVAL)
+                    }
+                })?;
+                ready
+            }
+        };
+
+        if !ready {
+            return Ok(PostAction::Continue);
+        }
+        if let Some(mapping) = self.mapping.take() {
+            callback(mapping, &mut ());
+        }
+        Ok(PostAction::Remove)
+    }
+
+    fn register(&mut self, poll: &mut Poll, token_factory: &mut TokenFactory) -> calloop::Result<()> {
+        match &mut self.wait {
+            ReadbackWait::Fence(source) => source.register(poll, token_factory),
+            ReadbackWait::Poll(timer) => timer.register(poll, token_factory),
+        }
+    }
+
+    fn reregister(&mut self, poll: &mut Poll, token_factory: &mut TokenFactory) -> calloop::Result<()> {
+        match &mut self.wait {
+            ReadbackWait::Fence(source) => source.reregister(poll, token_factory),
+            ReadbackWait::Poll(timer) => timer.reregister(poll, token_factory),
+        }
+    }
+
+    fn unregister(&mut self, poll: &mut Poll) -> calloop::Result<()> {
+        match &mut self.wait {
+            ReadbackWait::Fence(source) => source.unregister(poll),
+            ReadbackWait::Poll(timer) => timer.unregister(poll),
+        }
+    }
+}
@@ -163,7 +163,8 @@ impl RendererSurfaceState {
                 self.buffer_scale = attrs.buffer_scale;
                 self.buffer_transform = attrs.buffer_transform.into();
 
-                if !self.buffer.as_ref().map_or(false, |b| b == buffer) {
+                let same_buffer = self.buffer.as_ref().map_or(false, |b| b == buffer);
+                if !same_buffer {
                     self.buffer = Some(Buffer {
                         inner: Arc::new(InnerBuffer {
                             buffer,
@@ -175,7 +176,12 @@ impl RendererSurfaceState {
                     });
                 }
 
-                self.textures.clear();
+                // Committing the same buffer again without any damage leaves its contents untouched,
+                // so the imported textures stay valid. This avoids re-importing unchanged frames of
+                // clients committing at full rate.
+                if !same_buffer || !attrs.damage.is_empty() {
+                    self.textures.clear();
+                }
             }
             Some(BufferAssignment::Removed) => {
                 self.reset();
@@ -584,6 +590,35 @@ where
     result
 }
 
+/// Invalidates the imported textures of a surface and its subsurfaces.
+///
+/// The buffers will be imported again by the next call to [`import_surface`] or [`import_surface_tree`]
+/// and the whole surfaces are considered damaged.
+///
+/// This is useful if the gpu state might have been lost without clients attaching new buffers,
+/// e.g. after resuming from hibernation.
+/// See also `DrmCompositor::reset_after_resume`.
+///
+/// Note: This will do nothing, if you are not using
+/// [`crate::backend::renderer::utils::on_commit_buffer_handler`]
+/// to let smithay handle buffer management.
+pub fn invalidate_surface_tree_textures(surface: &WlSurface) {
+    with_surface_tree_downward(
+        surface,
+        (),
+        |_, _, _| TraversalAction::DoChildren(()),
+        |_, states, _| {
+            if let Some(data) = states.data_map.get::<RendererSurfaceStateUserData>() {
+                let mut data = data.lock().unwrap();
+                data.textures.clear();
+                data.renderer_seen.clear();
+                data.damage.reset();
+            }
+        },
+        |_, _, _| true,
+    );
+}
+
 /// Draws the render elements using a given [`Renderer`] and [`Frame`](crate::backend::renderer::Frame)
 ///
 /// - `scale` needs to be equivalent to the fractional scale the rendered result should have.
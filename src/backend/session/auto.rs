@@ -0,0 +1,191 @@
+//!
+//! Implementation of the [`Session`] trait selecting an available session at runtime.
+//!
+//! [`AutoSession`] uses libseat if the `backend_session_libseat` feature is enabled and a seat
+//! can be opened. Otherwise it falls back to a [`NoopSession`], so compositors can run nested under
+//! another compositor or X11 using the same code path as on a tty.
+//!
+//! The [`direct`](super::direct) session is never selected automatically, as it takes over the
+//! current virtual terminal.
+
+use std::{os::unix::io::OwnedFd, path::Path};
+
+use calloop::{EventSource, Poll, PostAction, Readiness, Token, TokenFactory};
+use rustix::fs::OFlags;
+use tracing::info;
+#[cfg(feature = "backend_session_libseat")]
+use tracing::warn;
+
+#[cfg(feature = "backend_session_libseat")]
+use super::libseat::{self, LibSeatSession, LibSeatSessionNotifier};
+use super::{AsErrno, Event, NoopSession, NoopSessionNotifier, Session};
+
+/// [`Session`] selected at runtime, see the [module documentation](self)
+#[derive(Debug, Clone)]
+pub enum AutoSession {
+    /// Session via libseat
+    #[cfg(feature = "backend_session_libseat")]
+    LibSeat(LibSeatSession),
+    /// Session without any device management
+    Noop(NoopSession),
+}
+
+/// `SessionNotifier` of an [`AutoSession`]
+#[derive(Debug)]
+pub enum AutoSessionNotifier {
+    /// Notifier of a libseat session
+    #[cfg(feature = "backend_session_libseat")]
+    LibSeat(LibSeatSessionNotifier),
+    /// Notifier of a session without any device management
+    Noop(NoopSessionNotifier),
+}
+
+impl AutoSession {
+    /// Creates a new session using the best available implementation
+    ///
+    /// This never fails, in the worst case a [`NoopSession`] is returned.
+    pub fn new() -> (AutoSession, AutoSessionNotifier) {
+        #[cfg(feature = "backend_session_libseat")]
+        match LibSeatSession::new() {
+            Ok((session, notifier)) => {
+                info!("Using libseat session");
+                return (
+                    AutoSession::LibSeat(session),
+                    AutoSessionNotifier::LibSeat(notifier),
+                );
+            }
+            Err(err) => {
+                warn!(?err, "Failed to create libseat session");
+            }
+        }
+
+        info!("Using session without device management");
+        let (session, notifier) = NoopSession::new();
+        (AutoSession::Noop(session), AutoSessionNotifier::Noop(notifier))
+    }
+
+    /// Returns `true` if the session is able to open devices
+    pub fn manages_devices(&self) -> bool {
+        !matches!(self, AutoSession::Noop(_))
+    }
+}
+
+impl Session for AutoSession {
+    type Error = Error;
+
+    fn open(&mut self, path: &Path, flags: OFlags) -> Result<OwnedFd, Error> {
+        match self {
+            #[cfg(feature = "backend_session_libseat")]
+            AutoSession::LibSeat(session) => session.open(path, flags).map_err(Error::from),
+            AutoSession::Noop(session) => session.open(path, flags).map_err(|_| Error::Unsupported),
+        }
+    }
+
+    fn close(&mut self, fd: OwnedFd) -> Result<(), Error> {
+        match self {
+            #[cfg(feature = "backend_session_libseat")]
+            AutoSession::LibSeat(session) => session.close(fd).map_err(Error::from),
+            AutoSession::Noop(session) => session.close(fd).map_err(|_| Error::Unsupported),
+        }
+    }
+
+    fn change_vt(&mut self, vt: i32) -> Result<(), Error> {
+        match self {
+            #[cfg(feature = "backend_session_libseat")]
+            AutoSession::LibSeat(session) => session.change_vt(vt).map_err(Error::from),
+            AutoSession::Noop(session) => session.change_vt(vt).map_err(|_| Error::Unsupported),
+        }
+    }
+
+    fn is_active(&self) -> bool {
+        match self {
+            #[cfg(feature = "backend_session_libseat")]
+            AutoSession::LibSeat(session) => session.is_active(),
+            AutoSession::Noop(session) => session.is_active(),
+        }
+    }
+
+    fn seat(&self) -> String {
+        match self {
+            #[cfg(feature = "backend_session_libseat")]
+            AutoSession::LibSeat(session) => session.seat(),
+            AutoSession::Noop(session) => session.seat(),
+        }
+    }
+}
+
+impl EventSource for AutoSessionNotifier {
+    type Event = Event;
+    type Metadata = ();
+    type Ret = ();
+    type Error = Error;
+
+    fn process_events<F>(
+        &mut self,
+        readiness: Readiness,
+        token: Token,
+        callback: F,
+    ) -> Result<PostAction, Error>
+    where
+        F: FnMut(Event, &mut ()),
+    {
+        match self {
+            #[cfg(feature = "backend_session_libseat")]
+            AutoSessionNotifier::LibSeat(notifier) => notifier
+                .process_events(readiness, token, callback)
+                .map_err(Error::from),
+            AutoSessionNotifier::Noop(notifier) => {
+                match notifier.process_events(readiness, token, callback) {
+                    Ok(action) => Ok(action),
+                    Err(never) => match never {},
+                }
+            }
+        }
+    }
+
+    fn register(&mut self, poll: &mut Poll, factory: &mut TokenFactory) -> calloop::Result<()> {
+        match self {
+            #[cfg(feature = "backend_session_libseat")]
+            AutoSessionNotifier::LibSeat(notifier) => notifier.register(poll, factory),
+            AutoSessionNotifier::Noop(notifier) => notifier.register(poll, factory),
+        }
+    }
+
+    fn reregister(&mut self, poll: &mut Poll, factory: &mut TokenFactory) -> calloop::Result<()> {
+        match self {
+            #[cfg(feature = "backend_session_libseat")]
+            AutoSessionNotifier::LibSeat(notifier) => notifier.reregister(poll, factory),
+            AutoSessionNotifier::Noop(notifier) => notifier.reregister(poll, factory),
+        }
+    }
+
+    fn unregister(&mut self, poll: &mut Poll) -> calloop::Result<()> {
+        match self {
+            #[cfg(feature = "backend_session_libseat")]
+            AutoSessionNotifier::LibSeat(notifier) => notifier.unregister(poll),
+            AutoSessionNotifier::Noop(notifier) => notifier.unregister(poll),
+        }
+    }
+}
+
+/// Errors of an [`AutoSession`]
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    /// Error of the libseat session
+    #[cfg(feature = "backend_session_libseat")]
+    #[error(transparent)]
+    LibSeat(#[from] libseat::Error),
+    /// The session does not manage any devices
+    #[error("The session does not manage any devices")]
+    Unsupported,
+}
+
+impl AsErrno for Error {
+    fn as_errno(&self) -> Option<i32> {
+        match self {
+            #[cfg(feature = "backend_session_libseat")]
+            Error::LibSeat(err) => err.as_errno(),
+            Error::Unsupported => None,
+        }
+    }
+}
@@ -0,0 +1,520 @@
+//!
+//! Implementation of the [`Session`] trait by opening devices directly.
+//!
+//! This session does not depend on any session manager like seatd or systemd-logind and is meant for
+//! processes started before any user session exists, e.g. a greeter started by a login manager
+//! running as a system service. The process needs to be privileged enough to open the
+//! required devices and to become drm master, which usually means running as root.
+//!
+//! If a tty is given, the session takes control of the corresponding virtual terminal, switches it
+//! into graphics mode and disables its keyboard input. Switching to another virtual terminal pauses the
+//! session and switching back activates it again, which is communicated via the [`DirectSessionNotifier`].
+//! The original state of the terminal is restored once the [`DirectSessionNotifier`] is dropped,
+//! which includes unwinding panics. When built with `panic = "abort"`, a panic hook restores the terminal
+//! instead. Nothing can be restored if the process is terminated by a signal without running destructors
+//! (e.g. `SIGKILL`, or an unhandled `SIGTERM` or `SIGINT`). Compositors should handle the catchable
+//! signals and exit through the event loop, a supervising process can use [`reset_vt`] for the rest.
+//!
+//! Instead of passing a known tty, [`DirectSession::new_on_free_vt`] allocates an unused virtual terminal.
+//! This only makes the session use that terminal for display and input, the compositor process and the
+//! processes it spawns keep their controlling terminal and standard streams. To run a process on the
+//! allocated terminal, open [`vt_path`] and pass it as the standard streams of the spawned command.
+//!
+//! Without a tty, the session is always active and does not support changing the virtual terminal.
+
+use std::{
+    cell::Cell,
+    io,
+    os::unix::io::{AsFd, AsRawFd, OwnedFd},
+    path::{Path, PathBuf},
+    rc::{Rc, Weak},
+};
+
+use calloop::{
+    signals::{Signal, Signals},
+    EventSource, Poll, PostAction, Readiness, Token, TokenFactory,
+};
+use rustix::{
+    fs::{Mode, OFlags},
+    io::Errno,
+};
+use tracing::{debug, info, info_span, instrument, warn};
+
+use crate::backend::session::{AsErrno, Event as SessionEvent, Session};
+
+// see linux/kd.h
+const KDSETMODE: libc::c_ulong = 0x4B3A;
+const KDGETMODE: libc::c_ulong = 0x4B3B;
+const KDGKBMODE: libc::c_ulong = 0x4B44;
+const KDSKBMODE: libc::c_ulong = 0x4B45;
+const KD_TEXT: libc::c_int = 0x00;
+const KD_GRAPHICS: libc::c_int = 0x01;
+const K_UNICODE: libc::c_int = 0x03;
+const K_OFF: libc::c_int = 0x04;
+
+// see linux/vt.h
+const VT_OPENQRY: libc::c_ulong = 0x5600;
+const VT_SETMODE: libc::c_ulong = 0x5602;
+const VT_RELDISP: libc::c_ulong = 0x5605;
+const VT_ACTIVATE: libc::c_ulong = 0x5606;
+const VT_WAITACTIVE: libc::c_ulong = 0x5607;
+const VT_AUTO: libc::c_char = 0x00;
+const VT_PROCESS: libc::c_char = 0x01;
+const VT_ACKACQ: libc::c_int = 0x02;
+
+const TTY_MAJOR: u32 = 4;
+const MAX_VT: u32 = 63;
+
+#[repr(C)]
+struct VtMode {
+    mode: libc::c_char,
+    waitv: libc::c_char,
+    relsig: libc::c_short,
+    acqsig: libc::c_short,
+    frsig: libc::c_short,
+}
+
+fn ioctl(fd: &impl AsFd, request: libc::c_ulong, arg: libc::c_ulong) -> Result<libc::c_int, Errno> {
+    // SAFETY: all requests used by this module either take an integer argument
+    // or a pointer to a correctly sized value.
+    let ret = unsafe { libc::ioctl(fd.as_fd().as_raw_fd(), request as _, arg) };
+    if ret < 0 {
+        Err(Errno::from_raw_os_error(
+            io::Error::last_os_error().raw_os_error().unwrap_or(0),
+        ))
+    } else {
+        Ok(ret)
+    }
+}
+
+fn open_vt(path: &Path) -> Result<(OwnedFd, i32), Error> {
+    let tty = rustix::fs::open(
+        path,
+        OFlags::RDWR | OFlags::CLOEXEC | OFlags::NOCTTY,
+        Mode::empty(),
+    )
+    .map_err(|err| Error::FailedToOpenTty(path.to_path_buf(), err))?;
+
+    let stat = rustix::fs::fstat(&tty).map_err(|err| Error::FailedToOpenTty(path.to_path_buf(), err))?;
+    let major = rustix::fs::major(stat.st_rdev);
+    let minor = rustix::fs::minor(stat.st_rdev);
+    if major != TTY_MAJOR || minor == 0 || minor > MAX_VT {
+        return Err(Error::NotAVirtualTerminal(path.to_path_buf()));
+    }
+
+    Ok((tty, minor as i32))
+}
+
+/// Returns the device path of the virtual terminal with the given number
+pub fn vt_path(number: i32) -> PathBuf {
+    PathBuf::from(format!("/dev/tty{}", number))
+}
+
+/// Finds the first virtual terminal not opened by any process
+///
+/// The returned number can be passed to [`vt_path`] to obtain the device path
+/// of the virtual terminal.
+pub fn find_free_vt() -> Result<i32, Error> {
+    let path = Path::new("/dev/tty0");
+    let tty = rustix::fs::open(
+        path,
+        OFlags::RDWR | OFlags::CLOEXEC | OFlags::NOCTTY,
+        Mode::empty(),
+    )
+    .map_err(|err| Error::FailedToOpenTty(path.to_path_buf(), err))?;
+
+    let mut number: libc::c_int = -1;
+    ioctl(&tty, VT_OPENQRY, &mut number as *mut _ as libc::c_ulong).map_err(Error::FailedToSetupVt)?;
+    if number <= 0 {
+        return Err(Error::NoFreeVirtualTerminal);
+    }
+    Ok(number)
+}
+
+/// Resets a virtual terminal to text mode, unicode keyboard input and automatic switching
+///
+/// A [`DirectSession`] restores the state of its virtual terminal when dropped or on panic,
+/// which is impossible if the process gets killed (e.g. by `SIGKILL`). While the kernel
+/// falls back to automatic switching once the controlling process is gone, the terminal
+/// is left in graphics mode without keyboard input. A supervising process or the next
+/// start of the compositor can use this function to make the terminal usable again.
+pub fn reset_vt(tty: &Path) -> Result<(), Error> {
+    let (tty, _) = open_vt(tty)?;
+    let mode = VtMode {
+        mode: VT_AUTO,
+        waitv: 0,
+        relsig: 0,
+        acqsig: 0,
+        frsig: 0,
+    };
+    ioctl(&tty, VT_SETMODE, &mode as *const _ as libc::c_ulong).map_err(Error::FailedToSetupVt)?;
+    ioctl(&tty, KDSETMODE, KD_TEXT as libc::c_ulong).map_err(Error::FailedToSetupVt)?;
+    ioctl(&tty, KDSKBMODE, K_UNICODE as libc::c_ulong).map_err(Error::FailedToSetupVt)?;
+    Ok(())
+}
+
+#[derive(Debug)]
+struct Vt {
+    tty: OwnedFd,
+    number: i32,
+    old_kd_mode: libc::c_int,
+    old_kb_mode: libc::c_int,
+}
+
+impl Vt {
+    fn setup(path: &Path) -> Result<Vt, Error> {
+        let (tty, number) = open_vt(path)?;
+
+        let mut old_kd_mode: libc::c_int = 0;
+        ioctl(&tty, KDGETMODE, &mut old_kd_mode as *mut _ as libc::c_ulong)
+            .map_err(Error::FailedToSetupVt)?;
+        let mut old_kb_mode: libc::c_int = 0;
+        ioctl(&tty, KDGKBMODE, &mut old_kb_mode as *mut _ as libc::c_ulong)
+            .map_err(Error::FailedToSetupVt)?;
+
+        let vt = Vt {
+            tty,
+            number,
+            old_kd_mode,
+            old_kb_mode,
+        };
+        #[cfg(panic = "abort")]
+        panic_restore::register(vt.tty.as_raw_fd(), old_kd_mode, old_kb_mode);
+
+        // input is read via libinput, the terminal should not receive it
+        ioctl(&vt.tty, KDSKBMODE, K_OFF as libc::c_ulong).map_err(Error::FailedToSetupVt)?;
+        ioctl(&vt.tty, KDSETMODE, KD_GRAPHICS as libc::c_ulong).map_err(Error::FailedToSetupVt)?;
+
+        let mode = VtMode {
+            mode: VT_PROCESS,
+            waitv: 0,
+            relsig: libc::SIGUSR1 as libc::c_short,
+            acqsig: libc::SIGUSR2 as libc::c_short,
+            frsig: 0,
+        };
+        ioctl(&vt.tty, VT_SETMODE, &mode as *const _ as libc::c_ulong).map_err(Error::FailedToSetupVt)?;
+
+        ioctl(&vt.tty, VT_ACTIVATE, vt.number as libc::c_ulong).map_err(Error::FailedToSetupVt)?;
+        ioctl(&vt.tty, VT_WAITACTIVE, vt.number as libc::c_ulong).map_err(Error::FailedToSetupVt)?;
+
+        Ok(vt)
+    }
+}
+
+impl Drop for Vt {
+    fn drop(&mut self) {
+        #[cfg(panic = "abort")]
+        panic_restore::unregister(self.tty.as_raw_fd());
+
+        info!("Restoring vt {}", self.number);
+        restore_vt(&self.tty, self.old_kd_mode, self.old_kb_mode);
+    }
+}
+
+fn restore_vt(tty: &impl AsFd, old_kd_mode: libc::c_int, old_kb_mode: libc::c_int) {
+    let mode = VtMode {
+        mode: VT_AUTO,
+        waitv: 0,
+        relsig: 0,
+        acqsig: 0,
+        frsig: 0,
+    };
+    if let Err(err) = ioctl(tty, VT_SETMODE, &mode as *const _ as libc::c_ulong) {
+        warn!("Failed to reset vt mode: {}", err);
+    }
+    let kd_mode = if old_kd_mode == KD_GRAPHICS {
+        KD_TEXT
+    } else {
+        old_kd_mode
+    };
+    if let Err(err) = ioctl(tty, KDSETMODE, kd_mode as libc::c_ulong) {
+        warn!("Failed to reset vt display mode: {}", err);
+    }
+    if let Err(err) = ioctl(tty, KDSKBMODE, old_kb_mode as libc::c_ulong) {
+        warn!("Failed to reset vt keyboard mode: {}", err);
+    }
+}
+
+// Without unwinding, a panic never drops the `Vt`, so restore the terminal from a panic hook instead.
+#[cfg(panic = "abort")]
+mod panic_restore {
+    use std::{
+        os::unix::io::{BorrowedFd, RawFd},
+        sync::{Mutex, Once},
+    };
+
+    static VTS: Mutex<Vec<(RawFd, libc::c_int, libc::c_int)>> = Mutex::new(Vec::new());
+    static HOOK: Once = Once::new();
+
+    pub fn register(tty: RawFd, old_kd_mode: libc::c_int, old_kb_mode: libc::c_int) {
+        HOOK.call_once(|| {
+            let previous = std::panic::take_hook();
+            std::panic::set_hook(Box::new(move |info| {
+                // never block inside the hook, a panic while holding the lock would deadlock otherwise
+                if let Ok(vts) = VTS.try_lock() {
+                    for &(tty, old_kd_mode, old_kb_mode) in vts.iter() {
+                        // SAFETY: entries are removed before the `Vt` closes its file descriptor
+                        let tty = unsafe { BorrowedFd::borrow_raw(tty) };
+                        super::restore_vt(&tty, old_kd_mode, old_kb_mode);
+                    }
+                }
+                previous(info);
+            }));
+        });
+        VTS.lock().unwrap().push((tty, old_kd_mode, old_kb_mode));
+    }
+
+    pub fn unregister(tty: RawFd) {
+        VTS.lock().unwrap().retain(|&(fd, _, _)| fd != tty);
+    }
+}
+
+#[derive(Debug)]
+struct DirectSessionImpl {
+    vt: Option<Vt>,
+    active: Cell<bool>,
+}
+
+/// [`Session`] opening devices directly
+#[derive(Debug, Clone)]
+pub struct DirectSession {
+    internal: Weak<DirectSessionImpl>,
+    span: tracing::Span,
+}
+
+/// `SessionNotifier` of a [`DirectSession`]
+#[derive(Debug)]
+pub struct DirectSessionNotifier {
+    internal: Rc<DirectSessionImpl>,
+    signals: Option<Signals>,
+    span: tracing::Span,
+}
+
+impl DirectSession {
+    /// Creates a new direct session
+    ///
+    /// If `tty` is given, it has to refer to a virtual terminal (e.g. `/dev/tty2`), which will be
+    /// activated and controlled by this session. This blocks the `SIGUSR1` and `SIGUSR2` signals
+    /// of the calling thread, which are used to get notified about virtual terminal switches.
+    pub fn new(tty: Option<&Path>) -> Result<(DirectSession, DirectSessionNotifier), Error> {
+        let span = info_span!("backend_session", "type" = "direct");
+        let _guard = span.enter();
+
+        let (vt, signals) = match tty {
+            Some(path) => {
+                // create the signal source before taking over the vt, so no signal gets lost
+                let signals =
+                    Signals::new(&[Signal::SIGUSR1, Signal::SIGUSR2]).map_err(Error::FailedToSetupSignals)?;
+                let vt = Vt::setup(path)?;
+                info!("Using vt {}", vt.number);
+                (Some(vt), Some(signals))
+            }
+            None => (None, None),
+        };
+
+        let internal = Rc::new(DirectSessionImpl {
+            vt,
+            active: Cell::new(true),
+        });
+
+        drop(_guard);
+        let session = DirectSession {
+            internal: Rc::downgrade(&internal),
+            span: span.clone(),
+        };
+        let notifier = DirectSessionNotifier {
+            internal,
+            signals,
+            span,
+        };
+
+        Ok((session, notifier))
+    }
+
+    /// Creates a new direct session on the first free virtual terminal
+    ///
+    /// See [`find_free_vt`] and [`DirectSession::new`]. The calling process is not moved onto
+    /// the allocated terminal, see the [module-level documentation](self) on how to spawn processes on it.
+    pub fn new_on_free_vt() -> Result<(DirectSession, DirectSessionNotifier), Error> {
+        let number = find_free_vt()?;
+        Self::new(Some(&vt_path(number)))
+    }
+
+    /// Returns the number of the virtual terminal controlled by this session, if any
+    pub fn vt(&self) -> Option<i32> {
+        self.internal.upgrade()?.vt.as_ref().map(|vt| vt.number)
+    }
+}
+
+impl Session for DirectSession {
+    type Error = Error;
+
+    #[instrument(parent = &self.span, skip(self))]
+    fn open(&mut self, path: &Path, flags: OFlags) -> Result<OwnedFd, Self::Error> {
+        debug!("Opening device: {:?}", path);
+        rustix::fs::open(path, flags, Mode::empty()).map_err(Error::FailedToOpenDevice)
+    }
+
+    #[instrument(parent = &self.span, skip(self))]
+    fn close(&mut self, fd: OwnedFd) -> Result<(), Self::Error> {
+        debug!("Closing device: {:?}", fd);
+        // `fd` is closed on drop
+        Ok(())
+    }
+
+    #[instrument(parent = &self.span, skip(self))]
+    fn change_vt(&mut self, vt: i32) -> Result<(), Self::Error> {
+        let internal = self.internal.upgrade().ok_or(Error::SessionLost)?;
+        let Some(current) = internal.vt.as_ref() else {
+            return Err(Error::NoVirtualTerminal);
+        };
+        debug!("Session switch: {:?}", vt);
+        ioctl(&current.tty, VT_ACTIVATE, vt as libc::c_ulong)
+            .map(|_| ())
+            .map_err(Error::FailedToChangeVt)
+    }
+
+    fn is_active(&self) -> bool {
+        self.internal
+            .upgrade()
+            .map(|internal| internal.active.get())
+            .unwrap_or(false)
+    }
+
+    fn seat(&self) -> String {
+        String::from("seat0")
+    }
+}
+
+impl DirectSessionNotifier {
+    /// Creates a new session object belonging to this notifier.
+    pub fn session(&self) -> DirectSession {
+        DirectSession {
+            internal: Rc::downgrade(&self.internal),
+            span: self.span.clone(),
+        }
+    }
+}
+
+impl EventSource for DirectSessionNotifier {
+    type Event = SessionEvent;
+    type Metadata = ();
+    type Ret = ();
+    type Error = Error;
+
+    #[profiling::function]
+    fn process_events<F>(
+        &mut self,
+        readiness: Readiness,
+        token: Token,
+        mut callback: F,
+    ) -> Result<PostAction, Error>
+    where
+        F: FnMut(SessionEvent, &mut ()),
+    {
+        let Some(signals) = self.signals.as_mut() else {
+            return Ok(PostAction::Continue);
+        };
+        let _guard = self.span.enter();
+        let internal = &self.internal;
+        signals
+            .process_events(readiness, token, |event, _| {
+                let Some(vt) = internal.vt.as_ref() else {
+                    return;
+                };
+                match event.signal() {
+                    Signal::SIGUSR1 => {
+                        debug!("Releasing vt {}", vt.number);
+                        internal.active.set(false);
+                        callback(SessionEvent::PauseSession, &mut ());
+                        if let Err(err) = ioctl(&vt.tty, VT_RELDISP, 1) {
+                            warn!("Failed to release vt: {}", err);
+                        }
+                    }
+                    Signal::SIGUSR2 => {
+                        debug!("Acquiring vt {}", vt.number);
+                        if let Err(err) = ioctl(&vt.tty, VT_RELDISP, VT_ACKACQ as libc::c_ulong) {
+                            warn!("Failed to acknowledge vt acquisition: {}", err);
+                        }
+                        internal.active.set(true);
+                        callback(SessionEvent::ActivateSession, &mut ());
+                    }
+                    _ => {}
+                }
+            })
+            .map_err(|_| Error::SessionLost)
+    }
+
+    fn register(&mut self, poll: &mut Poll, factory: &mut TokenFactory) -> calloop::Result<()> {
+        if let Some(signals) = self.signals.as_mut() {
+            signals.register(poll, factory)?;
+        }
+        Ok(())
+    }
+
+    fn reregister(&mut self, poll: &mut Poll, factory: &mut TokenFactory) -> calloop::Result<()> {
+        if let Some(signals) = self.signals.as_mut() {
+            signals.reregister(poll, factory)?;
+        }
+        Ok(())
+    }
+
+    fn unregister(&mut self, poll: &mut Poll) -> calloop::Result<()> {
+        if let Some(signals) = self.signals.as_mut() {
+            signals.unregister(poll)?;
+        }
+        Ok(())
+    }
+}
+
+/// Errors related to direct sessions
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    /// Failed to open the tty
+    #[error("Failed to open tty {0:?}: {1}")]
+    FailedToOpenTty(PathBuf, Errno),
+
+    /// The given tty is not a virtual terminal
+    #[error("{0:?} is not a virtual terminal")]
+    NotAVirtualTerminal(PathBuf),
+
+    /// Failed to setup the signal handling for virtual terminal switches
+    #[error("Failed to setup signal handling: {0}")]
+    FailedToSetupSignals(#[source] calloop::Error),
+
+    /// Failed to take control of the virtual terminal
+    #[error("Failed to setup virtual terminal: {0}")]
+    FailedToSetupVt(Errno),
+
+    /// Failed to open device
+    #[error("Failed to open device: {0}")]
+    FailedToOpenDevice(Errno),
+
+    /// Failed to change vt
+    #[error("Failed to change vt: {0}")]
+    FailedToChangeVt(Errno),
+
+    /// No unused virtual terminal is available
+    #[error("No free virtual terminal available")]
+    NoFreeVirtualTerminal,
+
+    /// The session does not control a virtual terminal
+    #[error("The session does not control a virtual terminal")]
+    NoVirtualTerminal,
+
+    /// Session is already closed
+    #[error("Session is already closed")]
+    SessionLost,
+}
+
+impl AsErrno for Error {
+    fn as_errno(&self) -> Option<i32> {
+        match self {
+            &Self::FailedToOpenTty(_, errno)
+            | &Self::FailedToSetupVt(errno)
+            | &Self::FailedToOpenDevice(errno)
+            | &Self::FailedToChangeVt(errno) => Some(errno.raw_os_error()),
+            _ => None,
+        }
+    }
+}
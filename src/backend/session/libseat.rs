@@ -228,8 +228,10 @@ impl EventSource for LibSeatSessionNotifier {
                     }
                     SeatEvent::Disable => {
                         internal.active.store(false, Ordering::SeqCst);
-                        internal.seat.borrow_mut().disable().unwrap();
+                        // acknowledge only after the compositor had the chance to finish pending
+                        // work on its devices, as they get revoked once the seat is disabled
                         callback(SessionEvent::PauseSession, &mut ());
+                        internal.seat.borrow_mut().disable().unwrap();
                     }
                 },
                 channel::Event::Closed => {
@@ -21,9 +21,21 @@
 //!
 //! ## Available providers
 //!
-//! This module provides just one session implementation, through [libseat](https://sr.ht/~kennylevinsen/seatd/),
+//! This module provides a session implementation through [libseat](https://sr.ht/~kennylevinsen/seatd/),
 //! gated by the `backend_session_libseat` cargo feature.
 //!
+//! libseat itself talks to either seatd, systemd-logind or uses its builtin seatd-server, so this covers
+//! systems without systemd as well as rootless operation. The backend can be selected at runtime via the
+//! `LIBSEAT_BACKEND` environment variable.
+//!
+//! Additionally the [`direct`] session opens devices without any session manager, e.g. for greeters
+//! started by a login manager before any user session exists. It is gated by the
+//! `backend_session_direct` cargo feature and requires sufficient privileges.
+//!
+//! For nested compositors, that do not need to manage any devices, the [`NoopSession`] can be used
+//! in place of a real session. The [`auto`] session picks libseat if available and falls back to the
+//! [`NoopSession`], so the same code path works for standalone and nested operation.
+//!
 //! Other implementations can be provided out-of-tree.
 
 use rustix::fs::OFlags;
@@ -35,6 +47,10 @@ use std::{
     sync::{Arc, Mutex},
 };
 
+use calloop::{EventSource, Poll, PostAction, Readiness, Token, TokenFactory};
+
+use crate::input::keyboard::{keysyms, Keysym, ModifiersState};
+
 /// General session interface.
 ///
 /// Provides a way to open and close devices and change the active vt.
@@ -91,6 +107,75 @@ impl Session for () {
     }
 }
 
+/// [`Session`] without any device management
+///
+/// Meant for compositors running nested under another compositor or X11, which do not need to open
+/// any devices. This allows the same code paths to be used for nested and standalone operation.
+/// The session is always active, opening devices and changing the virtual terminal always fails.
+#[derive(Debug, Clone, Default)]
+pub struct NoopSession;
+
+/// `SessionNotifier` of a [`NoopSession`]
+///
+/// As the session is always active, this never generates any events.
+#[derive(Debug, Default)]
+pub struct NoopSessionNotifier;
+
+impl NoopSession {
+    /// Creates a new no-op session
+    pub fn new() -> (NoopSession, NoopSessionNotifier) {
+        (NoopSession, NoopSessionNotifier)
+    }
+}
+
+impl Session for NoopSession {
+    type Error = ();
+
+    fn open(&mut self, _path: &Path, _flags: OFlags) -> Result<OwnedFd, Self::Error> {
+        Err(())
+    }
+    fn close(&mut self, _fd: OwnedFd) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn change_vt(&mut self, _vt: i32) -> Result<(), Self::Error> {
+        Err(())
+    }
+
+    fn is_active(&self) -> bool {
+        true
+    }
+    fn seat(&self) -> String {
+        String::from("seat0")
+    }
+}
+
+impl EventSource for NoopSessionNotifier {
+    type Event = Event;
+    type Metadata = ();
+    type Ret = ();
+    type Error = std::convert::Infallible;
+
+    fn process_events<F>(&mut self, _: Readiness, _: Token, _: F) -> Result<PostAction, Self::Error>
+    where
+        F: FnMut(Event, &mut ()),
+    {
+        Ok(PostAction::Continue)
+    }
+
+    fn register(&mut self, _: &mut Poll, _: &mut TokenFactory) -> calloop::Result<()> {
+        Ok(())
+    }
+
+    fn reregister(&mut self, _: &mut Poll, _: &mut TokenFactory) -> calloop::Result<()> {
+        Ok(())
+    }
+
+    fn unregister(&mut self, _: &mut Poll) -> calloop::Result<()> {
+        Ok(())
+    }
+}
+
 impl<S: Session> Session for Rc<RefCell<S>> {
     type Error = S::Error;
 
@@ -139,6 +224,25 @@ impl<S: Session> Session for Arc<Mutex<S>> {
     }
 }
 
+/// Returns the virtual terminal to switch to for a pressed key
+///
+/// Most keymaps map `Ctrl+Alt+F1` to `Ctrl+Alt+F12` to the `XF86Switch_VT_1` to `XF86Switch_VT_12` keysyms,
+/// which are handled regardless of `modifiers`. For keymaps without these, `Ctrl+Alt+F1` to `Ctrl+Alt+F12`
+/// are handled directly. The result can be passed to [`Session::change_vt`].
+///
+/// Note that switching the virtual terminal pauses the session, so pressed keys should not be forwarded
+/// to clients, to avoid stuck keys once the session is activated again.
+pub fn vt_for_keysym(modifiers: &ModifiersState, keysym: Keysym) -> Option<i32> {
+    let raw = keysym.raw();
+    if (keysyms::KEY_XF86Switch_VT_1..=keysyms::KEY_XF86Switch_VT_12).contains(&raw) {
+        Some((raw - keysyms::KEY_XF86Switch_VT_1 + 1) as i32)
+    } else if modifiers.ctrl && modifiers.alt && (keysyms::KEY_F1..=keysyms::KEY_F12).contains(&raw) {
+        Some((raw - keysyms::KEY_F1 + 1) as i32)
+    } else {
+        None
+    }
+}
+
 /// Allows errors to be described by an error number
 pub trait AsErrno: ::std::fmt::Debug {
     /// Returns the error number representing this error if any
@@ -151,5 +255,8 @@ impl AsErrno for () {
     }
 }
 
+pub mod auto;
+#[cfg(feature = "backend_session_direct")]
+pub mod direct;
 #[cfg(feature = "backend_session_libseat")]
 pub mod libseat;
@@ -0,0 +1,845 @@
+//! Simulated input backend
+//!
+//! This backend generates input events that are injected programmatically instead of being read from
+//! hardware, so compositors can be tested without udev, libinput or a nested window. Events are sent
+//! through a [`SimulatedInputHandle`] and delivered by the [`SimulatedInputSource`] inserted into the
+//! event loop, just like the events of any other [`InputBackend`].
+//!
+//! ```no_run
+//! use smithay::backend::{
+//!     input::{DeviceCapability, KeyState},
+//!     simulated::SimulatedInputSource,
+//! };
+//!
+//! # let event_loop = calloop::EventLoop::<()>::try_new().unwrap();
+//! let (source, handle) = SimulatedInputSource::new();
+//! event_loop
+//!     .handle()
+//!     .insert_source(source, |event, _, _state| {
+//!         // process the event like the events of any other input backend
+//!     })
+//!     .unwrap();
+//!
+//! let keyboard = handle.add_device("test-keyboard", &[DeviceCapability::Keyboard]);
+//! handle.key(&keyboard, 0, 30, KeyState::Pressed);
+//! handle.key(&keyboard, 10_000, 30, KeyState::Released);
+//! ```
+//!
+//! Sequences of events can also be described in a script, see [`SimulatedInputHandle::run_script`].
+
+use std::{
+    collections::HashMap,
+    hash::{Hash, Hasher},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+};
+
+use calloop::{
+    channel::{self, Channel, Sender},
+    EventSource, Poll, PostAction, Readiness, Token, TokenFactory,
+};
+
+use super::input::{
+    AbsolutePositionEvent, Axis, AxisRelativeDirection, AxisSource, ButtonState, Device, DeviceCapability,
+    Event, InputBackend, InputEvent, KeyState, KeyboardKeyEvent, Keycode, PointerAxisEvent,
+    PointerButtonEvent, PointerMotionAbsoluteEvent, PointerMotionEvent, TouchCancelEvent, TouchDownEvent,
+    TouchEvent, TouchFrameEvent, TouchMotionEvent, TouchSlot, TouchUpEvent, UnusedEvent,
+};
+
+static DEVICE_ID: AtomicU32 = AtomicU32::new(0);
+
+/// Marker type for the simulated input backend
+#[derive(Debug)]
+pub struct SimulatedInputBackend;
+
+impl InputBackend for SimulatedInputBackend {
+    type Device = SimulatedDevice;
+    type KeyboardKeyEvent = SimulatedKeyboardKeyEvent;
+    type PointerAxisEvent = SimulatedPointerAxisEvent;
+    type PointerButtonEvent = SimulatedPointerButtonEvent;
+    type PointerMotionEvent = SimulatedPointerMotionEvent;
+    type PointerMotionAbsoluteEvent = SimulatedAbsoluteEvent;
+
+    type GestureSwipeBeginEvent = UnusedEvent;
+    type GestureSwipeUpdateEvent = UnusedEvent;
+    type GestureSwipeEndEvent = UnusedEvent;
+    type GesturePinchBeginEvent = UnusedEvent;
+    type GesturePinchUpdateEvent = UnusedEvent;
+    type GesturePinchEndEvent = UnusedEvent;
+    type GestureHoldBeginEvent = UnusedEvent;
+    type GestureHoldEndEvent = UnusedEvent;
+
+    type TouchDownEvent = SimulatedAbsoluteEvent;
+    type TouchUpEvent = SimulatedTouchEvent;
+    type TouchMotionEvent = SimulatedAbsoluteEvent;
+    type TouchCancelEvent = SimulatedTouchEvent;
+    type TouchFrameEvent = SimulatedTouchEvent;
+    type TabletToolAxisEvent = UnusedEvent;
+    type TabletToolProximityEvent = UnusedEvent;
+    type TabletToolTipEvent = UnusedEvent;
+    type TabletToolButtonEvent = UnusedEvent;
+
+    type TabletPadButtonEvent = UnusedEvent;
+    type TabletPadRingEvent = UnusedEvent;
+    type TabletPadStripEvent = UnusedEvent;
+    type SwitchToggleEvent = UnusedEvent;
+
+    type SpecialEvent = UnusedEvent;
+}
+
+/// A simulated input device
+///
+/// Devices are compared by their id, which is unique for every device created
+/// by [`SimulatedInputHandle::add_device`].
+#[derive(Debug, Clone)]
+pub struct SimulatedDevice {
+    id: u32,
+    name: Arc<str>,
+    capabilities: Arc<[DeviceCapability]>,
+}
+
+impl PartialEq for SimulatedDevice {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for SimulatedDevice {}
+
+impl Hash for SimulatedDevice {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl Device for SimulatedDevice {
+    fn id(&self) -> String {
+        format!("simulated-{}", self.id)
+    }
+
+    fn name(&self) -> String {
+        self.name.to_string()
+    }
+
+    fn has_capability(&self, capability: DeviceCapability) -> bool {
+        self.capabilities.contains(&capability)
+    }
+
+    fn usb_id(&self) -> Option<(u32, u32)> {
+        None
+    }
+
+    fn syspath(&self) -> Option<PathBuf> {
+        None
+    }
+}
+
+/// Key press or release of a simulated keyboard
+#[derive(Debug, Clone)]
+pub struct SimulatedKeyboardKeyEvent {
+    device: SimulatedDevice,
+    time: u64,
+    key: Keycode,
+    state: KeyState,
+    count: u32,
+}
+
+impl Event<SimulatedInputBackend> for SimulatedKeyboardKeyEvent {
+    fn time(&self) -> u64 {
+        self.time
+    }
+
+    fn device(&self) -> SimulatedDevice {
+        self.device.clone()
+    }
+}
+
+impl KeyboardKeyEvent<SimulatedInputBackend> for SimulatedKeyboardKeyEvent {
+    fn key_code(&self) -> Keycode {
+        self.key
+    }
+
+    fn state(&self) -> KeyState {
+        self.state
+    }
+
+    fn count(&self) -> u32 {
+        self.count
+    }
+}
+
+/// Relative motion of a simulated pointer
+///
+/// No acceleration is applied, the accelerated and unaccelerated deltas are identical.
+#[derive(Debug, Clone)]
+pub struct SimulatedPointerMotionEvent {
+    device: SimulatedDevice,
+    time: u64,
+    dx: f64,
+    dy: f64,
+}
+
+impl Event<SimulatedInputBackend> for SimulatedPointerMotionEvent {
+    fn time(&self) -> u64 {
+        self.time
+    }
+
+    fn device(&self) -> SimulatedDevice {
+        self.device.clone()
+    }
+}
+
+impl PointerMotionEvent<SimulatedInputBackend> for SimulatedPointerMotionEvent {
+    fn delta_x(&self) -> f64 {
+        self.dx
+    }
+
+    fn delta_y(&self) -> f64 {
+        self.dy
+    }
+
+    fn delta_x_unaccel(&self) -> f64 {
+        self.dx
+    }
+
+    fn delta_y_unaccel(&self) -> f64 {
+        self.dy
+    }
+}
+
+/// Absolute position of a simulated pointer or touch point
+///
+/// The position is normalized to `0.0..=1.0`, like the events of touchscreens and tablets.
+#[derive(Debug, Clone)]
+pub struct SimulatedAbsoluteEvent {
+    device: SimulatedDevice,
+    time: u64,
+    slot: TouchSlot,
+    x: f64,
+    y: f64,
+}
+
+impl Event<SimulatedInputBackend> for SimulatedAbsoluteEvent {
+    fn time(&self) -> u64 {
+        self.time
+    }
+
+    fn device(&self) -> SimulatedDevice {
+        self.device.clone()
+    }
+}
+
+impl AbsolutePositionEvent<SimulatedInputBackend> for SimulatedAbsoluteEvent {
+    fn x(&self) -> f64 {
+        self.x
+    }
+
+    fn y(&self) -> f64 {
+        self.y
+    }
+
+    fn x_transformed(&self, width: i32) -> f64 {
+        self.x * width as f64
+    }
+
+    fn y_transformed(&self, height: i32) -> f64 {
+        self.y * height as f64
+    }
+}
+
+impl PointerMotionAbsoluteEvent<SimulatedInputBackend> for SimulatedAbsoluteEvent {}
+
+impl TouchEvent<SimulatedInputBackend> for SimulatedAbsoluteEvent {
+    fn slot(&self) -> TouchSlot {
+        self.slot
+    }
+}
+
+impl TouchDownEvent<SimulatedInputBackend> for SimulatedAbsoluteEvent {}
+impl TouchMotionEvent<SimulatedInputBackend> for SimulatedAbsoluteEvent {}
+
+/// Button press or release of a simulated pointer
+#[derive(Debug, Clone)]
+pub struct SimulatedPointerButtonEvent {
+    device: SimulatedDevice,
+    time: u64,
+    button: u32,
+    state: ButtonState,
+}
+
+impl Event<SimulatedInputBackend> for SimulatedPointerButtonEvent {
+    fn time(&self) -> u64 {
+        self.time
+    }
+
+    fn device(&self) -> SimulatedDevice {
+        self.device.clone()
+    }
+}
+
+impl PointerButtonEvent<SimulatedInputBackend> for SimulatedPointerButtonEvent {
+    fn button_code(&self) -> u32 {
+        self.button
+    }
+
+    fn state(&self) -> ButtonState {
+        self.state
+    }
+}
+
+/// Scroll event of a simulated pointer
+#[derive(Debug, Clone)]
+pub struct SimulatedPointerAxisEvent {
+    device: SimulatedDevice,
+    time: u64,
+    source: AxisSource,
+    horizontal: f64,
+    vertical: f64,
+}
+
+impl Event<SimulatedInputBackend> for SimulatedPointerAxisEvent {
+    fn time(&self) -> u64 {
+        self.time
+    }
+
+    fn device(&self) -> SimulatedDevice {
+        self.device.clone()
+    }
+}
+
+impl PointerAxisEvent<SimulatedInputBackend> for SimulatedPointerAxisEvent {
+    fn amount(&self, axis: Axis) -> Option<f64> {
+        let amount = match axis {
+            Axis::Horizontal => self.horizontal,
+            Axis::Vertical => self.vertical,
+        };
+        (amount != 0.0 || self.source == AxisSource::Finger).then_some(amount)
+    }
+
+    fn amount_v120(&self, axis: Axis) -> Option<f64> {
+        // wheel events use the common 15 degrees per detent
+        if matches!(self.source, AxisSource::Wheel | AxisSource::WheelTilt) {
+            self.amount(axis).map(|amount| amount / 15.0 * 120.0)
+        } else {
+            None
+        }
+    }
+
+    fn source(&self) -> AxisSource {
+        self.source
+    }
+
+    fn relative_direction(&self, _axis: Axis) -> AxisRelativeDirection {
+        AxisRelativeDirection::Identical
+    }
+}
+
+/// Touch up, cancel or frame event of a simulated touch device
+#[derive(Debug, Clone)]
+pub struct SimulatedTouchEvent {
+    device: SimulatedDevice,
+    time: u64,
+    slot: TouchSlot,
+}
+
+impl Event<SimulatedInputBackend> for SimulatedTouchEvent {
+    fn time(&self) -> u64 {
+        self.time
+    }
+
+    fn device(&self) -> SimulatedDevice {
+        self.device.clone()
+    }
+}
+
+impl TouchEvent<SimulatedInputBackend> for SimulatedTouchEvent {
+    fn slot(&self) -> TouchSlot {
+        self.slot
+    }
+}
+
+impl TouchUpEvent<SimulatedInputBackend> for SimulatedTouchEvent {}
+impl TouchCancelEvent<SimulatedInputBackend> for SimulatedTouchEvent {}
+impl TouchFrameEvent<SimulatedInputBackend> for SimulatedTouchEvent {}
+
+/// Handle to inject events into a [`SimulatedInputSource`]
+///
+/// All events take an explicit timestamp in microseconds, so tests behave deterministically.
+/// Events sent after the source was dropped are discarded.
+#[derive(Debug, Clone)]
+pub struct SimulatedInputHandle {
+    sender: Sender<InputEvent<SimulatedInputBackend>>,
+}
+
+impl SimulatedInputHandle {
+    fn send(&self, event: InputEvent<SimulatedInputBackend>) {
+        let _ = self.sender.send(event);
+    }
+
+    /// Add a new device with the given capabilities
+    pub fn add_device(&self, name: &str, capabilities: &[DeviceCapability]) -> SimulatedDevice {
+        let device = SimulatedDevice {
+            id: DEVICE_ID.fetch_add(1, Ordering::Relaxed),
+            name: name.into(),
+            capabilities: capabilities.into(),
+        };
+        self.send(InputEvent::DeviceAdded {
+            device: device.clone(),
+        });
+        device
+    }
+
+    /// Remove a device
+    pub fn remove_device(&self, device: &SimulatedDevice) {
+        self.send(InputEvent::DeviceRemoved {
+            device: device.clone(),
+        });
+    }
+
+    /// Press or release a key
+    ///
+    /// `key` is the evdev keycode of the key, without the xkb offset of 8.
+    pub fn key(&self, device: &SimulatedDevice, time: u64, key: u32, state: KeyState) {
+        self.send(InputEvent::Keyboard {
+            event: SimulatedKeyboardKeyEvent {
+                device: device.clone(),
+                time,
+                key: (key + 8).into(),
+                state,
+                count: (state == KeyState::Pressed) as u32,
+            },
+        });
+    }
+
+    /// Move a pointer relative to its current position
+    pub fn pointer_motion(&self, device: &SimulatedDevice, time: u64, dx: f64, dy: f64) {
+        self.send(InputEvent::PointerMotion {
+            event: SimulatedPointerMotionEvent {
+                device: device.clone(),
+                time,
+                dx,
+                dy,
+            },
+        });
+    }
+
+    /// Move a pointer to an absolute position, normalized to `0.0..=1.0`
+    pub fn pointer_motion_absolute(&self, device: &SimulatedDevice, time: u64, x: f64, y: f64) {
+        self.send(InputEvent::PointerMotionAbsolute {
+            event: SimulatedAbsoluteEvent {
+                device: device.clone(),
+                time,
+                slot: TouchSlot::default(),
+                x,
+                y,
+            },
+        });
+    }
+
+    /// Press or release a pointer button
+    ///
+    /// `button` is the evdev code of the button, e.g. `0x110` for the left button.
+    pub fn button(&self, device: &SimulatedDevice, time: u64, button: u32, state: ButtonState) {
+        self.send(InputEvent::PointerButton {
+            event: SimulatedPointerButtonEvent {
+                device: device.clone(),
+                time,
+                button,
+                state,
+            },
+        });
+    }
+
+    /// Scroll by the given amounts
+    ///
+    /// For [`AxisSource::Wheel`] the amounts are given in degrees, a detent usually being 15 degrees.
+    pub fn axis(
+        &self,
+        device: &SimulatedDevice,
+        time: u64,
+        source: AxisSource,
+        horizontal: f64,
+        vertical: f64,
+    ) {
+        self.send(InputEvent::PointerAxis {
+            event: SimulatedPointerAxisEvent {
+                device: device.clone(),
+                time,
+                source,
+                horizontal,
+                vertical,
+            },
+        });
+    }
+
+    /// Put a touch point down at an absolute position, normalized to `0.0..=1.0`
+    pub fn touch_down(&self, device: &SimulatedDevice, time: u64, slot: u32, x: f64, y: f64) {
+        self.send(InputEvent::TouchDown {
+            event: SimulatedAbsoluteEvent {
+                device: device.clone(),
+                time,
+                slot: Some(slot).into(),
+                x,
+                y,
+            },
+        });
+    }
+
+    /// Move a touch point to an absolute position, normalized to `0.0..=1.0`
+    pub fn touch_motion(&self, device: &SimulatedDevice, time: u64, slot: u32, x: f64, y: f64) {
+        self.send(InputEvent::TouchMotion {
+            event: SimulatedAbsoluteEvent {
+                device: device.clone(),
+                time,
+                slot: Some(slot).into(),
+                x,
+                y,
+            },
+        });
+    }
+
+    /// Lift a touch point
+    pub fn touch_up(&self, device: &SimulatedDevice, time: u64, slot: u32) {
+        self.send(InputEvent::TouchUp {
+            event: SimulatedTouchEvent {
+                device: device.clone(),
+                time,
+                slot: Some(slot).into(),
+            },
+        });
+    }
+
+    /// Finish a set of touch events, that logically belong together
+    pub fn touch_frame(&self, device: &SimulatedDevice, time: u64) {
+        self.send(InputEvent::TouchFrame {
+            event: SimulatedTouchEvent {
+                device: device.clone(),
+                time,
+                slot: TouchSlot::default(),
+            },
+        });
+    }
+
+    /// Cancel the current touch sequence
+    pub fn touch_cancel(&self, device: &SimulatedDevice, time: u64) {
+        self.send(InputEvent::TouchCancel {
+            event: SimulatedTouchEvent {
+                device: device.clone(),
+                time,
+                slot: TouchSlot::default(),
+            },
+        });
+    }
+
+    /// Inject the events described by a script
+    ///
+    /// Every non-empty line not starting with `#` describes one event, starting with its timestamp in
+    /// milliseconds, followed by a command and its arguments. Devices are referred to by the name given
+    /// when adding them with `device`:
+    ///
+    /// ```text
+    /// 0    device kbd keyboard
+    /// 0    device mouse pointer
+    /// 0    device screen touch
+    /// 10   key kbd 30 press
+    /// 20   key kbd 30 release
+    /// 30   motion mouse 5.0 -3.0
+    /// 40   absolute mouse 0.5 0.5
+    /// 50   button mouse 272 press
+    /// 60   button mouse 272 release
+    /// 70   scroll mouse 0.0 15.0
+    /// 80   touch-down screen 0 0.25 0.25
+    /// 80   touch-frame screen
+    /// 90   touch-motion screen 0 0.5 0.5
+    /// 90   touch-frame screen
+    /// 100  touch-up screen 0
+    /// 100  touch-frame screen
+    /// 110  touch-cancel screen
+    /// 120  remove kbd
+    /// ```
+    ///
+    /// Capabilities of `device` are a comma-separated list of `keyboard`, `pointer` and `touch`.
+    /// Scrolling uses [`AxisSource::Wheel`]. The script is validated completely before any event is
+    /// injected.
+    pub fn run_script(&self, script: &str) -> Result<(), ScriptError> {
+        let mut devices = HashMap::new();
+        let mut events = Vec::new();
+
+        for (idx, line) in script.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |kind| ScriptError { line: idx + 1, kind };
+
+            let mut args = line.split_whitespace();
+            let time = args
+                .next()
+                .and_then(|time| time.parse::<f64>().ok())
+                .filter(|time| time.is_finite() && *time >= 0.0)
+                .ok_or(error(ScriptErrorKind::InvalidTime))?;
+            let time = (time * 1000.0) as u64;
+            let command = args.next().ok_or(error(ScriptErrorKind::MissingCommand))?;
+            let args = args.collect::<Vec<_>>();
+
+            let arity = match command {
+                "device" => 2,
+                "remove" | "touch-frame" | "touch-cancel" => 1,
+                "touch-up" => 2,
+                "key" | "motion" | "absolute" | "button" | "scroll" => 3,
+                "touch-down" | "touch-motion" => 4,
+                _ => return Err(error(ScriptErrorKind::UnknownCommand(command.to_string()))),
+            };
+            if args.len() != arity {
+                return Err(error(ScriptErrorKind::InvalidArguments));
+            }
+
+            if command == "device" {
+                let capabilities = args[1]
+                    .split(',')
+                    .map(|capability| match capability {
+                        "keyboard" => Ok(DeviceCapability::Keyboard),
+                        "pointer" => Ok(DeviceCapability::Pointer),
+                        "touch" => Ok(DeviceCapability::Touch),
+                        _ => Err(error(ScriptErrorKind::InvalidArguments)),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                devices.insert(args[0], capabilities.clone());
+                events.push(ScriptEvent::Add(args[0], capabilities));
+                continue;
+            }
+
+            if !devices.contains_key(args[0]) {
+                return Err(error(ScriptErrorKind::UnknownDevice(args[0].to_string())));
+            }
+            let float = |arg: &str| {
+                arg.parse::<f64>()
+                    .ok()
+                    .filter(|value| value.is_finite())
+                    .ok_or(error(ScriptErrorKind::InvalidArguments))
+            };
+            let int = |arg: &str| {
+                arg.parse::<u32>()
+                    .map_err(|_| error(ScriptErrorKind::InvalidArguments))
+            };
+            let pressed = |arg: &str| match arg {
+                "press" => Ok(true),
+                "release" => Ok(false),
+                _ => Err(error(ScriptErrorKind::InvalidArguments)),
+            };
+
+            let action = match command {
+                "remove" => {
+                    devices.remove(args[0]);
+                    ScriptAction::Remove
+                }
+                "key" => ScriptAction::Key(int(args[1])?, pressed(args[2])?),
+                "motion" => ScriptAction::Motion(float(args[1])?, float(args[2])?),
+                "absolute" => ScriptAction::Absolute(float(args[1])?, float(args[2])?),
+                "button" => ScriptAction::Button(int(args[1])?, pressed(args[2])?),
+                "scroll" => ScriptAction::Scroll(float(args[1])?, float(args[2])?),
+                "touch-down" => ScriptAction::TouchDown(int(args[1])?, float(args[2])?, float(args[3])?),
+                "touch-motion" => ScriptAction::TouchMotion(int(args[1])?, float(args[2])?, float(args[3])?),
+                "touch-up" => ScriptAction::TouchUp(int(args[1])?),
+                "touch-frame" => ScriptAction::TouchFrame,
+                "touch-cancel" => ScriptAction::TouchCancel,
+                _ => unreachable!(),
+            };
+            events.push(ScriptEvent::Action(args[0], time, action));
+        }
+
+        let mut devices = HashMap::new();
+        for event in events {
+            let (name, time, action) = match event {
+                ScriptEvent::Add(name, capabilities) => {
+                    devices.insert(name, self.add_device(name, &capabilities));
+                    continue;
+                }
+                ScriptEvent::Action(name, time, action) => (name, time, action),
+            };
+            let device = &devices[name];
+
+            match action {
+                ScriptAction::Remove => {
+                    self.remove_device(device);
+                    devices.remove(name);
+                }
+                ScriptAction::Key(key, pressed) => {
+                    let state = if pressed {
+                        KeyState::Pressed
+                    } else {
+                        KeyState::Released
+                    };
+                    self.key(device, time, key, state);
+                }
+                ScriptAction::Motion(dx, dy) => self.pointer_motion(device, time, dx, dy),
+                ScriptAction::Absolute(x, y) => self.pointer_motion_absolute(device, time, x, y),
+                ScriptAction::Button(button, pressed) => {
+                    let state = if pressed {
+                        ButtonState::Pressed
+                    } else {
+                        ButtonState::Released
+                    };
+                    self.button(device, time, button, state);
+                }
+                ScriptAction::Scroll(horizontal, vertical) => {
+                    self.axis(device, time, AxisSource::Wheel, horizontal, vertical)
+                }
+                ScriptAction::TouchDown(slot, x, y) => self.touch_down(device, time, slot, x, y),
+                ScriptAction::TouchMotion(slot, x, y) => self.touch_motion(device, time, slot, x, y),
+                ScriptAction::TouchUp(slot) => self.touch_up(device, time, slot),
+                ScriptAction::TouchFrame => self.touch_frame(device, time),
+                ScriptAction::TouchCancel => self.touch_cancel(device, time),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Inject the events described by a script file, see [`SimulatedInputHandle::run_script`]
+    pub fn run_script_file(&self, path: impl AsRef<std::path::Path>) -> Result<(), ScriptError> {
+        let script = std::fs::read_to_string(path).map_err(|err| ScriptError {
+            line: 0,
+            kind: ScriptErrorKind::Io(err),
+        })?;
+        self.run_script(&script)
+    }
+}
+
+enum ScriptEvent<'a> {
+    Add(&'a str, Vec<DeviceCapability>),
+    Action(&'a str, u64, ScriptAction),
+}
+
+enum ScriptAction {
+    Remove,
+    Key(u32, bool),
+    Motion(f64, f64),
+    Absolute(f64, f64),
+    Button(u32, bool),
+    Scroll(f64, f64),
+    TouchDown(u32, f64, f64),
+    TouchMotion(u32, f64, f64),
+    TouchUp(u32),
+    TouchFrame,
+    TouchCancel,
+}
+
+/// Error while running an input script
+#[derive(Debug, thiserror::Error)]
+#[error("Invalid input script at line {line}: {kind}")]
+pub struct ScriptError {
+    /// Line of the script the error occurred on, starting at 1, or 0 if the script could not be read
+    pub line: usize,
+    /// Kind of the error
+    pub kind: ScriptErrorKind,
+}
+
+/// Kinds of errors while running an input script
+#[derive(Debug, thiserror::Error)]
+pub enum ScriptErrorKind {
+    /// The script file could not be read
+    #[error("Failed to read the script: {0}")]
+    Io(#[from] std::io::Error),
+    /// The line doesn't start with a valid timestamp
+    #[error("Invalid timestamp")]
+    InvalidTime,
+    /// The line is missing a command
+    #[error("Missing command")]
+    MissingCommand,
+    /// The command is unknown
+    #[error("Unknown command {0:?}")]
+    UnknownCommand(String),
+    /// The device was not added before or was already removed
+    #[error("Unknown device {0:?}")]
+    UnknownDevice(String),
+    /// The arguments of the command are invalid
+    #[error("Invalid arguments")]
+    InvalidArguments,
+}
+
+/// Event source delivering the events injected through a [`SimulatedInputHandle`]
+#[derive(Debug)]
+pub struct SimulatedInputSource {
+    channel: Channel<InputEvent<SimulatedInputBackend>>,
+}
+
+impl SimulatedInputSource {
+    /// Create a new source and the handle to inject events into it
+    pub fn new() -> (Self, SimulatedInputHandle) {
+        let (sender, channel) = channel::channel();
+        (SimulatedInputSource { channel }, SimulatedInputHandle { sender })
+    }
+}
+
+impl EventSource for SimulatedInputSource {
+    type Event = InputEvent<SimulatedInputBackend>;
+    type Metadata = ();
+    type Ret = ();
+    type Error = channel::ChannelError;
+
+    fn process_events<F>(
+        &mut self,
+        readiness: Readiness,
+        token: Token,
+        mut callback: F,
+    ) -> Result<PostAction, Self::Error>
+    where
+        F: FnMut(Self::Event, &mut Self::Metadata) -> Self::Ret,
+    {
+        self.channel.process_events(readiness, token, |event, _| {
+            if let channel::Event::Msg(event) = event {
+                callback(event, &mut ());
+            }
+        })
+    }
+
+    fn register(&mut self, poll: &mut Poll, token_factory: &mut TokenFactory) -> calloop::Result<()> {
+        self.channel.register(poll, token_factory)
+    }
+
+    fn reregister(&mut self, poll: &mut Poll, token_factory: &mut TokenFactory) -> calloop::Result<()> {
+        self.channel.reregister(poll, token_factory)
+    }
+
+    fn unregister(&mut self, poll: &mut Poll) -> calloop::Result<()> {
+        self.channel.unregister(poll)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn script() {
+        let mut event_loop = calloop::EventLoop::<Vec<_>>::try_new().unwrap();
+        let (source, handle) = SimulatedInputSource::new();
+        event_loop
+            .handle()
+            .insert_source(source, |event, _, events| events.push(event))
+            .unwrap();
+        handle
+            .run_script("0 device kbd keyboard\n# comment\n\n10 key kbd 30 press\n12.5 remove kbd\n")
+            .unwrap();
+
+        let mut events = Vec::new();
+        event_loop
+            .dispatch(Some(std::time::Duration::ZERO), &mut events)
+            .unwrap();
+        assert!(matches!(&events[..], [
+            InputEvent::DeviceAdded { .. },
+            InputEvent::Keyboard { event },
+            InputEvent::DeviceRemoved { .. },
+        ] if event.time() == 10_000 && event.key_code() == 38.into()));
+
+        let err = handle
+            .run_script("0 device kbd keyboard\n1 key mouse 30 press")
+            .unwrap_err();
+        assert_eq!(err.line, 2);
+        assert!(matches!(err.kind, ScriptErrorKind::UnknownDevice(_)));
+    }
+}
@@ -32,6 +32,11 @@
 //! }).expect("Failed to insert the udev source into the event loop");
 //! ```
 //!
+//! Devices appearing after initialization, like external gpus or usb display adapters, are reported via
+//! [`UdevEvent::Added`] and can be opened like any device of the initial list.
+//! Once a device disappears, [`UdevEvent::Removed`] is emitted and all state referring to it,
+//! e.g. the [`DrmDevice`](crate::backend::drm::DrmDevice) and its surfaces, should be dropped.
+//!
 //! Additionally this contains some utility functions related to scanning.
 //!
 //! See also `anvil/src/udev.rs` for pure hardware backed example of a compositor utilizing this
@@ -58,6 +63,7 @@ use tracing::{debug, debug_span, info, warn};
 /// given handler of any changes. Can be used to provide hot-plug functionality for gpus and
 /// attached monitors.
 pub struct UdevBackend {
+    seat: String,
     devices: HashMap<dev_t, PathBuf>,
     monitor: MonitorSocket,
     token: Option<Token>,
@@ -69,6 +75,7 @@ impl fmt::Debug for UdevBackend {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         use udev::AsRaw;
         f.debug_struct("UdevBackend")
+            .field("seat", &self.seat)
             .field("devices", &self.devices)
             .field("monitor", &format!("MonitorSocket ({:?})", self.monitor.as_raw()))
             .finish()
@@ -107,6 +114,7 @@ impl UdevBackend {
 
         drop(_guard);
         Ok(UdevBackend {
+            seat: seat.to_string(),
             devices,
             monitor,
             token: None,
@@ -114,6 +122,11 @@ impl UdevBackend {
         })
     }
 
+    /// Returns the name of the seat this backend is monitoring
+    pub fn seat(&self) -> &str {
+        &self.seat
+    }
+
     /// Get a list of DRM devices currently known to the backend
     ///
     /// You should call this once before inserting the event source into your
@@ -153,7 +166,12 @@ impl EventSource for UdevBackend {
             );
             match event.event_type() {
                 // New device
+                // Only primary nodes of the bound seat are tracked, like in `all_gpus`.
+                // Render nodes and connectors are part of the same subsystem.
                 EventType::Add => {
+                    if !is_gpu_of_seat(&event, &self.seat) {
+                        continue;
+                    }
                     if let (Some(path), Some(devnum)) = (event.devnode(), event.devnum()) {
                         info!("New device: #{} at {}", devnum, path.display());
                         if self.devices.insert(devnum, path.to_path_buf()).is_none() {
@@ -242,11 +260,7 @@ pub fn primary_gpu<S: AsRef<str>>(seat: S) -> io::Result<Option<PathBuf>> {
     if let Some(path) = enumerator
         .scan_devices()?
         .filter(|device| {
-            let seat_name = device
-                .property_value("ID_SEAT")
-                .map(|x| x.to_os_string())
-                .unwrap_or_else(|| OsString::from("seat0"));
-            if seat_name == *seat.as_ref() {
+            if device_seat(device) == *seat.as_ref() {
                 if let Ok(Some(pci)) = device.parent_with_subsystem(Path::new("pci")) {
                     if let Some(id) = pci.attribute_value("boot_vga") {
                         return id == "1";
@@ -269,22 +283,109 @@ pub fn primary_gpu<S: AsRef<str>>(seat: S) -> io::Result<Option<PathBuf>> {
 /// Might be used for manual  [`DrmDevice`](crate::backend::drm::DrmDevice)
 /// initialization.
 pub fn all_gpus<S: AsRef<str>>(seat: S) -> io::Result<Vec<PathBuf>> {
+    let mut enumerator = Enumerator::new()?;
+    enumerator.match_subsystem("drm")?;
+    enumerator.match_sysname("card[0-9]*")?;
+    let mut gpus = enumerator
+        .scan_devices()?
+        .filter(|device| is_gpu_of_seat(device, seat.as_ref()))
+        .flat_map(|device| device.devnode().map(PathBuf::from))
+        .collect::<Vec<_>>();
+    gpus.sort();
+    Ok(gpus)
+}
+
+fn is_gpu_of_seat(device: &udev::Device, seat: &str) -> bool {
+    let is_card = device
+        .sysname()
+        .to_str()
+        .and_then(|name| name.strip_prefix("card"))
+        .map(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()))
+        .unwrap_or(false);
+    is_card && device_seat(device) == *seat
+}
+
+// devices without an explicit assignment belong to the default seat
+fn device_seat(device: &udev::Device) -> OsString {
+    device
+        .property_value("ID_SEAT")
+        .map(|x| x.to_os_string())
+        .unwrap_or_else(|| OsString::from("seat0"))
+}
+
+/// Returns the names of all seats with assigned gpu or input devices
+///
+/// Devices are assigned to seats via the `ID_SEAT` udev property, e.g. by `loginctl attach`.
+/// `seat0` is always part of the list. A compositor driving multiple seats can create a
+/// [`UdevBackend`] and a libinput context (see `Libinput::udev_assign_seat`) per seat,
+/// each only reporting the devices of its seat.
+pub fn all_seats() -> io::Result<Vec<String>> {
+    let mut seats = vec![String::from("seat0")];
+    for subsystem in ["drm", "input"] {
+        let mut enumerator = Enumerator::new()?;
+        enumerator.match_subsystem(subsystem)?;
+        for device in enumerator.scan_devices()? {
+            if let Some(seat) = device_seat(&device).to_str() {
+                if !seats.iter().any(|s| s == seat) {
+                    seats.push(seat.to_string());
+                }
+            }
+        }
+    }
+    seats.sort();
+    Ok(seats)
+}
+
+/// Returns the seat a device named by it's [`dev_t`] is assigned to
+///
+/// Returns `None` if no gpu or input device with the given id exists.
+pub fn seat_of_device(dev: dev_t) -> io::Result<Option<String>> {
+    for subsystem in ["drm", "input"] {
+        let mut enumerator = Enumerator::new()?;
+        enumerator.match_subsystem(subsystem)?;
+        if let Some(device) = enumerator
+            .scan_devices()?
+            .find(|device| device.devnum() == Some(dev))
+        {
+            return Ok(Some(device_seat(&device).to_string_lossy().into_owned()));
+        }
+    }
+    Ok(None)
+}
+
+/// Returns the path of the first device driven by the virtual kms (`vkms`) driver if any
+///
+/// `vkms` is a software-only kms driver, that can be loaded via `modprobe vkms`. It allows
+/// running real modesetting code paths without any display hardware, e.g. for integration
+/// tests in CI. It exposes a single virtual connector and does not provide a render node,
+/// so rendering needs to happen on another gpu or in software.
+pub fn vkms_gpu() -> io::Result<Option<PathBuf>> {
     let mut enumerator = Enumerator::new()?;
     enumerator.match_subsystem("drm")?;
     enumerator.match_sysname("card[0-9]*")?;
     let mut gpus = enumerator
         .scan_devices()?
         .filter(|device| {
-            device
-                .property_value("ID_SEAT")
-                .map(|x| x.to_os_string())
-                .unwrap_or_else(|| OsString::from("seat0"))
-                == *seat.as_ref()
+            device_driver(device)
+                .map(|driver| driver == "vkms")
+                .unwrap_or(false)
         })
         .flat_map(|device| device.devnode().map(PathBuf::from))
         .collect::<Vec<_>>();
     gpus.sort();
-    Ok(gpus)
+    Ok(gpus.into_iter().next())
+}
+
+// the driver is usually bound to a parent of the drm device
+fn device_driver(device: &udev::Device) -> Option<OsString> {
+    let mut device = Some(device.clone());
+    while let Some(dev) = device {
+        if let Some(driver) = dev.driver() {
+            return Some(driver.to_os_string());
+        }
+        device = dev.parent();
+    }
+    None
 }
 
 /// Returns the loaded driver for a device named by it's [`dev_t`].
@@ -295,15 +396,243 @@ pub fn driver(dev: dev_t) -> io::Result<Option<OsString>> {
     Ok(enumerator
         .scan_devices()?
         .filter(|device| device.devnum() == Some(dev))
-        .flat_map(|dev| {
-            let mut device = Some(dev);
-            while let Some(dev) = device {
-                if dev.driver().is_some() {
-                    return dev.driver().map(std::ffi::OsStr::to_os_string);
-                }
-                device = dev.parent();
-            }
-            None
-        })
+        .flat_map(|device| device_driver(&device))
         .next())
 }
+
+/// Classes of input devices as tagged by udev via the `ID_INPUT_*` properties
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InputClass {
+    /// `ID_INPUT_KEYBOARD`
+    Keyboard,
+    /// `ID_INPUT_KEY`, devices with any keys, e.g. power buttons
+    Key,
+    /// `ID_INPUT_MOUSE`
+    Mouse,
+    /// `ID_INPUT_TOUCHPAD`
+    Touchpad,
+    /// `ID_INPUT_TOUCHSCREEN`
+    Touchscreen,
+    /// `ID_INPUT_TABLET`
+    Tablet,
+    /// `ID_INPUT_TABLET_PAD`
+    TabletPad,
+    /// `ID_INPUT_POINTINGSTICK`
+    PointingStick,
+    /// `ID_INPUT_TRACKBALL`
+    Trackball,
+    /// `ID_INPUT_JOYSTICK`
+    Joystick,
+    /// `ID_INPUT_SWITCH`
+    Switch,
+}
+
+impl InputClass {
+    fn property(&self) -> &'static str {
+        match self {
+            InputClass::Keyboard => "ID_INPUT_KEYBOARD",
+            InputClass::Key => "ID_INPUT_KEY",
+            InputClass::Mouse => "ID_INPUT_MOUSE",
+            InputClass::Touchpad => "ID_INPUT_TOUCHPAD",
+            InputClass::Touchscreen => "ID_INPUT_TOUCHSCREEN",
+            InputClass::Tablet => "ID_INPUT_TABLET",
+            InputClass::TabletPad => "ID_INPUT_TABLET_PAD",
+            InputClass::PointingStick => "ID_INPUT_POINTINGSTICK",
+            InputClass::Trackball => "ID_INPUT_TRACKBALL",
+            InputClass::Joystick => "ID_INPUT_JOYSTICK",
+            InputClass::Switch => "ID_INPUT_SWITCH",
+        }
+    }
+}
+
+/// Rule matching udev devices by their properties
+///
+/// Meant to be used for configuration driven device rules, e.g. to apply settings to a
+/// specific input device or to select a gpu. Every criteria set needs to match, an empty
+/// matcher matches every device.
+///
+/// Input devices can be matched via their [`syspath`](crate::backend::input::Device::syspath),
+/// drm devices via their [`dev_t`].
+///
+/// ```no_run
+/// use smithay::backend::udev::{DeviceMatcher, InputClass};
+///
+/// let matcher = DeviceMatcher::new()
+///     .input_class(InputClass::Touchpad)
+///     .vendor_id(0x046d)
+///     .id_path("pci-0000:00:14.0-usb-*");
+/// # let syspath = std::path::Path::new("/sys/devices/virtual/input/input0");
+/// if matcher.matches_syspath(syspath).unwrap_or(false) {
+///     // apply touchpad settings
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeviceMatcher {
+    vendor_id: Option<u32>,
+    product_id: Option<u32>,
+    input_classes: Vec<InputClass>,
+    seat: Option<String>,
+    id_path: Option<String>,
+}
+
+impl DeviceMatcher {
+    /// Create a new matcher matching every device
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only match devices with the given vendor id
+    ///
+    /// This is the usb vendor id for usb devices and the pci vendor id for pci devices.
+    pub fn vendor_id(mut self, vendor_id: u32) -> Self {
+        self.vendor_id = Some(vendor_id);
+        self
+    }
+
+    /// Only match devices with the given product id
+    ///
+    /// This is the usb product id for usb devices and the pci device id for pci devices.
+    pub fn product_id(mut self, product_id: u32) -> Self {
+        self.product_id = Some(product_id);
+        self
+    }
+
+    /// Only match input devices of the given class
+    ///
+    /// Can be called multiple times to require multiple classes.
+    pub fn input_class(mut self, class: InputClass) -> Self {
+        if !self.input_classes.contains(&class) {
+            self.input_classes.push(class);
+        }
+        self
+    }
+
+    /// Only match devices assigned to the given seat
+    pub fn seat(mut self, seat: impl Into<String>) -> Self {
+        self.seat = Some(seat.into());
+        self
+    }
+
+    /// Only match devices with an `ID_PATH` matching the given glob pattern
+    ///
+    /// `*` matches any number of characters, `?` matches a single character.
+    pub fn id_path(mut self, pattern: impl Into<String>) -> Self {
+        self.id_path = Some(pattern.into());
+        self
+    }
+
+    /// Returns `true` if the given device matches
+    pub fn matches(&self, device: &udev::Device) -> bool {
+        if let Some(vendor_id) = self.vendor_id {
+            if device_id(device, "ID_VENDOR_ID", "vendor") != Some(vendor_id) {
+                return false;
+            }
+        }
+        if let Some(product_id) = self.product_id {
+            if device_id(device, "ID_MODEL_ID", "device") != Some(product_id) {
+                return false;
+            }
+        }
+        if !self.input_classes.iter().all(|class| {
+            device
+                .property_value(class.property())
+                .map(|v| v == "1")
+                .unwrap_or(false)
+        }) {
+            return false;
+        }
+        if let Some(seat) = self.seat.as_deref() {
+            if device_seat(device) != *seat {
+                return false;
+            }
+        }
+        if let Some(pattern) = self.id_path.as_deref() {
+            let id_path = device.property_value("ID_PATH").and_then(|x| x.to_str());
+            if !id_path
+                .map(|id_path| glob_match(pattern, id_path))
+                .unwrap_or(false)
+            {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Returns `true` if the device at the given sysfs path matches
+    pub fn matches_syspath(&self, syspath: &Path) -> io::Result<bool> {
+        let device = udev::Device::from_syspath(syspath)?;
+        Ok(self.matches(&device))
+    }
+
+    /// Returns `true` if the character device named by it's [`dev_t`] matches
+    pub fn matches_devnum(&self, dev: dev_t) -> io::Result<bool> {
+        let device = udev::Device::from_devnum(udev::DeviceType::Character, dev)?;
+        Ok(self.matches(&device))
+    }
+}
+
+// ids are found either as udev properties (usb) or sysfs attributes on a parent (pci)
+fn device_id(device: &udev::Device, property: &str, attribute: &str) -> Option<u32> {
+    let parse = |value: &std::ffi::OsStr| {
+        let value = value.to_str()?;
+        u32::from_str_radix(value.trim_start_matches("0x"), 16).ok()
+    };
+
+    if let Some(id) = device.property_value(property).and_then(parse) {
+        return Some(id);
+    }
+    let mut device = Some(device.clone());
+    while let Some(dev) = device {
+        if dev.subsystem().map(|s| s == "pci").unwrap_or(false) {
+            return dev.attribute_value(attribute).and_then(parse);
+        }
+        device = dev.parent();
+    }
+    None
+}
+
+fn glob_match(pattern: &str, value: &str) -> bool {
+    let pattern = pattern.as_bytes();
+    let value = value.as_bytes();
+    let (mut p, mut v) = (0, 0);
+    let mut backtrack = None;
+
+    while v < value.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                backtrack = Some((p, v));
+                p += 1;
+            }
+            Some(&c) if c == b'?' || c == value[v] => {
+                p += 1;
+                v += 1;
+            }
+            _ => match backtrack {
+                Some((bp, bv)) => {
+                    p = bp + 1;
+                    v = bv + 1;
+                    backtrack = Some((bp, bv + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::glob_match;
+
+    #[test]
+    fn glob() {
+        assert!(glob_match(
+            "pci-0000:00:14.0-usb-*",
+            "pci-0000:00:14.0-usb-0:2:1.0"
+        ));
+        assert!(glob_match("*usb-0:?:1.0", "pci-0000:00:14.0-usb-0:2:1.0"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("pci-*-usb-0:3*", "pci-0000:00:14.0-usb-0:2:1.0"));
+        assert!(!glob_match("platform-*", "pci-0000:00:14.0-usb-0:2:1.0"));
+    }
+}
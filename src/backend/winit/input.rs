@@ -408,6 +408,9 @@ impl InputBackend for WinitInput {
     type TabletToolTipEvent = UnusedEvent;
     type TabletToolButtonEvent = UnusedEvent;
 
+    type TabletPadButtonEvent = UnusedEvent;
+    type TabletPadRingEvent = UnusedEvent;
+    type TabletPadStripEvent = UnusedEvent;
     type SwitchToggleEvent = UnusedEvent;
 
     type SpecialEvent = UnusedEvent;
@@ -323,6 +323,35 @@ where
         }
     }
 
+    /// Limits the region of the current backbuffer updated in this frame, if supported (see
+    /// [`EGLSurface::set_damage_region`]).
+    ///
+    /// This has to be called after [`WinitGraphicsBackend::buffer_age`] and before rendering,
+    /// `damage` has to contain all regions rendered to in this frame.
+    #[instrument(level = "trace", parent = &self.span, skip(self))]
+    pub fn set_damage_region(
+        &self,
+        damage: &[Rectangle<i32, Physical>],
+    ) -> Result<(), crate::backend::egl::EGLError> {
+        if !self.damage_tracking || damage.is_empty() {
+            return Ok(());
+        }
+        self.egl_surface.set_damage_region(&self.flip_damage(damage))
+    }
+
+    // EGL expects damage relative to the bottom-left corner
+    fn flip_damage(&self, damage: &[Rectangle<i32, Physical>]) -> Vec<Rectangle<i32, Physical>> {
+        let bind_size = self
+            .bind_size
+            .expect("using damage without ever binding the renderer.");
+        damage
+            .iter()
+            .map(|rect| {
+                Rectangle::from_loc_and_size((rect.loc.x, bind_size.h - rect.loc.y - rect.size.h), rect.size)
+            })
+            .collect()
+    }
+
     /// Submits the back buffer to the window by swapping, requires the window to be previously
     /// bound (see [`WinitGraphicsBackend::bind`]).
     #[instrument(level = "trace", parent = &self.span, skip(self))]
@@ -332,21 +361,7 @@ where
         damage: Option<&[Rectangle<i32, Physical>]>,
     ) -> Result<(), crate::backend::SwapBuffersError> {
         let mut damage = match damage {
-            Some(damage) if self.damage_tracking && !damage.is_empty() => {
-                let bind_size = self
-                    .bind_size
-                    .expect("submitting without ever binding the renderer.");
-                let damage = damage
-                    .iter()
-                    .map(|rect| {
-                        Rectangle::from_loc_and_size(
-                            (rect.loc.x, bind_size.h - rect.loc.y - rect.size.h),
-                            rect.size,
-                        )
-                    })
-                    .collect::<Vec<_>>();
-                Some(damage)
-            }
+            Some(damage) if self.damage_tracking && !damage.is_empty() => Some(self.flip_damage(damage)),
             _ => None,
         };
 
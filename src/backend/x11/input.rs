@@ -256,6 +256,9 @@ impl InputBackend for X11Input {
     type TabletToolTipEvent = UnusedEvent;
     type TabletToolButtonEvent = UnusedEvent;
 
+    type TabletPadButtonEvent = UnusedEvent;
+    type TabletPadRingEvent = UnusedEvent;
+    type TabletPadStripEvent = UnusedEvent;
     type SwitchToggleEvent = UnusedEvent;
 
     type SpecialEvent = UnusedEvent;
@@ -1,21 +1,25 @@
 //! This module contains the [`Space`] helper class as well has related
 //! rendering helpers to add custom elements or different clients to a space.
 
-use crate::{
-    backend::renderer::{
-        damage::{Error as OutputDamageTrackerError, OutputDamageTracker, RenderOutputResult},
-        element::{AsRenderElements, RenderElement, Wrap},
-        Color32F, Renderer, Texture,
-    },
-    output::{Output, OutputModeSource, OutputNoMode},
-    utils::{IsAlive, Logical, Point, Rectangle, Scale, Transform},
-};
 #[cfg(feature = "wayland_frontend")]
 use crate::{
     backend::renderer::{element::surface::WaylandSurfaceRenderElement, ImportAll},
     desktop::{layer_map_for_output, LayerSurface, WindowSurfaceType},
     wayland::shell::wlr_layer::Layer,
 };
+use crate::{
+    backend::{
+        allocator::Fourcc,
+        renderer::{
+            damage::{Error as OutputDamageTrackerError, OutputDamageTracker, RenderOutputResult},
+            element::{AsRenderElements, RenderElement, Wrap},
+            sync::SyncPoint,
+            Color32F, Offscreen, Renderer, Texture,
+        },
+    },
+    output::{Output, OutputModeSource, OutputNoMode},
+    utils::{Buffer as BufferCoords, IsAlive, Logical, Physical, Point, Rectangle, Scale, Size, Transform},
+};
 use std::{collections::HashMap, fmt};
 use tracing::{debug, debug_span, instrument};
 #[cfg(feature = "wayland_frontend")]
@@ -704,3 +708,56 @@ where
 
     damage_tracker.render_output(renderer, age, &render_elements, clear_color)
 }
+
+/// Renders a single element into a new offscreen buffer
+///
+/// The element is rendered at its natural size, containing everything inside its
+/// [`bbox`](SpaceElement::bbox) (e.g. subsurfaces, popups or server-side decorations),
+/// independent of its position in any [`Space`], the outputs it is shown on or other elements
+/// occluding it. This can be used to implement screenshots or screencasts of single windows.
+///
+/// Returns the buffer, the area of the buffer relative to the element's [`geometry`](SpaceElement::geometry)
+/// and the [`SyncPoint`] of the rendering operation. The buffer is left bound to the renderer,
+/// so [`ExportMem::copy_framebuffer`](crate::backend::renderer::ExportMem::copy_framebuffer)
+/// can be used to read back its contents.
+#[instrument(level = "trace", skip(renderer, element))]
+#[profiling::function]
+pub fn render_element_offscreen<R, T, E>(
+    renderer: &mut R,
+    element: &E,
+    scale: Scale<f64>,
+    format: Fourcc,
+) -> Result<(T, Rectangle<i32, Physical>, SyncPoint), OutputDamageTrackerError<R>>
+where
+    R: Renderer + Offscreen<T>,
+    T: Clone,
+    <R as Renderer>::TextureId: Texture,
+    E: SpaceElement + AsRenderElements<R>,
+    <E as AsRenderElements<R>>::RenderElement: RenderElement<R>,
+{
+    let geometry = element.geometry();
+    let bbox: Rectangle<i32, Physical> = element.bbox().to_physical_precise_up(scale);
+    let geometry_loc: Point<i32, Physical> = geometry.loc.to_physical_precise_round(scale);
+
+    let size = Size::<i32, BufferCoords>::from((bbox.size.w.max(1), bbox.size.h.max(1)));
+    let buffer = renderer
+        .create_buffer(format, size)
+        .map_err(OutputDamageTrackerError::Rendering)?;
+
+    // place the top-left corner of the bbox at the origin of the buffer
+    let elements = element.render_elements::<<E as AsRenderElements<R>>::RenderElement>(
+        renderer,
+        (-bbox.loc.x, -bbox.loc.y).into(),
+        scale,
+        1.0,
+    );
+    let mut damage_tracker = OutputDamageTracker::new(bbox.size, scale, Transform::Normal);
+    let res =
+        damage_tracker.render_output_with(renderer, buffer.clone(), 0, &elements, Color32F::TRANSPARENT)?;
+
+    Ok((
+        buffer,
+        Rectangle::from_loc_and_size(bbox.loc - geometry_loc, bbox.size),
+        res.sync,
+    ))
+}
@@ -20,7 +20,10 @@ use wayland_server::protocol::wl_surface::{self, WlSurface};
 use std::{
     borrow::Cow,
     hash::{Hash, Hasher},
-    sync::{Arc, Mutex, MutexGuard},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
     time::Duration,
 };
 
@@ -153,6 +156,8 @@ impl LayerMap {
     }
 
     /// Returns a [`LayerSurface`] under a given point and on a given layer, if any.
+    ///
+    /// Input-transparent layer surfaces, see [`LayerSurface::set_input_transparent`], are skipped.
     pub fn layer_under<P: Into<Point<f64, Logical>>>(
         &self,
         layer: WlrLayer,
@@ -160,6 +165,9 @@ impl LayerMap {
     ) -> Option<&LayerSurface> {
         let point = point.into();
         self.layers_on(layer).rev().find(|l| {
+            if l.is_input_transparent() {
+                return false;
+            }
             let bbox_with_popups = {
                 let mut bbox = l.bbox_with_popups();
                 let state = layer_state(l);
@@ -495,6 +503,7 @@ pub(crate) struct LayerSurfaceInner {
     pub(crate) id: usize,
     surface: WlrLayerSurface,
     namespace: String,
+    input_transparent: AtomicBool,
     userdata: UserDataMap,
 }
 
@@ -519,6 +528,7 @@ impl LayerSurface {
             id: layer_id::next(),
             surface,
             namespace,
+            input_transparent: AtomicBool::new(false),
             userdata: UserDataMap::new(),
         }))
     }
@@ -712,6 +722,19 @@ impl LayerSurface {
         }
     }
 
+    /// Mark this layer surface as input-transparent
+    ///
+    /// Input-transparent layer surfaces are still rendered, but are skipped by
+    /// [`LayerMap::layer_under`], so click-through overlays never receive input.
+    pub fn set_input_transparent(&self, transparent: bool) {
+        self.0.input_transparent.store(transparent, Ordering::SeqCst);
+    }
+
+    /// Returns if this layer surface is input-transparent, see [`LayerSurface::set_input_transparent`]
+    pub fn is_input_transparent(&self) -> bool {
+        self.0.input_transparent.load(Ordering::SeqCst)
+    }
+
     /// Returns a [`UserDataMap`] to allow associating arbitrary data with this surface.
     pub fn user_data(&self) -> &UserDataMap {
         &self.0.userdata
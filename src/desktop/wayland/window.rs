@@ -9,13 +9,14 @@ use crate::{
         dmabuf::DmabufFeedback,
         seat::WaylandFocus,
         shell::xdg::{SurfaceCachedState, ToplevelSurface},
+        xdg_toplevel_icon::ToplevelIconCachedState,
     },
 };
 use std::{
     borrow::Cow,
     hash::{Hash, Hasher},
     sync::{
-        atomic::{AtomicU8, Ordering},
+        atomic::{AtomicBool, AtomicU8, Ordering},
         Arc, Mutex,
     },
     time::Duration,
@@ -43,6 +44,7 @@ pub(crate) struct WindowInner {
     surface: WindowSurface,
     bbox: Mutex<Rectangle<i32, Logical>>,
     pub(crate) z_index: AtomicU8,
+    input_transparent: AtomicBool,
     user_data: UserDataMap,
 }
 
@@ -119,6 +121,7 @@ impl Window {
             surface: WindowSurface::Wayland(toplevel),
             bbox: Mutex::new(Rectangle::from_loc_and_size((0, 0), (0, 0))),
             z_index: AtomicU8::new(RenderZindex::Shell as u8),
+            input_transparent: AtomicBool::new(false),
             user_data: UserDataMap::new(),
         }))
     }
@@ -133,6 +136,7 @@ impl Window {
             surface: WindowSurface::X11(surface),
             bbox: Mutex::new(Rectangle::from_loc_and_size((0, 0), (0, 0))),
             z_index: AtomicU8::new(RenderZindex::Shell as u8),
+            input_transparent: AtomicBool::new(false),
             user_data: UserDataMap::new(),
         }))
     }
@@ -330,7 +334,8 @@ impl Window {
     /// Finds the topmost surface under this point matching the input regions of the surface and returns
     /// it together with the location of this surface.
     ///
-    /// In case no surface input region matches the point [`None`] is returned.
+    /// In case no surface input region matches the point or the window is input-transparent,
+    /// see [`Window::set_input_transparent`], [`None`] is returned.
     ///
     /// - `point` should be relative to (0,0) of the window.
     pub fn surface_under<P: Into<Point<f64, Logical>>>(
@@ -338,6 +343,10 @@ impl Window {
         point: P,
         surface_type: WindowSurfaceType,
     ) -> Option<(wl_surface::WlSurface, Point<i32, Logical>)> {
+        if self.is_input_transparent() {
+            return None;
+        }
+
         let point = point.into();
         if let Some(surface) = self.wl_surface() {
             if surface_type.contains(WindowSurfaceType::POPUP) {
@@ -368,6 +377,23 @@ impl Window {
         }
     }
 
+    /// Returns the icon currently set via the xdg-toplevel-icon protocol
+    ///
+    /// Returns `None` for X11 windows and windows without an icon.
+    /// Taskbars or foreign-toplevel implementations can use the icon name or the
+    /// icon buffers to represent the window, see [`ToplevelIconCachedState`].
+    pub fn toplevel_icon(&self) -> Option<ToplevelIconCachedState> {
+        let toplevel = self.toplevel()?;
+        let icon = with_states(toplevel.wl_surface(), |states| {
+            states
+                .cached_state
+                .get::<ToplevelIconCachedState>()
+                .current()
+                .clone()
+        });
+        (icon.icon_name().is_some() || !icon.buffers().is_empty()).then_some(icon)
+    }
+
     /// Returns the underlying X11 surface
     #[cfg(feature = "xwayland")]
     pub fn x11_surface(&self) -> Option<&X11Surface> {
@@ -388,6 +414,22 @@ impl Window {
         self.0.z_index.store(z_index, Ordering::SeqCst);
     }
 
+    /// Mark this window as input-transparent
+    ///
+    /// Input-transparent windows (e.g. click-through overlays like on-screen rulers
+    /// or closed-caption layers) are still rendered, but are never part of the input region.
+    /// [`Window::surface_under`] returns no surface for them and
+    /// [`Space::element_under`](crate::desktop::Space::element_under) will thus
+    /// return the element below instead.
+    pub fn set_input_transparent(&self, transparent: bool) {
+        self.0.input_transparent.store(transparent, Ordering::SeqCst);
+    }
+
+    /// Returns if this window is input-transparent, see [`Window::set_input_transparent`]
+    pub fn is_input_transparent(&self) -> bool {
+        self.0.input_transparent.load(Ordering::SeqCst)
+    }
+
     /// Returns a [`UserDataMap`] to allow associating arbitrary data with this window.
     #[inline]
     pub fn user_data(&self) -> &UserDataMap {
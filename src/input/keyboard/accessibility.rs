@@ -0,0 +1,493 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+
+use xkbcommon::xkb::Keycode;
+
+use crate::backend::input::KeyState;
+
+// evdev codes of the modifier keys of a standard keyboard, offset by 8 for xkb
+const DEFAULT_MODIFIER_KEYS: [u32; 8] = [
+    29,  // KEY_LEFTCTRL
+    97,  // KEY_RIGHTCTRL
+    42,  // KEY_LEFTSHIFT
+    54,  // KEY_RIGHTSHIFT
+    56,  // KEY_LEFTALT
+    100, // KEY_RIGHTALT
+    125, // KEY_LEFTMETA
+    126, // KEY_RIGHTMETA
+];
+
+/// A key event generated by [`KeyboardAccessibility`] or [`KeyRepeat`](super::KeyRepeat)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FilteredKeyEvent {
+    /// Keycode of the event
+    pub keycode: Keycode,
+    /// State of the key
+    pub state: KeyState,
+    /// Timestamp of the event in milliseconds
+    pub time: u32,
+}
+
+/// State of a modifier with sticky keys enabled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StickyModifierState {
+    /// The modifier was tapped once and applies to the next key
+    Latched,
+    /// The modifier was tapped twice and applies until it is tapped again
+    Locked,
+}
+
+/// State change of [`KeyboardAccessibility`], e.g. to update on-screen indicators
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessibilityEvent {
+    /// A modifier was latched, locked or released by sticky keys
+    StickyModifier {
+        /// Keycode of the modifier
+        keycode: Keycode,
+        /// New state of the modifier, `None` if it was released
+        state: Option<StickyModifierState>,
+    },
+    /// A key press is held back by slow keys until it was held down long enough
+    SlowKeyPending(Keycode),
+    /// A key press held back by slow keys was accepted
+    SlowKeyAccepted(Keycode),
+    /// A key held back by slow keys was released before it was accepted
+    SlowKeyRejected(Keycode),
+    /// A key press was ignored by bounce keys
+    KeyBounced(Keycode),
+}
+
+struct StateCallback(Box<dyn FnMut(AccessibilityEvent) + Send>);
+
+impl fmt::Debug for StateCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StateCallback").finish_non_exhaustive()
+    }
+}
+
+/// Accessibility filters for keyboard input
+///
+/// On Wayland these filters have to be implemented by the compositor. They are applied to the
+/// raw key events of the input backend via [`KeyboardAccessibility::filter`], and the resulting
+/// events are fed to [`KeyboardHandle::input`](super::KeyboardHandle::input) in order.
+///
+/// - *Sticky keys* allow to use modifiers without holding them down. Tapping a modifier latches it
+///   for the next key, tapping it twice locks it until it is tapped again.
+/// - *Slow keys* only accept key presses, after the key was held down for a given duration.
+///   As the acceptance happens without further input, [`KeyboardAccessibility::dispatch_pending`]
+///   has to be called at [`KeyboardAccessibility::next_deadline`].
+/// - *Bounce keys* ignore repeated presses of the same key within a given duration.
+///
+/// The state of the filters can be queried to show on-screen indicators, or followed via
+/// [`KeyboardAccessibility::set_state_callback`].
+#[derive(Debug)]
+pub struct KeyboardAccessibility {
+    sticky_keys: bool,
+    slow_keys: Option<Duration>,
+    bounce_keys: Option<Duration>,
+    modifier_keys: Vec<Keycode>,
+
+    sticky: HashMap<Keycode, StickyModifierState>,
+    sticky_tap: Option<Keycode>,
+    sticky_swallowed: Option<Keycode>,
+    slow_pending: HashMap<Keycode, u32>,
+    last_release: HashMap<Keycode, u32>,
+    bounced: Vec<Keycode>,
+    callback: Option<StateCallback>,
+}
+
+impl Default for KeyboardAccessibility {
+    fn default() -> Self {
+        Self {
+            sticky_keys: false,
+            slow_keys: None,
+            bounce_keys: None,
+            modifier_keys: DEFAULT_MODIFIER_KEYS
+                .iter()
+                .map(|code| Keycode::new(code + 8))
+                .collect(),
+
+            sticky: HashMap::new(),
+            sticky_tap: None,
+            sticky_swallowed: None,
+            slow_pending: HashMap::new(),
+            last_release: HashMap::new(),
+            bounced: Vec::new(),
+            callback: None,
+        }
+    }
+}
+
+impl KeyboardAccessibility {
+    /// Create a new set of filters, all disabled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enable or disable sticky keys
+    ///
+    /// Returns the events releasing all latched and locked modifiers, when disabling.
+    pub fn set_sticky_keys(&mut self, enabled: bool, time: u32) -> Vec<FilteredKeyEvent> {
+        self.sticky_keys = enabled;
+        self.sticky_tap = None;
+        if enabled {
+            return Vec::new();
+        }
+
+        let released = self
+            .sticky
+            .drain()
+            .map(|(keycode, _)| keycode)
+            .collect::<Vec<_>>();
+        released
+            .into_iter()
+            .map(|keycode| {
+                self.notify(AccessibilityEvent::StickyModifier { keycode, state: None });
+                FilteredKeyEvent {
+                    keycode,
+                    state: KeyState::Released,
+                    time,
+                }
+            })
+            .collect()
+    }
+
+    /// Set a callback invoked on every state change of the filters
+    ///
+    /// The callback is invoked from within [`KeyboardAccessibility::filter`] and
+    /// [`KeyboardAccessibility::dispatch_pending`] and can be used to update on-screen
+    /// indicators or play feedback sounds.
+    pub fn set_state_callback(&mut self, callback: impl FnMut(AccessibilityEvent) + Send + 'static) {
+        self.callback = Some(StateCallback(Box::new(callback)));
+    }
+
+    fn notify(&mut self, event: AccessibilityEvent) {
+        if let Some(callback) = self.callback.as_mut() {
+            (callback.0)(event);
+        }
+    }
+
+    /// Set the duration a key has to be held down to be accepted, `None` disables slow keys
+    pub fn set_slow_keys(&mut self, delay: Option<Duration>) {
+        self.slow_keys = delay;
+        if delay.is_none() {
+            self.slow_pending.clear();
+        }
+    }
+
+    /// Set the duration in which repeated presses of a key are ignored, `None` disables bounce keys
+    pub fn set_bounce_keys(&mut self, delay: Option<Duration>) {
+        self.bounce_keys = delay;
+    }
+
+    /// Set the keys treated as modifiers by sticky keys
+    ///
+    /// Defaults to the Control, Shift, Alt and Meta keys of a standard keyboard.
+    pub fn set_modifier_keys(&mut self, keys: impl IntoIterator<Item = Keycode>) {
+        self.modifier_keys = keys.into_iter().collect();
+    }
+
+    /// Returns if sticky keys are enabled
+    pub fn sticky_keys(&self) -> bool {
+        self.sticky_keys
+    }
+
+    /// Returns the delay of slow keys, if enabled
+    pub fn slow_keys(&self) -> Option<Duration> {
+        self.slow_keys
+    }
+
+    /// Returns the delay of bounce keys, if enabled
+    pub fn bounce_keys(&self) -> Option<Duration> {
+        self.bounce_keys
+    }
+
+    /// Returns the state of a modifier with sticky keys enabled
+    pub fn sticky_state(&self, keycode: Keycode) -> Option<StickyModifierState> {
+        self.sticky.get(&keycode).copied()
+    }
+
+    /// Iterate over all latched or locked modifiers
+    pub fn sticky_modifiers(&self) -> impl Iterator<Item = (Keycode, StickyModifierState)> + '_ {
+        self.sticky.iter().map(|(k, s)| (*k, *s))
+    }
+
+    /// Iterate over the keys held down, but not yet accepted by slow keys
+    pub fn pending_slow_keys(&self) -> impl Iterator<Item = Keycode> + '_ {
+        self.slow_pending.keys().copied()
+    }
+
+    /// Returns the time at which [`KeyboardAccessibility::dispatch_pending`] has to be called
+    pub fn next_deadline(&self) -> Option<u32> {
+        let delay = self.slow_keys?.as_millis() as u32;
+        self.slow_pending
+            .values()
+            .map(|pressed| pressed.wrapping_add(delay))
+            .min()
+    }
+
+    /// Accept the key presses held down long enough for slow keys at `time`
+    pub fn dispatch_pending(&mut self, time: u32) -> Vec<FilteredKeyEvent> {
+        let mut events = Vec::new();
+        let Some(delay) = self.slow_keys else {
+            return events;
+        };
+        let delay = delay.as_millis() as u32;
+
+        let mut accepted = self
+            .slow_pending
+            .iter()
+            .filter(|(_, pressed)| time.wrapping_sub(**pressed) >= delay)
+            .map(|(keycode, pressed)| (*keycode, *pressed))
+            .collect::<Vec<_>>();
+        accepted.sort_by_key(|(_, pressed)| *pressed);
+
+        for (keycode, _) in accepted {
+            self.slow_pending.remove(&keycode);
+            self.notify(AccessibilityEvent::SlowKeyAccepted(keycode));
+            self.sticky_filter(keycode, KeyState::Pressed, time, &mut events);
+        }
+        events
+    }
+
+    /// Filter a key event of the input backend
+    ///
+    /// Returns the events to forward to the keyboard, which may be none or several.
+    pub fn filter(&mut self, keycode: Keycode, state: KeyState, time: u32) -> Vec<FilteredKeyEvent> {
+        let mut events = Vec::new();
+
+        // bounce keys
+        if let Some(delay) = self.bounce_keys {
+            match state {
+                KeyState::Pressed => {
+                    if let Some(released) = self.last_release.get(&keycode) {
+                        if time.wrapping_sub(*released) < delay.as_millis() as u32 {
+                            self.bounced.push(keycode);
+                            self.notify(AccessibilityEvent::KeyBounced(keycode));
+                            return events;
+                        }
+                    }
+                }
+                KeyState::Released => {
+                    if let Some(idx) = self.bounced.iter().position(|k| *k == keycode) {
+                        self.bounced.remove(idx);
+                        return events;
+                    }
+                    self.last_release.insert(keycode, time);
+                }
+            }
+        }
+
+        // slow keys
+        if self.slow_keys.is_some() {
+            match state {
+                KeyState::Pressed => {
+                    self.slow_pending.insert(keycode, time);
+                    self.notify(AccessibilityEvent::SlowKeyPending(keycode));
+                    return events;
+                }
+                KeyState::Released => {
+                    if self.slow_pending.remove(&keycode).is_some() {
+                        // released before it was accepted
+                        self.notify(AccessibilityEvent::SlowKeyRejected(keycode));
+                        return events;
+                    }
+                }
+            }
+        }
+
+        self.sticky_filter(keycode, state, time, &mut events);
+        events
+    }
+
+    fn sticky_filter(
+        &mut self,
+        keycode: Keycode,
+        state: KeyState,
+        time: u32,
+        events: &mut Vec<FilteredKeyEvent>,
+    ) {
+        let event = |keycode, state| FilteredKeyEvent { keycode, state, time };
+
+        if !self.sticky_keys {
+            events.push(event(keycode, state));
+            return;
+        }
+
+        if self.modifier_keys.contains(&keycode) {
+            match (state, self.sticky.get(&keycode).copied()) {
+                // the modifier is still held down from the tap before, swallow the press and release
+                (KeyState::Pressed, Some(StickyModifierState::Latched)) => {
+                    self.sticky.insert(keycode, StickyModifierState::Locked);
+                    self.sticky_swallowed = Some(keycode);
+                    self.notify(AccessibilityEvent::StickyModifier {
+                        keycode,
+                        state: Some(StickyModifierState::Locked),
+                    });
+                }
+                (KeyState::Pressed, Some(StickyModifierState::Locked)) => {
+                    self.sticky.remove(&keycode);
+                    self.sticky_tap = None;
+                    self.notify(AccessibilityEvent::StickyModifier { keycode, state: None });
+                }
+                (KeyState::Pressed, None) => {
+                    self.sticky_tap = Some(keycode);
+                    events.push(event(keycode, state));
+                }
+                (KeyState::Released, _) if self.sticky_swallowed == Some(keycode) => {
+                    self.sticky_swallowed = None;
+                }
+                (KeyState::Released, Some(StickyModifierState::Locked)) => {}
+                (KeyState::Released, _) => {
+                    if self.sticky_tap == Some(keycode) {
+                        // tapped without any other key, keep it held down
+                        self.sticky.insert(keycode, StickyModifierState::Latched);
+                        self.sticky_tap = None;
+                        self.notify(AccessibilityEvent::StickyModifier {
+                            keycode,
+                            state: Some(StickyModifierState::Latched),
+                        });
+                    } else {
+                        // used as a regular modifier
+                        self.sticky.remove(&keycode);
+                        events.push(event(keycode, state));
+                    }
+                }
+            }
+            return;
+        }
+
+        self.sticky_tap = None;
+        events.push(event(keycode, state));
+        if state == KeyState::Released {
+            let latched = self
+                .sticky
+                .iter()
+                .filter(|(_, state)| **state == StickyModifierState::Latched)
+                .map(|(keycode, _)| *keycode)
+                .collect::<Vec<_>>();
+            for modifier in latched {
+                self.sticky.remove(&modifier);
+                events.push(event(modifier, KeyState::Released));
+                self.notify(AccessibilityEvent::StickyModifier {
+                    keycode: modifier,
+                    state: None,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CTRL: u32 = 29 + 8;
+    const A: u32 = 30 + 8;
+
+    fn keys(events: Vec<FilteredKeyEvent>) -> Vec<(u32, KeyState)> {
+        events.into_iter().map(|e| (e.keycode.raw(), e.state)).collect()
+    }
+
+    #[test]
+    fn sticky_latch_and_lock() {
+        let mut filter = KeyboardAccessibility::new();
+        filter.set_sticky_keys(true, 0);
+        let ctrl = Keycode::new(CTRL);
+        let a = Keycode::new(A);
+
+        assert_eq!(
+            keys(filter.filter(ctrl, KeyState::Pressed, 0)),
+            [(CTRL, KeyState::Pressed)]
+        );
+        assert!(filter.filter(ctrl, KeyState::Released, 1).is_empty());
+        assert_eq!(filter.sticky_state(ctrl), Some(StickyModifierState::Latched));
+
+        assert_eq!(
+            keys(filter.filter(a, KeyState::Pressed, 2)),
+            [(A, KeyState::Pressed)]
+        );
+        assert_eq!(
+            keys(filter.filter(a, KeyState::Released, 3)),
+            [(A, KeyState::Released), (CTRL, KeyState::Released)]
+        );
+        assert_eq!(filter.sticky_state(ctrl), None);
+
+        filter.filter(ctrl, KeyState::Pressed, 4);
+        filter.filter(ctrl, KeyState::Released, 5);
+        assert!(filter.filter(ctrl, KeyState::Pressed, 6).is_empty());
+        assert!(filter.filter(ctrl, KeyState::Released, 7).is_empty());
+        assert_eq!(filter.sticky_state(ctrl), Some(StickyModifierState::Locked));
+
+        filter.filter(a, KeyState::Pressed, 8);
+        assert_eq!(
+            keys(filter.filter(a, KeyState::Released, 9)),
+            [(A, KeyState::Released)]
+        );
+        assert_eq!(filter.sticky_state(ctrl), Some(StickyModifierState::Locked));
+
+        assert!(filter.filter(ctrl, KeyState::Pressed, 10).is_empty());
+        assert_eq!(
+            keys(filter.filter(ctrl, KeyState::Released, 11)),
+            [(CTRL, KeyState::Released)]
+        );
+        assert_eq!(filter.sticky_state(ctrl), None);
+    }
+
+    #[test]
+    fn slow_and_bounce_keys() {
+        let mut filter = KeyboardAccessibility::new();
+        filter.set_slow_keys(Some(Duration::from_millis(100)));
+        filter.set_bounce_keys(Some(Duration::from_millis(50)));
+        let a = Keycode::new(A);
+
+        assert!(filter.filter(a, KeyState::Pressed, 0).is_empty());
+        assert!(filter.filter(a, KeyState::Released, 50).is_empty());
+        assert_eq!(filter.next_deadline(), None);
+
+        // bounced
+        assert!(filter.filter(a, KeyState::Pressed, 80).is_empty());
+        assert!(filter.filter(a, KeyState::Released, 90).is_empty());
+
+        assert!(filter.filter(a, KeyState::Pressed, 200).is_empty());
+        assert_eq!(filter.next_deadline(), Some(300));
+        assert!(filter.dispatch_pending(250).is_empty());
+        assert_eq!(keys(filter.dispatch_pending(300)), [(A, KeyState::Pressed)]);
+        assert_eq!(
+            keys(filter.filter(a, KeyState::Released, 350)),
+            [(A, KeyState::Released)]
+        );
+    }
+
+    #[test]
+    fn state_callback() {
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut filter = KeyboardAccessibility::new();
+        filter.set_sticky_keys(true, 0);
+        filter.set_state_callback({
+            let events = events.clone();
+            move |event| events.lock().unwrap().push(event)
+        });
+        let ctrl = Keycode::new(CTRL);
+        let a = Keycode::new(A);
+
+        filter.filter(ctrl, KeyState::Pressed, 0);
+        filter.filter(ctrl, KeyState::Released, 1);
+        filter.filter(a, KeyState::Pressed, 2);
+        filter.filter(a, KeyState::Released, 3);
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                AccessibilityEvent::StickyModifier {
+                    keycode: ctrl,
+                    state: Some(StickyModifierState::Latched),
+                },
+                AccessibilityEvent::StickyModifier {
+                    keycode: ctrl,
+                    state: None
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,137 @@
+use std::{ffi::OsStr, fmt, io, path::Path};
+
+use thiserror::Error;
+use xkbcommon::xkb::{self, compose, Keysym};
+
+/// Errors that can occur when loading a compose table
+#[derive(Debug, Error)]
+pub enum ComposeError {
+    /// The compose file could not be read
+    #[error("Failed to read the compose file: {0}")]
+    Io(#[from] io::Error),
+    /// libxkbcommon could not load the compose table
+    #[error("Libxkbcommon could not load the compose table")]
+    BadTable,
+}
+
+/// Result of feeding a keysym into [`Compose`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ComposeStatus {
+    /// The keysym is not part of a compose sequence and should be handled as usual
+    Nothing,
+    /// The keysym started or continued a compose sequence and should not be handled further
+    Composing,
+    /// The keysym completed a compose sequence
+    Composed {
+        /// The resulting keysym, if the sequence results in a single one
+        keysym: Option<Keysym>,
+        /// The resulting text
+        utf8: Option<String>,
+    },
+    /// The keysym did not continue the current compose sequence, which was cancelled
+    Cancelled,
+}
+
+/// Compose sequence handling for compositor-internal text input
+///
+/// Clients resolve dead keys and compose sequences (like `<Multi_key> <o> <e>` resulting in `œ`)
+/// themselves, but keys handled by the compositor, e.g. in a run dialog or for forwarding text through the
+/// input method path, only ever see the raw keysyms. [`Compose`] resolves those using the compose
+/// tables of libxkbcommon.
+///
+/// Keysyms of key presses have to be passed to [`Compose::feed`], usually from within the filter
+/// of [`KeyboardHandle::input`](super::KeyboardHandle::input) using
+/// [`KeysymHandle::modified_sym`](super::KeysymHandle::modified_sym).
+pub struct Compose {
+    state: compose::State,
+}
+
+impl fmt::Debug for Compose {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Compose").finish_non_exhaustive()
+    }
+}
+
+impl Compose {
+    /// Load the compose table of the current locale
+    ///
+    /// The locale is taken from `LC_ALL`, `LC_CTYPE` or `LANG`, defaulting to `C`.
+    /// libxkbcommon respects `XCOMPOSEFILE` and `~/.XCompose` as usual.
+    pub fn new() -> Result<Self, ComposeError> {
+        let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .into_iter()
+            .filter_map(std::env::var_os)
+            .find(|locale| !locale.is_empty())
+            .unwrap_or_else(|| "C".into());
+        Self::from_locale(&locale)
+    }
+
+    /// Load the compose table of the given locale
+    pub fn from_locale(locale: &OsStr) -> Result<Self, ComposeError> {
+        let context = xkb::Context::new(xkb::CONTEXT_NO_FLAGS);
+        let table = compose::Table::new_from_locale(&context, locale, compose::COMPILE_NO_FLAGS)
+            .map_err(|_| ComposeError::BadTable)?;
+        Ok(Self::from_table(&table))
+    }
+
+    /// Load a compose table from a file in the format of `Compose(5)`
+    ///
+    /// `locale` is used to resolve includes of the system compose file of the locale.
+    pub fn from_file(path: impl AsRef<Path>, locale: &OsStr) -> Result<Self, ComposeError> {
+        let buffer = std::fs::read(path)?;
+        let context = xkb::Context::new(xkb::CONTEXT_NO_FLAGS);
+        let table = compose::Table::new_from_buffer(
+            &context,
+            buffer,
+            locale,
+            compose::FORMAT_TEXT_V1,
+            compose::COMPILE_NO_FLAGS,
+        )
+        .map_err(|_| ComposeError::BadTable)?;
+        Ok(Self::from_table(&table))
+    }
+
+    fn from_table(table: &compose::Table) -> Self {
+        Compose {
+            state: compose::State::new(table, compose::STATE_NO_FLAGS),
+        }
+    }
+
+    /// Feed the keysym of a key press into the current compose sequence
+    ///
+    /// Modifier keys don't affect the sequence and return [`ComposeStatus::Nothing`].
+    pub fn feed(&mut self, keysym: Keysym) -> ComposeStatus {
+        if matches!(self.state.feed(keysym), compose::FeedResult::Ignored) {
+            return ComposeStatus::Nothing;
+        }
+
+        match self.state.status() {
+            compose::Status::Nothing => ComposeStatus::Nothing,
+            compose::Status::Composing => ComposeStatus::Composing,
+            compose::Status::Composed => {
+                let status = ComposeStatus::Composed {
+                    keysym: self.state.keysym(),
+                    utf8: self.state.utf8(),
+                };
+                self.state.reset();
+                status
+            }
+            compose::Status::Cancelled => {
+                self.state.reset();
+                ComposeStatus::Cancelled
+            }
+        }
+    }
+
+    /// Returns `true` if a compose sequence is in progress
+    pub fn is_composing(&self) -> bool {
+        matches!(self.state.status(), compose::Status::Composing)
+    }
+
+    /// Abort the current compose sequence
+    ///
+    /// This should be called when the keyboard focus changes.
+    pub fn reset(&mut self) {
+        self.state.reset();
+    }
+}
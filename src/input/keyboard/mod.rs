@@ -26,6 +26,15 @@ mod keymap_file;
 #[cfg(feature = "wayland_frontend")]
 pub use keymap_file::KeymapFile;
 
+mod accessibility;
+pub use accessibility::{AccessibilityEvent, FilteredKeyEvent, KeyboardAccessibility, StickyModifierState};
+
+mod repeat;
+pub use repeat::KeyRepeat;
+
+mod compose;
+pub use compose::{Compose, ComposeError, ComposeStatus};
+
 mod modifiers_state;
 pub use modifiers_state::{ModifiersState, SerializedMods};
 
@@ -775,6 +784,26 @@ impl<D: SeatHandler + 'static> KeyboardHandle<D> {
             state.update_key(*key, xkb::KeyDirection::Down);
         }
 
+        // Carry over latched and locked modifiers (e.g. caps or num lock) and the locked layout.
+        // Modifier indices may differ between keymaps, so they are matched by name.
+        {
+            let old = internal.xkb.lock().unwrap();
+            let latched = remap_mods(
+                &old.keymap,
+                &keymap,
+                old.state.serialize_mods(xkb::STATE_MODS_LATCHED),
+            );
+            let locked = remap_mods(
+                &old.keymap,
+                &keymap,
+                old.state.serialize_mods(xkb::STATE_MODS_LOCKED),
+            );
+            let layout = old.state.serialize_layout(xkb::STATE_LAYOUT_LOCKED);
+            let layout = if layout < keymap.num_layouts() { layout } else { 0 };
+            let depressed = state.serialize_mods(xkb::STATE_MODS_DEPRESSED);
+            state.update_mask(depressed, latched, locked, 0, 0, layout);
+        }
+
         let led_mapping = LedMapping::from_keymap(&keymap);
         internal.led_mapping = led_mapping;
         internal.mods_state.update_with(&state);
@@ -807,6 +836,9 @@ impl<D: SeatHandler + 'static> KeyboardHandle<D> {
     /// Change the [`Keymap`](xkb::Keymap) used by the keyboard.
     ///
     /// The input is a keymap in XKB_KEYMAP_FORMAT_TEXT_V1 format.
+    ///
+    /// Pressed keys, latched and locked modifiers and the active layout are preserved where
+    /// possible, and the new keymap is sent to the focused client.
     pub fn set_keymap_from_string(&self, data: &mut D, keymap: String) -> Result<(), Error> {
         // Construct the Keymap internally instead of accepting one as input
         // because libxkbcommon is not thread-safe.
@@ -825,6 +857,10 @@ impl<D: SeatHandler + 'static> KeyboardHandle<D> {
     }
 
     /// Change the [`XkbConfig`] used by the keyboard.
+    ///
+    /// This allows changing the rules, model, layout, variant and options at runtime.
+    /// Pressed keys, latched and locked modifiers and the active layout are preserved where
+    /// possible, and the new keymap is sent to the focused client.
     pub fn set_xkb_config(&self, data: &mut D, xkb_config: XkbConfig<'_>) -> Result<(), Error> {
         let keymap = xkb_config
             .compile_keymap(&self.arc.internal.lock().unwrap().xkb.lock().unwrap().context)
@@ -1099,6 +1135,19 @@ impl<D: SeatHandler + 'static> KeyboardHandle<D> {
         self.arc.internal.lock().unwrap().focus.is_some()
     }
 
+    /// Returns the repeat rate (in characters per second) and delay (in milliseconds) configured for this keyboard
+    pub fn repeat_info(&self) -> (i32, i32) {
+        let guard = self.arc.internal.lock().unwrap();
+        (guard.repeat_rate, guard.repeat_delay)
+    }
+
+    /// Check if the given key repeats in the current keymap
+    pub fn key_repeats(&self, keycode: Keycode) -> bool {
+        let internal = self.arc.internal.lock().unwrap();
+        let xkb = internal.xkb.lock().unwrap();
+        xkb.keymap.key_repeats(keycode)
+    }
+
     /// Change the repeat info configured for this keyboard
     #[instrument(parent = &self.arc.span, skip(self))]
     pub fn change_repeat_info(&self, rate: i32, delay: i32) {
@@ -1135,6 +1184,15 @@ impl<D: SeatHandler + 'static> KeyboardHandle<D> {
     }
 }
 
+// Translate a modifier mask of one keymap into another, matching modifiers by name
+fn remap_mods(from: &xkb::Keymap, to: &xkb::Keymap, mask: xkb::ModMask) -> xkb::ModMask {
+    (0..from.num_mods())
+        .filter(|idx| mask & (1 << idx) != 0)
+        .map(|idx| to.mod_get_index(from.mod_get_name(idx)))
+        .filter(|idx| *idx != xkb::MOD_INVALID)
+        .fold(0, |mask, idx| mask | (1 << idx))
+}
+
 impl<D> KeyboardHandle<D>
 where
     D: SeatHandler,
@@ -0,0 +1,143 @@
+use xkbcommon::xkb::Keycode;
+
+use crate::backend::input::KeyState;
+
+use super::FilteredKeyEvent;
+
+/// Key repeat generation
+///
+/// Wayland clients implement key repeat themselves, based on the repeat info sent by the compositor.
+/// Compositor-side keybindings don't get any repeated keys. [`KeyRepeat`] synthesizes those from
+/// the key events of the input backend, passed to [`KeyRepeat::key_event`].
+///
+/// The synthesized repeats are meant for compositor-side keybindings only, e.g. to repeat the action
+/// bound to the held key. They must not be passed to [`KeyboardHandle::input`](super::KeyboardHandle::input),
+/// as that forwards them to the focused client, which repeats the key on its own, resulting in
+/// every key being repeated twice.
+///
+/// Repeats are generated without further input, so [`KeyRepeat::dispatch_pending`] has to be called at
+/// [`KeyRepeat::next_deadline`], e.g. by (re-)arming a calloop
+/// [`Timer`](calloop::timer::Timer) after each event. The timestamps use the same clock as the
+/// timestamps of the input events.
+#[derive(Debug, Clone)]
+pub struct KeyRepeat {
+    rate: i32,
+    delay: i32,
+    current: Option<(Keycode, u32)>,
+}
+
+impl KeyRepeat {
+    /// Create a new repeat generator with a given rate (in characters per second) and delay (in milliseconds)
+    ///
+    /// A rate of zero disables key repeat. Usually the same values as passed to
+    /// [`Seat::add_keyboard`](crate::input::Seat::add_keyboard) should be used,
+    /// see [`KeyboardHandle::repeat_info`](super::KeyboardHandle::repeat_info).
+    pub fn new(rate: i32, delay: i32) -> Self {
+        Self {
+            rate,
+            delay,
+            current: None,
+        }
+    }
+
+    /// Change the rate (in characters per second) and delay (in milliseconds) of repeated keys
+    pub fn set_repeat_info(&mut self, rate: i32, delay: i32) {
+        self.rate = rate;
+        self.delay = delay;
+        if rate <= 0 {
+            self.current = None;
+        }
+    }
+
+    /// Returns the key currently repeating, if any
+    pub fn repeating_key(&self) -> Option<Keycode> {
+        self.current.map(|(keycode, _)| keycode)
+    }
+
+    /// Track a key event of the input backend
+    ///
+    /// `repeats` should reflect, if the key repeats in the current keymap,
+    /// see [`KeyboardHandle::key_repeats`](super::KeyboardHandle::key_repeats).
+    /// Pressing a repeating key starts repeating it, replacing the previously repeating key.
+    /// Releasing the repeating key stops the repeat.
+    pub fn key_event(&mut self, keycode: Keycode, state: KeyState, repeats: bool, time: u32) {
+        match state {
+            KeyState::Pressed if repeats && self.rate > 0 => {
+                self.current = Some((keycode, time.wrapping_add(self.delay.max(0) as u32)));
+            }
+            KeyState::Pressed => {}
+            KeyState::Released => {
+                if self.repeating_key() == Some(keycode) {
+                    self.current = None;
+                }
+            }
+        }
+    }
+
+    /// Stop repeating the current key
+    ///
+    /// This should be called when the keyboard focus changes.
+    pub fn cancel(&mut self) {
+        self.current = None;
+    }
+
+    /// Returns the time at which [`KeyRepeat::dispatch_pending`] has to be called
+    pub fn next_deadline(&self) -> Option<u32> {
+        self.current.map(|(_, next)| next)
+    }
+
+    /// Generate the key repeats due at `time`
+    ///
+    /// Returns a key press for every repeat since the last call. These are not meant to be
+    /// forwarded to clients, see the [type documentation](KeyRepeat).
+    pub fn dispatch_pending(&mut self, time: u32) -> Vec<FilteredKeyEvent> {
+        let mut events = Vec::new();
+        let Some((keycode, next)) = self.current.as_mut() else {
+            return events;
+        };
+
+        let interval = (1000 / self.rate.max(1)).max(1) as u32;
+        // the difference is negative (and wraps around) if the deadline did not pass yet
+        while (time.wrapping_sub(*next) as i32) >= 0 {
+            events.push(FilteredKeyEvent {
+                keycode: *keycode,
+                state: KeyState::Pressed,
+                time: *next,
+            });
+            *next = next.wrapping_add(interval);
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeats_until_released() {
+        let a = Keycode::new(38);
+        let shift = Keycode::new(50);
+        let mut repeat = KeyRepeat::new(25, 600);
+
+        repeat.key_event(a, KeyState::Pressed, true, 1000);
+        assert_eq!(repeat.next_deadline(), Some(1600));
+        assert!(repeat.dispatch_pending(1599).is_empty());
+
+        let events = repeat.dispatch_pending(1680);
+        assert_eq!(
+            events.iter().map(|event| event.time).collect::<Vec<_>>(),
+            [1600, 1640, 1680]
+        );
+        assert!(events.iter().all(|event| event.keycode == a));
+
+        // non-repeating keys don't interrupt the repeat
+        repeat.key_event(shift, KeyState::Pressed, false, 1690);
+        repeat.key_event(shift, KeyState::Released, false, 1700);
+        assert_eq!(repeat.repeating_key(), Some(a));
+
+        repeat.key_event(a, KeyState::Released, true, 1710);
+        assert_eq!(repeat.next_deadline(), None);
+        assert!(repeat.dispatch_pending(2000).is_empty());
+    }
+}
@@ -0,0 +1,103 @@
+use crate::utils::{Logical, Point};
+
+// assumed time since the previous event, if there is none (8ms, 125Hz)
+const DEFAULT_INTERVAL_USEC: u64 = 8_000;
+// events further apart than this are not considered part of the same movement
+const MAX_INTERVAL_USEC: u64 = 100_000;
+
+/// Compositor-side acceleration of relative pointer motion using a custom curve
+///
+/// The curve is defined like the custom acceleration profile of libinput: `points` are the output
+/// speeds at input speeds of `0`, `step`, `2 * step`, ..., measured in device units per millisecond.
+/// Speeds between the points are interpolated linearly, speeds beyond the last point are extrapolated
+/// using the last two points.
+///
+/// The acceleration is applied to the unaccelerated delta of motion events
+/// (see [`PointerMotionEvent::delta_unaccel`](crate::backend::input::PointerMotionEvent::delta_unaccel))
+/// before passing it on to [`PointerHandle::motion`](super::PointerHandle::motion). Relative motion
+/// events should keep using the unaccelerated delta.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccelCurve {
+    step: f64,
+    points: Vec<f64>,
+    last_time: Option<u64>,
+}
+
+impl AccelCurve {
+    /// Create a new curve from the given step and points
+    ///
+    /// Returns `None` if `step` is not positive, less than two points are given,
+    /// or any point is negative or not finite.
+    pub fn new(step: f64, points: Vec<f64>) -> Option<Self> {
+        if !(step.is_finite() && step > 0.0)
+            || points.len() < 2
+            || points.iter().any(|point| !(point.is_finite() && *point >= 0.0))
+        {
+            return None;
+        }
+
+        Some(AccelCurve {
+            step,
+            points,
+            last_time: None,
+        })
+    }
+
+    /// Output speed for the given input speed
+    pub fn speed(&self, input: f64) -> f64 {
+        let position = input.max(0.0) / self.step;
+        let idx = (position.floor() as usize).min(self.points.len() - 2);
+        let (a, b) = (self.points[idx], self.points[idx + 1]);
+        (a + (b - a) * (position - idx as f64)).max(0.0)
+    }
+
+    /// Accelerate a motion event
+    ///
+    /// `time_usec` is the timestamp of the event in microseconds, used to determine the speed of the motion.
+    pub fn accelerate(&mut self, delta_unaccel: Point<f64, Logical>, time_usec: u64) -> Point<f64, Logical> {
+        let interval = match self.last_time.replace(time_usec) {
+            Some(last) if time_usec > last && time_usec - last <= MAX_INTERVAL_USEC => time_usec - last,
+            _ => DEFAULT_INTERVAL_USEC,
+        };
+
+        let distance = f64::hypot(delta_unaccel.x, delta_unaccel.y);
+        if distance == 0.0 {
+            return delta_unaccel;
+        }
+        let input = distance / (interval as f64 / 1000.0);
+        let factor = self.speed(input) / input;
+        (delta_unaccel.x * factor, delta_unaccel.y * factor).into()
+    }
+
+    /// Forget the timestamp of the previous event
+    ///
+    /// This should be called when the device was paused, e.g. on session switches.
+    pub fn reset(&mut self) {
+        self.last_time = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interpolates_and_extrapolates() {
+        let curve = AccelCurve::new(1.0, vec![0.0, 1.0, 4.0]).unwrap();
+        assert_eq!(curve.speed(0.5), 0.5);
+        assert_eq!(curve.speed(1.5), 2.5);
+        assert_eq!(curve.speed(3.0), 7.0);
+        assert!(AccelCurve::new(0.0, vec![0.0, 1.0]).is_none());
+        assert!(AccelCurve::new(1.0, vec![1.0]).is_none());
+    }
+
+    #[test]
+    fn accelerates_by_speed() {
+        let mut curve = AccelCurve::new(1.0, vec![0.0, 1.0, 4.0]).unwrap();
+        curve.accelerate((0.0, 0.0).into(), 0);
+        // 16 units in 8ms, 2 units/ms
+        assert_eq!(curve.accelerate((16.0, 0.0).into(), 8_000), (32.0, 0.0).into());
+        // 2 units in 4ms, 0.5 units/ms
+        assert_eq!(curve.accelerate((0.0, 2.0).into(), 12_000), (0.0, 2.0).into());
+    }
+}
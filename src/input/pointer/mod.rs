@@ -12,6 +12,9 @@ use crate::{
     utils::{Clock, IsAlive, Logical, Monotonic, Point},
 };
 
+mod accel;
+pub use accel::AccelCurve;
+
 mod cursor_image;
 pub use cursor_icon::CursorIcon;
 pub use cursor_image::{CursorImageAttributes, CursorImageStatus, CursorImageSurfaceData};
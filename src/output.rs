@@ -211,6 +211,59 @@ impl Scale {
     }
 }
 
+// pixel densities considered comfortable at usual viewing distances
+const DESKTOP_DPI: f64 = 96.0;
+const LAPTOP_DPI: f64 = 125.0;
+// displays smaller than this are assumed to be viewed from closer up, like laptop panels
+const LAPTOP_DIAGONAL_INCHES: f64 = 20.0;
+// don't scale below a logical height usable by most applications
+const MIN_LOGICAL_HEIGHT: f64 = 720.0;
+
+/// Guess a sensible default scale from the physical size (in millimeters) and resolution of a display
+///
+/// The scale is chosen to reach a comfortable pixel density, assuming smaller displays like laptop panels
+/// are viewed from closer up. It is rounded to multiples of `0.25`, which are precisely representable in
+/// the fractional scale protocol, and never scales the display to a logical height below 720 pixels.
+///
+/// Returns `None` if the physical size is unknown or implausible. Projectors and some TVs, for example,
+/// report no size or only their aspect ratio. Compositors should prefer scales configured by the user
+/// over this guess.
+pub fn guess_scale(physical_size: Size<i32, Raw>, mode_size: Size<i32, Physical>) -> Option<f64> {
+    if physical_size.w <= 0 || physical_size.h <= 0 || mode_size.w <= 0 || mode_size.h <= 0 {
+        return None;
+    }
+
+    // some displays report their aspect ratio instead of their size
+    if matches!(
+        (physical_size.w, physical_size.h),
+        (4, 3) | (16, 9) | (16, 10) | (40, 30) | (160, 90) | (160, 100)
+    ) {
+        return None;
+    }
+    let diagonal_mm = f64::hypot(physical_size.w as f64, physical_size.h as f64);
+    let diagonal_inches = diagonal_mm / 25.4;
+    if diagonal_inches < 4.0 {
+        return None;
+    }
+    let physical_aspect = physical_size.w as f64 / physical_size.h as f64;
+    let mode_aspect = mode_size.w as f64 / mode_size.h as f64;
+    if (physical_aspect / mode_aspect - 1.0).abs() > 0.2 && (physical_aspect * mode_aspect - 1.0).abs() > 0.2
+    {
+        return None;
+    }
+
+    let dpi = f64::hypot(mode_size.w as f64, mode_size.h as f64) / diagonal_inches;
+    let target = if diagonal_inches < LAPTOP_DIAGONAL_INCHES {
+        LAPTOP_DPI
+    } else {
+        DESKTOP_DPI
+    };
+
+    let max_scale = (mode_size.w.min(mode_size.h) as f64 / MIN_LOGICAL_HEIGHT).max(1.0);
+    let scale = ((dpi / target) * 4.0).round() / 4.0;
+    Some(scale.min((max_scale * 4.0).floor() / 4.0).clamp(1.0, 4.0))
+}
+
 #[derive(Debug)]
 pub(crate) struct Inner {
     pub(crate) name: String,
@@ -347,6 +400,16 @@ impl Output {
         self.inner.0.lock().unwrap().physical.clone()
     }
 
+    /// Returns a suggested scale for the output based on its physical size, see [`guess_scale`]
+    ///
+    /// The preferred mode is used, if set, otherwise the current mode.
+    /// Returns `None` if neither a mode nor a valid physical size is known.
+    pub fn suggested_scale(&self) -> Option<f64> {
+        let inner = self.inner.0.lock().unwrap();
+        let mode = inner.preferred_mode.or(inner.current_mode)?;
+        guess_scale(inner.physical.size, mode.size)
+    }
+
     /// Returns the currently advertised modes of the output
     pub fn modes(&self) -> Vec<Mode> {
         self.inner.0.lock().unwrap().modes.clone()
@@ -553,3 +616,24 @@ impl TryFrom<OutputModeSource> for (Size<i32, Physical>, utils::Scale<f64>, Tran
 #[derive(Debug, thiserror::Error)]
 #[error("Output has no active mode")]
 pub struct OutputNoMode;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guessed_scales() {
+        // 14" laptop panel
+        assert_eq!(guess_scale((302, 189).into(), (2880, 1800).into()), Some(2.0));
+        // 27" 4k monitor
+        assert_eq!(guess_scale((597, 336).into(), (3840, 2160).into()), Some(1.75));
+        // 24" 1080p monitor
+        assert_eq!(guess_scale((531, 299).into(), (1920, 1080).into()), Some(1.0));
+        // rotated panel
+        assert_eq!(guess_scale((189, 302).into(), (2880, 1800).into()), Some(2.0));
+        // aspect ratio instead of size
+        assert_eq!(guess_scale((16, 9).into(), (3840, 2160).into()), None);
+        assert_eq!(guess_scale((160, 90).into(), (3840, 2160).into()), None);
+        assert_eq!(guess_scale((0, 0).into(), (1920, 1080).into()), None);
+    }
+}
@@ -0,0 +1,169 @@
+//! Environment setup for clients launched by the compositor
+//!
+//! Clients find the compositor through environment variables like `WAYLAND_DISPLAY` and,
+//! if xwayland is running, `DISPLAY`. [`ClientEnvironment`] collects these variables,
+//! so they can be applied to spawned processes and exported to the activation environment
+//! of systemd and dbus, which is used to start autostarted and dbus-activated applications.
+//!
+//! ```no_run
+//! use smithay::utils::env::ClientEnvironment;
+//!
+//! let mut environment = ClientEnvironment::new("wayland-1");
+//! // once xwayland is ready
+//! environment.set_x11_display(1);
+//! environment.update_activation_environment().unwrap();
+//!
+//! let mut command = std::process::Command::new("weston-terminal");
+//! environment.apply(&mut command);
+//! command.spawn().unwrap();
+//! ```
+
+use std::{
+    ffi::{OsStr, OsString},
+    io,
+    process::{Command, Stdio},
+};
+
+use tracing::{debug, warn};
+
+/// Environment variables clients need to connect to the compositor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientEnvironment {
+    vars: Vec<(OsString, Option<OsString>)>,
+}
+
+impl ClientEnvironment {
+    /// Create a new environment for the wayland socket with the given name
+    ///
+    /// This sets `WAYLAND_DISPLAY` and `XDG_SESSION_TYPE`. `DISPLAY` is unset
+    /// until [`ClientEnvironment::set_x11_display`] is called, so clients don't connect
+    /// to an X server the compositor might be nested in.
+    pub fn new(socket_name: impl AsRef<OsStr>) -> Self {
+        let mut env = ClientEnvironment { vars: Vec::new() };
+        env.set("WAYLAND_DISPLAY", socket_name);
+        env.set("XDG_SESSION_TYPE", "wayland");
+        env.unset("DISPLAY");
+        env
+    }
+
+    /// Set `DISPLAY` to the given display number, e.g. once xwayland is ready
+    pub fn set_x11_display(&mut self, display_number: u32) {
+        self.set("DISPLAY", format!(":{}", display_number));
+    }
+
+    /// Unset `DISPLAY`, e.g. once xwayland exited
+    pub fn unset_x11_display(&mut self) {
+        self.unset("DISPLAY");
+    }
+
+    /// Set an additional variable, like `XDG_CURRENT_DESKTOP`
+    pub fn set(&mut self, key: impl AsRef<OsStr>, value: impl AsRef<OsStr>) {
+        self.insert(key.as_ref(), Some(value.as_ref().to_os_string()));
+    }
+
+    /// Remove a variable from the environment of clients
+    pub fn unset(&mut self, key: impl AsRef<OsStr>) {
+        self.insert(key.as_ref(), None);
+    }
+
+    /// Returns the value of a variable, `None` if it is not set or unset by this environment
+    pub fn get(&self, key: impl AsRef<OsStr>) -> Option<&OsStr> {
+        self.vars
+            .iter()
+            .find(|(k, _)| k.as_os_str() == key.as_ref())
+            .and_then(|(_, v)| v.as_deref())
+    }
+
+    /// Iterate over all variables set by this environment
+    pub fn vars(&self) -> impl Iterator<Item = (&OsStr, &OsStr)> {
+        self.vars
+            .iter()
+            .filter_map(|(k, v)| v.as_deref().map(|v| (k.as_os_str(), v)))
+    }
+
+    /// Apply the environment to a command
+    pub fn apply(&self, command: &mut Command) {
+        for (key, value) in &self.vars {
+            match value {
+                Some(value) => command.env(key, value),
+                None => command.env_remove(key),
+            };
+        }
+    }
+
+    /// Apply the environment to the compositor process itself
+    ///
+    /// Processes spawned without [`ClientEnvironment::apply`] inherit it from there.
+    ///
+    /// # Safety
+    ///
+    /// Modifying the environment of the current process is not thread-safe,
+    /// see [`std::env::set_var`]. This should be called before spawning any threads.
+    pub unsafe fn apply_to_current_process(&self) {
+        for (key, value) in &self.vars {
+            match value {
+                Some(value) => std::env::set_var(key, value),
+                None => std::env::remove_var(key),
+            }
+        }
+    }
+
+    /// Export the environment to the activation environment of systemd and dbus
+    ///
+    /// This runs `dbus-update-activation-environment --systemd` and falls back to
+    /// `systemctl --user import-environment`, if the former is not available.
+    /// The commands are run synchronously and should finish quickly, but this should nevertheless
+    /// only be called on startup or when the environment changed.
+    pub fn update_activation_environment(&self) -> io::Result<()> {
+        let assignments = self
+            .vars()
+            .map(|(key, value)| {
+                let mut assignment = key.to_os_string();
+                assignment.push("=");
+                assignment.push(value);
+                assignment
+            })
+            .collect::<Vec<_>>();
+
+        let mut dbus = Command::new("dbus-update-activation-environment");
+        dbus.arg("--systemd").args(&assignments);
+        match run(dbus) {
+            Ok(()) => return Ok(()),
+            Err(err) => debug!(?err, "Failed to update dbus activation environment"),
+        }
+
+        // `import-environment` reads the values from the environment of the command
+        let mut systemctl = Command::new("systemctl");
+        systemctl.args(["--user", "import-environment"]);
+        for (key, value) in self.vars() {
+            systemctl.arg(key).env(key, value);
+        }
+        run(systemctl).map_err(|err| {
+            warn!(?err, "Failed to update systemd activation environment");
+            err
+        })
+    }
+
+    fn insert(&mut self, key: &OsStr, value: Option<OsString>) {
+        match self.vars.iter_mut().find(|(k, _)| k.as_os_str() == key) {
+            Some((_, v)) => *v = value,
+            None => self.vars.push((key.to_os_string(), value)),
+        }
+    }
+}
+
+fn run(mut command: Command) -> io::Result<()> {
+    let status = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("{:?} failed with {}", command.get_program(), status),
+        ))
+    }
+}
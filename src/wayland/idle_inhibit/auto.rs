@@ -0,0 +1,106 @@
+//! Policy combining explicit idle inhibitors with automatic inhibition
+//!
+//! Clients playing videos do not always take an idle inhibitor. [`AutoIdleInhibit`] tracks
+//! the inhibitors of the [`IdleInhibitHandler`](super::IdleInhibitHandler) and can additionally
+//! inhibit idling, while a fullscreen surface hints video or game content via the
+//! [`content_type`](crate::wayland::content_type) protocol.
+//!
+//! ```no_run
+//! # use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
+//! use smithay::wayland::idle_inhibit::{auto::AutoIdleInhibit, IdleInhibitHandler};
+//!
+//! # struct State { auto_inhibit: AutoIdleInhibit, fullscreen: Option<WlSurface> }
+//! impl IdleInhibitHandler for State {
+//!    fn inhibit(&mut self, surface: WlSurface) {
+//!        self.auto_inhibit.inhibit(surface);
+//!    }
+//!
+//!    fn uninhibit(&mut self, surface: WlSurface) {
+//!        self.auto_inhibit.uninhibit(&surface);
+//!    }
+//! }
+//!
+//! # let mut state: State = unimplemented!();
+//! // e.g. once per frame
+//! let inhibited = state.auto_inhibit.is_inhibited(state.fullscreen.as_ref());
+//! // state.idle_notifier.set_is_inhibited(inhibited);
+//! ```
+
+use wayland_protocols::wp::content_type::v1::server::wp_content_type_v1;
+use wayland_server::{protocol::wl_surface::WlSurface, Resource};
+
+use crate::wayland::{compositor, content_type::ContentTypeSurfaceCachedState};
+
+/// Idle inhibition policy, see the [module docs](self)
+#[derive(Debug)]
+pub struct AutoIdleInhibit {
+    inhibitors: Vec<WlSurface>,
+    auto_inhibit: bool,
+}
+
+impl Default for AutoIdleInhibit {
+    fn default() -> Self {
+        Self {
+            inhibitors: Vec::new(),
+            auto_inhibit: true,
+        }
+    }
+}
+
+impl AutoIdleInhibit {
+    /// Create a new policy with automatic inhibition enabled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enable or disable automatic inhibition for fullscreen video and game content
+    ///
+    /// Explicit inhibitors are honored regardless of this setting.
+    pub fn set_auto_inhibit(&mut self, enabled: bool) {
+        self.auto_inhibit = enabled;
+    }
+
+    /// Returns if automatic inhibition is enabled
+    pub fn auto_inhibit(&self) -> bool {
+        self.auto_inhibit
+    }
+
+    /// Track a new inhibitor, see [`IdleInhibitHandler::inhibit`](super::IdleInhibitHandler::inhibit)
+    pub fn inhibit(&mut self, surface: WlSurface) {
+        if !self.inhibitors.contains(&surface) {
+            self.inhibitors.push(surface);
+        }
+    }
+
+    /// Remove an inhibitor, see [`IdleInhibitHandler::uninhibit`](super::IdleInhibitHandler::uninhibit)
+    pub fn uninhibit(&mut self, surface: &WlSurface) {
+        self.inhibitors.retain(|s| s != surface);
+    }
+
+    /// Returns `true` if idling should currently be inhibited
+    ///
+    /// `fullscreen` is the surface currently shown fullscreen in the foreground, if any.
+    /// Compositors might want to only pass surfaces of inhibitors, that are actually visible,
+    /// to [`AutoIdleInhibit::inhibit`], as the protocol only requires inhibition while the
+    /// surface is visible.
+    pub fn is_inhibited(&mut self, fullscreen: Option<&WlSurface>) -> bool {
+        self.inhibitors.retain(|s| s.is_alive());
+        if !self.inhibitors.is_empty() {
+            return true;
+        }
+
+        self.auto_inhibit
+            && fullscreen
+                .filter(|surface| surface.is_alive())
+                .map(|surface| {
+                    compositor::with_states(surface, |states| {
+                        let mut guard = states.cached_state.get::<ContentTypeSurfaceCachedState>();
+                        matches!(
+                            guard.current().content_type(),
+                            wp_content_type_v1::Type::Video | wp_content_type_v1::Type::Game
+                        )
+                    })
+                })
+                .unwrap_or(false)
+    }
+}
@@ -41,6 +41,7 @@ use wayland_server::{Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch,
 
 use crate::wayland::idle_inhibit::inhibitor::IdleInhibitorState;
 
+pub mod auto;
 pub mod inhibitor;
 
 const MANAGER_VERSION: u32 = 1;
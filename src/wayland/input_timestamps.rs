@@ -0,0 +1,258 @@
+//! Utilities for high-resolution input timestamps
+//!
+//! This module implements the `zwp_input_timestamps_manager_v1` protocol, which allows clients
+//! to receive timestamps of keyboard, pointer and touch events in nanosecond resolution,
+//! instead of the milliseconds of the core protocol.
+//!
+//! The events passed to [`KeyboardHandle`], [`PointerHandle`] and [`TouchHandle`] only carry
+//! millisecond timestamps. To provide the precise timestamp of the event currently being processed,
+//! call [`InputTimestampsHandle::set_time`] with the microsecond timestamp of the input backend
+//! (see [`Event::time`](crate::backend::input::Event::time)) before forwarding the event to the seat.
+//! Otherwise the millisecond timestamps are sent in the higher resolution format.
+//!
+//! ```no_run
+//! # extern crate wayland_server;
+//! use smithay::delegate_input_timestamps;
+//! use smithay::wayland::input_timestamps::{InputTimestampsManagerState, InputTimestampsSeat};
+//!
+//! # struct State { seat_state: smithay::input::SeatState<Self> }
+//! # impl smithay::input::SeatHandler for State {
+//! #     type KeyboardFocus = smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
+//! #     type PointerFocus = smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
+//! #     type TouchFocus = smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
+//! #     fn seat_state(&mut self) -> &mut smithay::input::SeatState<Self> { &mut self.seat_state }
+//! # }
+//! # let mut display = wayland_server::Display::<State>::new().unwrap();
+//! # let mut seat_state = smithay::input::SeatState::<State>::new();
+//! # let seat = seat_state.new_wl_seat(&display.handle(), "seat-0");
+//! InputTimestampsManagerState::new::<State>(&display.handle());
+//! delegate_input_timestamps!(State);
+//!
+//! // when processing an input event of the backend
+//! # let time_usec = 0;
+//! seat.input_timestamps().set_time(time_usec);
+//! ```
+
+use std::sync::{Arc, Mutex};
+
+use wayland_protocols::wp::input_timestamps::zv1::server::{
+    zwp_input_timestamps_manager_v1::{self, ZwpInputTimestampsManagerV1},
+    zwp_input_timestamps_v1::{self, ZwpInputTimestampsV1},
+};
+use wayland_server::{
+    backend::{ClientId, GlobalId, ObjectId},
+    Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource,
+};
+
+use crate::input::{keyboard::KeyboardHandle, pointer::PointerHandle, touch::TouchHandle, Seat, SeatHandler};
+
+const MANAGER_VERSION: u32 = 1;
+
+#[derive(Debug, Default)]
+struct Inner {
+    time_usec: Option<u64>,
+    // timestamps objects and the id of the wl_keyboard, wl_pointer or wl_touch they belong to
+    instances: Vec<(ZwpInputTimestampsV1, ObjectId)>,
+}
+
+/// Handle to the input timestamps of a seat
+#[derive(Debug, Default, Clone)]
+pub struct InputTimestampsHandle {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl InputTimestampsHandle {
+    /// Set the precise timestamp of the input event about to be processed, in microseconds
+    ///
+    /// The timestamp is used for all following events of the seat, whose millisecond timestamp matches it.
+    pub fn set_time(&self, time_usec: u64) {
+        self.inner.lock().unwrap().time_usec = Some(time_usec);
+    }
+
+    /// Send the timestamp for the next event of the given `wl_keyboard`, `wl_pointer` or `wl_touch`
+    pub(crate) fn send(&self, device: &impl Resource, time: u32) {
+        let inner = self.inner.lock().unwrap();
+        let time_usec = inner
+            .time_usec
+            .filter(|time_usec| (time_usec / 1000) as u32 == time)
+            .unwrap_or(time as u64 * 1000);
+        let secs = time_usec / 1_000_000;
+        let nsecs = (time_usec % 1_000_000) as u32 * 1000;
+
+        for (timestamps, _) in inner.instances.iter().filter(|(_, id)| *id == device.id()) {
+            timestamps.timestamp((secs >> 32) as u32, (secs & 0xffffffff) as u32, nsecs);
+        }
+    }
+}
+
+/// Extends [Seat] with input timestamps functionality
+pub trait InputTimestampsSeat {
+    /// Get the input timestamps handle of this seat
+    fn input_timestamps(&self) -> &InputTimestampsHandle;
+}
+
+impl<D: SeatHandler + 'static> InputTimestampsSeat for Seat<D> {
+    fn input_timestamps(&self) -> &InputTimestampsHandle {
+        let user_data = self.user_data();
+        user_data.insert_if_missing_threadsafe(InputTimestampsHandle::default);
+        user_data.get::<InputTimestampsHandle>().unwrap()
+    }
+}
+
+/// User data of ZwpInputTimestampsV1 object
+#[derive(Debug)]
+pub struct InputTimestampsUserData {
+    handle: Option<InputTimestampsHandle>,
+}
+
+/// State of the input timestamps manager
+#[derive(Debug)]
+pub struct InputTimestampsManagerState {
+    global: GlobalId,
+}
+
+impl InputTimestampsManagerState {
+    /// Register new [ZwpInputTimestampsManagerV1] global
+    pub fn new<D>(display: &DisplayHandle) -> Self
+    where
+        D: GlobalDispatch<ZwpInputTimestampsManagerV1, ()>,
+        D: Dispatch<ZwpInputTimestampsManagerV1, ()>,
+        D: Dispatch<ZwpInputTimestampsV1, InputTimestampsUserData>,
+        D: SeatHandler,
+        D: 'static,
+    {
+        let global = display.create_global::<D, ZwpInputTimestampsManagerV1, _>(MANAGER_VERSION, ());
+
+        Self { global }
+    }
+
+    /// [ZwpInputTimestampsManagerV1] GlobalId getter
+    pub fn global(&self) -> GlobalId {
+        self.global.clone()
+    }
+}
+
+impl<D> GlobalDispatch<ZwpInputTimestampsManagerV1, (), D> for InputTimestampsManagerState
+where
+    D: GlobalDispatch<ZwpInputTimestampsManagerV1, ()>
+        + Dispatch<ZwpInputTimestampsManagerV1, ()>
+        + SeatHandler
+        + 'static,
+{
+    fn bind(
+        _state: &mut D,
+        _dh: &DisplayHandle,
+        _client: &Client,
+        resource: New<ZwpInputTimestampsManagerV1>,
+        _global_data: &(),
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        data_init.init(resource, ());
+    }
+}
+
+impl<D> Dispatch<ZwpInputTimestampsManagerV1, (), D> for InputTimestampsManagerState
+where
+    D: Dispatch<ZwpInputTimestampsManagerV1, ()>,
+    D: Dispatch<ZwpInputTimestampsV1, InputTimestampsUserData>,
+    D: SeatHandler,
+    D: 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        _manager: &ZwpInputTimestampsManagerV1,
+        request: zwp_input_timestamps_manager_v1::Request,
+        _data: &(),
+        _dh: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        let seats = &state.seat_state().seats;
+        let (id, device, seat) = match request {
+            zwp_input_timestamps_manager_v1::Request::GetKeyboardTimestamps { id, keyboard } => {
+                let handle = KeyboardHandle::<D>::from_resource(&keyboard);
+                let seat = seats
+                    .iter()
+                    .find(|seat| handle.is_some() && seat.get_keyboard() == handle);
+                (id, keyboard.id(), seat)
+            }
+            zwp_input_timestamps_manager_v1::Request::GetPointerTimestamps { id, pointer } => {
+                let handle = PointerHandle::<D>::from_resource(&pointer);
+                let seat = seats
+                    .iter()
+                    .find(|seat| handle.is_some() && seat.get_pointer() == handle);
+                (id, pointer.id(), seat)
+            }
+            zwp_input_timestamps_manager_v1::Request::GetTouchTimestamps { id, touch } => {
+                let handle = TouchHandle::<D>::from_resource(&touch);
+                let seat = seats
+                    .iter()
+                    .find(|seat| handle.is_some() && seat.get_touch() == handle);
+                (id, touch.id(), seat)
+            }
+            zwp_input_timestamps_manager_v1::Request::Destroy => return,
+            _ => unreachable!(),
+        };
+
+        // objects of inert input devices never receive any events
+        let handle = seat.map(|seat| seat.input_timestamps().clone());
+        let timestamps = data_init.init(
+            id,
+            InputTimestampsUserData {
+                handle: handle.clone(),
+            },
+        );
+        if let Some(handle) = handle {
+            handle.inner.lock().unwrap().instances.push((timestamps, device));
+        }
+    }
+}
+
+impl<D> Dispatch<ZwpInputTimestampsV1, InputTimestampsUserData, D> for InputTimestampsManagerState
+where
+    D: Dispatch<ZwpInputTimestampsV1, InputTimestampsUserData>,
+    D: SeatHandler,
+    D: 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _timestamps: &ZwpInputTimestampsV1,
+        request: zwp_input_timestamps_v1::Request,
+        _data: &InputTimestampsUserData,
+        _dh: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            zwp_input_timestamps_v1::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(_state: &mut D, _: ClientId, object: &ZwpInputTimestampsV1, data: &InputTimestampsUserData) {
+        if let Some(ref handle) = data.handle {
+            handle
+                .inner
+                .lock()
+                .unwrap()
+                .instances
+                .retain(|(timestamps, _)| timestamps.id() != object.id());
+        }
+    }
+}
+
+/// Macro to delegate implementation of the input timestamps protocol
+#[macro_export]
+macro_rules! delegate_input_timestamps {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        $crate::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::wp::input_timestamps::zv1::server::zwp_input_timestamps_manager_v1::ZwpInputTimestampsManagerV1: ()
+        ] => $crate::wayland::input_timestamps::InputTimestampsManagerState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::wp::input_timestamps::zv1::server::zwp_input_timestamps_manager_v1::ZwpInputTimestampsManagerV1: ()
+        ] => $crate::wayland::input_timestamps::InputTimestampsManagerState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::wp::input_timestamps::zv1::server::zwp_input_timestamps_v1::ZwpInputTimestampsV1: $crate::wayland::input_timestamps::InputTimestampsUserData
+        ] => $crate::wayland::input_timestamps::InputTimestampsManagerState);
+    };
+}
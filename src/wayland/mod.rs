@@ -60,6 +60,7 @@ pub mod fractional_scale;
 pub mod idle_inhibit;
 pub mod idle_notify;
 pub mod input_method;
+pub mod input_timestamps;
 pub mod keyboard_shortcuts_inhibit;
 pub mod output;
 pub mod pointer_constraints;
@@ -78,6 +79,7 @@ pub mod tablet_manager;
 pub mod text_input;
 pub mod viewporter;
 pub mod virtual_keyboard;
+pub mod virtual_pointer;
 pub mod xdg_activation;
 pub mod xdg_foreign;
 pub mod xdg_system_bell;
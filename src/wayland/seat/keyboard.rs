@@ -18,7 +18,9 @@ use crate::{
         Seat, SeatHandler, SeatState,
     },
     utils::Serial,
-    wayland::{input_method::InputMethodSeat, text_input::TextInputSeat},
+    wayland::{
+        input_method::InputMethodSeat, input_timestamps::InputTimestampsSeat, text_input::TextInputSeat,
+    },
 };
 
 impl<D> KeyboardHandle<D>
@@ -207,7 +209,9 @@ impl<D: SeatHandler + 'static> KeyboardTarget<D> for WlSurface {
         serial: Serial,
         time: u32,
     ) {
+        let timestamps = seat.input_timestamps();
         for_each_focused_kbds(seat, self, |kbd| {
+            timestamps.send(&kbd, time);
             kbd.key(serial.into(), time, key.raw_code().raw() - 8, state.into())
         })
     }
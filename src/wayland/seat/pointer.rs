@@ -27,7 +27,11 @@ use crate::{
         Seat,
     },
     utils::{Client, Point, Serial},
-    wayland::{compositor, pointer_constraints::with_pointer_constraint},
+    wayland::{
+        compositor,
+        input_timestamps::{InputTimestampsHandle, InputTimestampsSeat},
+        pointer_constraints::with_pointer_constraint,
+    },
 };
 
 use super::{SeatHandler, SeatState, WaylandFocus};
@@ -88,7 +92,12 @@ impl WlPointerHandle {
         *self.last_enter.lock().unwrap() = None;
     }
 
-    fn motion<D: SeatHandler + 'static>(&self, surface: &WlSurface, event: &MotionEvent) {
+    fn motion<D: SeatHandler + 'static>(
+        &self,
+        surface: &WlSurface,
+        event: &MotionEvent,
+        timestamps: &InputTimestampsHandle,
+    ) {
         self.for_each_focused_pointer(surface, |ptr| {
             let client_scale = ptr
                 .data::<PointerUserData<D>>()
@@ -96,17 +105,25 @@ impl WlPointerHandle {
                 .client_scale
                 .load(Ordering::Acquire);
             let location = event.location.to_client(client_scale as f64);
+            timestamps.send(&ptr, event.time);
             ptr.motion(event.time, location.x, location.y);
         })
     }
 
-    fn button(&self, surface: &WlSurface, event: &ButtonEvent) {
+    fn button(&self, surface: &WlSurface, event: &ButtonEvent, timestamps: &InputTimestampsHandle) {
         self.for_each_focused_pointer(surface, |ptr| {
+            timestamps.send(&ptr, event.time);
             ptr.button(event.serial.into(), event.time, event.button, event.state.into());
         })
     }
 
-    fn axis<D: SeatHandler + 'static>(&self, surface: &WlSurface, details: AxisFrame) {
+    fn axis<D: SeatHandler + 'static>(
+        &self,
+        surface: &WlSurface,
+        details: AxisFrame,
+        timestamps: &InputTimestampsHandle,
+    ) {
+        // the timestamp applies to the next event carrying a time, so it is sent for every one of them
         self.for_each_focused_pointer(surface, |ptr| {
             if ptr.version() >= 5 {
                 // axis source
@@ -157,6 +174,7 @@ impl WlPointerHandle {
                 }
                 // stop
                 if details.stop.0 {
+                    timestamps.send(&ptr, details.time);
                     ptr.axis_stop(details.time, WlAxis::HorizontalScroll);
 
                     compositor::with_states(surface, |states| {
@@ -166,6 +184,7 @@ impl WlPointerHandle {
                     });
                 }
                 if details.stop.1 {
+                    timestamps.send(&ptr, details.time);
                     ptr.axis_stop(details.time, WlAxis::VerticalScroll);
 
                     compositor::with_states(surface, |states| {
@@ -188,6 +207,7 @@ impl WlPointerHandle {
                         details.relative_direction.0.into(),
                     );
                 }
+                timestamps.send(&ptr, details.time);
                 ptr.axis(
                     details.time,
                     WlAxis::HorizontalScroll,
@@ -198,6 +218,7 @@ impl WlPointerHandle {
                 if ptr.version() >= 9 {
                     ptr.axis_relative_direction(WlAxis::VerticalScroll, details.relative_direction.1.into());
                 }
+                timestamps.send(&ptr, details.time);
                 ptr.axis(
                     details.time,
                     WlAxis::VerticalScroll,
@@ -260,7 +281,9 @@ where
 
     fn motion(&self, seat: &Seat<D>, _data: &mut D, event: &MotionEvent) {
         if let Some(pointer) = seat.get_pointer() {
-            pointer.wl_pointer.motion::<D>(self, event);
+            pointer
+                .wl_pointer
+                .motion::<D>(self, event, seat.input_timestamps());
         }
     }
 
@@ -272,13 +295,15 @@ where
 
     fn button(&self, seat: &Seat<D>, _data: &mut D, event: &ButtonEvent) {
         if let Some(pointer) = seat.get_pointer() {
-            pointer.wl_pointer.button(self, event);
+            pointer.wl_pointer.button(self, event, seat.input_timestamps());
         }
     }
 
     fn axis(&self, seat: &Seat<D>, _data: &mut D, details: AxisFrame) {
         if let Some(pointer) = seat.get_pointer() {
-            pointer.wl_pointer.axis::<D>(self, details);
+            pointer
+                .wl_pointer
+                .axis::<D>(self, details, seat.input_timestamps());
         }
     }
 
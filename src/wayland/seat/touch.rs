@@ -16,7 +16,7 @@ use crate::input::{
     Seat,
 };
 use crate::{input::touch::DownEvent, wayland::seat::wl_surface::WlSurface};
-use crate::{input::touch::TouchHandle, utils::Serial};
+use crate::{input::touch::TouchHandle, utils::Serial, wayland::input_timestamps::InputTimestampsSeat};
 
 impl<D: SeatHandler> TouchHandle<D> {
     pub(crate) fn new_touch(&self, touch: WlTouch) {
@@ -71,6 +71,7 @@ where
                 .client_scale
                 .load(Ordering::Acquire);
             let location = event.location.to_client(client_scale as f64);
+            seat.input_timestamps().send(&touch, event.time);
             touch.down(
                 serial.into(),
                 event.time,
@@ -86,6 +87,7 @@ where
         let serial = event.serial;
         let slot = event.slot;
         for_each_focused_touch(seat, self, seq, |touch| {
+            seat.input_timestamps().send(&touch, event.time);
             touch.up(serial.into(), event.time, slot.into());
         })
     }
@@ -99,6 +101,7 @@ where
                 .client_scale
                 .load(Ordering::Acquire);
             let location = event.location.to_client(client_scale as f64);
+            seat.input_timestamps().send(&touch, event.time);
             touch.motion(event.time, slot.into(), location.x, location.y);
         })
     }
@@ -1,6 +1,7 @@
 use std::any::Any;
 use std::any::TypeId;
 
+use wayland_protocols::ext::data_control::v1::server::ext_data_control_device_v1::ExtDataControlDeviceV1;
 use wayland_protocols::wp::primary_selection::zv1::server::zwp_primary_selection_device_v1::ZwpPrimarySelectionDeviceV1 as PrimaryDevice;
 use wayland_protocols_wlr::data_control::v1::server::zwlr_data_control_device_v1::ZwlrDataControlDeviceV1;
 use wayland_server::backend::ObjectId;
@@ -19,6 +20,7 @@ pub enum SelectionDevice {
     DataDevice(WlDataDevice),
     Primary(PrimaryDevice),
     DataControl(ZwlrDataControlDeviceV1),
+    ExtDataControl(ExtDataControlDeviceV1),
 }
 
 impl SelectionDevice {
@@ -54,6 +56,10 @@ impl SelectionDevice {
                 let data: &DataControlDeviceUserData = device.data().unwrap();
                 data.wl_seat.clone()
             }
+            SelectionDevice::ExtDataControl(device) => {
+                let data: &DataControlDeviceUserData = device.data().unwrap();
+                data.wl_seat.clone()
+            }
         }
     }
 
@@ -66,6 +72,9 @@ impl SelectionDevice {
             (Self::DataControl(device), SelectionOffer::DataControl(offer)) => {
                 device.selection(Some(offer));
             }
+            (Self::ExtDataControl(device), SelectionOffer::ExtDataControl(offer)) => {
+                device.selection(Some(offer));
+            }
             _ => unreachable!("non-supported configuration for setting clipboard selection."),
         }
     }
@@ -74,6 +83,7 @@ impl SelectionDevice {
         match self {
             Self::DataDevice(device) => device.selection(None),
             Self::DataControl(device) => device.selection(None),
+            Self::ExtDataControl(device) => device.selection(None),
             Self::Primary(_) => unreachable!("primary clipboard has no clipboard selection"),
         }
     }
@@ -86,6 +96,9 @@ impl SelectionDevice {
             (Self::DataControl(device), SelectionOffer::DataControl(offer)) => {
                 device.primary_selection(Some(offer));
             }
+            (Self::ExtDataControl(device), SelectionOffer::ExtDataControl(offer)) => {
+                device.primary_selection(Some(offer));
+            }
             _ => unreachable!("non-supported configuration for setting clipboard selection."),
         }
     }
@@ -94,6 +107,7 @@ impl SelectionDevice {
         match self {
             Self::Primary(device) => device.selection(None),
             Self::DataControl(device) => device.primary_selection(None),
+            Self::ExtDataControl(device) => device.primary_selection(None),
             Self::DataDevice(_) => unreachable!("data control has primary selection"),
         }
     }
@@ -0,0 +1,91 @@
+//! Automatic handling of the `ext_data_control` protocol
+//!
+//! This is the standardized successor of the [`wlr_data_control`](super::wlr_data_control) protocol.
+//! Both protocols are served by the same [`DataControlState`], share the selection state of a seat
+//! and use the same client filter, so they can be offered side by side to support both generations
+//! of clipboard managers.
+//!
+//! ## Initialization
+//!
+//! Set up the [`wlr_data_control`](super::wlr_data_control) protocol and enable the `ext_data_control`
+//! global on its state, as shown in the example:
+//!
+//! ```
+//! # extern crate wayland_server;
+//! # #[macro_use] extern crate smithay;
+//! use smithay::wayland::selection::SelectionHandler;
+//! use smithay::wayland::selection::wlr_data_control::{DataControlState, DataControlHandler};
+//! # use smithay::input::{Seat, SeatHandler, SeatState, pointer::CursorImageStatus};
+//! # use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
+//!
+//! # struct State { data_control_state: DataControlState }
+//! # let mut display = wayland_server::Display::<State>::new().unwrap();
+//! // Create the data_control state
+//! let mut data_control_state = DataControlState::new::<State, _>(
+//!     &display.handle(), None, |_| true
+//! );
+//! // and offer the ext_data_control protocol as well
+//! data_control_state.enable_ext_data_control::<State>(&display.handle());
+//!
+//! // insert the DataControlState into your state
+//! // ..
+//!
+//! // implement the necessary traits
+//! # impl SeatHandler for State {
+//! #     type KeyboardFocus = WlSurface;
+//! #     type PointerFocus = WlSurface;
+//! #     type TouchFocus = WlSurface;
+//! #     fn seat_state(&mut self) -> &mut SeatState<Self> { unimplemented!() }
+//! #     fn focus_changed(&mut self, seat: &Seat<Self>, focused: Option<&WlSurface>) { unimplemented!() }
+//! #     fn cursor_image(&mut self, seat: &Seat<Self>, image: CursorImageStatus) { unimplemented!() }
+//! # }
+//! impl SelectionHandler for State {
+//!     type SelectionUserData = ();
+//! }
+//! impl DataControlHandler for State {
+//!     fn data_control_state(&self) -> &DataControlState { &self.data_control_state }
+//!     // ... override default implementations here to customize handling ...
+//! }
+//! delegate_data_control!(State);
+//! delegate_ext_data_control!(State);
+//!
+//! // You're now ready to go!
+//! ```
+//!
+//! Be aware that data control clients rely on other selection providers to be implemented, like
+//! wl_data_device or zwp_primary_selection.
+
+use wayland_protocols::ext::data_control::v1::server::{
+    ext_data_control_device_v1::{self, ExtDataControlDeviceV1},
+    ext_data_control_manager_v1::{self, ExtDataControlManagerV1},
+    ext_data_control_source_v1::{self, ExtDataControlSourceV1},
+};
+
+use super::wlr_data_control::data_control_dispatch;
+pub use super::wlr_data_control::{DataControlHandler, DataControlState};
+
+data_control_dispatch!(
+    ext_data_control_manager_v1::ExtDataControlManagerV1,
+    ext_data_control_device_v1::ExtDataControlDeviceV1,
+    ext_data_control_source_v1::ExtDataControlSourceV1,
+    ExtDataControl
+);
+
+#[allow(missing_docs)] // TODO
+#[macro_export]
+macro_rules! delegate_ext_data_control {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        $crate::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::ext::data_control::v1::server::ext_data_control_manager_v1::ExtDataControlManagerV1: $crate::wayland::selection::wlr_data_control::DataControlManagerGlobalData
+        ] => $crate::wayland::selection::wlr_data_control::DataControlState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::ext::data_control::v1::server::ext_data_control_manager_v1::ExtDataControlManagerV1: $crate::wayland::selection::wlr_data_control::DataControlManagerUserData
+        ] => $crate::wayland::selection::wlr_data_control::DataControlState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::ext::data_control::v1::server::ext_data_control_device_v1::ExtDataControlDeviceV1: $crate::wayland::selection::wlr_data_control::DataControlDeviceUserData
+        ] => $crate::wayland::selection::wlr_data_control::DataControlState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::ext::data_control::v1::server::ext_data_control_source_v1::ExtDataControlSourceV1: $crate::wayland::selection::wlr_data_control::DataControlSourceUserData
+        ] => $crate::wayland::selection::wlr_data_control::DataControlState);
+    };
+}
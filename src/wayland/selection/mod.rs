@@ -10,12 +10,15 @@
 //! - The [`primary_selection`](primary_selection/index.html) module to work with the primary selection.
 //! - The [`wlr_data_control`](wlr_data_control/index.html) module to hook data control into
 //!   clipboard and primary selection
+//! - The [`ext_data_control`](ext_data_control/index.html) module, the standardized successor
+//!   of `wlr_data_control`, sharing the same selection state
 
 use std::os::unix::io::OwnedFd;
 
 use crate::input::{Seat, SeatHandler};
 
 pub mod data_device;
+pub mod ext_data_control;
 pub mod primary_selection;
 pub mod wlr_data_control;
 
@@ -68,6 +71,7 @@ pub(crate) mod private {
     ///    Enum::DataDevice(foo) => foo.something(),
     ///    Enum::Primary(foo) => foo.something(),
     ///    Enum::DataControl(foo) => foo.something(),
+    ///    Enum::ExtDataControl(foo) => foo.something(),
     /// }
     /// ```
     ///
@@ -78,6 +82,7 @@ pub(crate) mod private {
     ///    (Enum::DataDevice(foo), EnumNext::DataDevice(zoo))  => foo.something(zoo),
     ///    (Enum::Primary(foo), EnumNext::Primary(zoo))  => foo.something(zoo),
     ///    (Enum::DataControl(foo), EnumNext::DataControl(zoo))  => foo.something(zoo),
+    ///    (Enum::ExtDataControl(foo), EnumNext::ExtDataControl(zoo))  => foo.something(zoo),
     ///    _ => unreachable!(),
     /// }
     /// ```
@@ -89,6 +94,7 @@ pub(crate) mod private {
                 $enum::DataDevice($($c1)*) => $x,
                 $enum::Primary($($c1)*) => $x,
                 $enum::DataControl($($c1)*) => $x,
+                $enum::ExtDataControl($($c1)*) => $x,
             }
         };
         ($what:ident$(, $what_next:ident)+; $enum:ident ( $($c1:tt)*) $(, $enum_next:ident ( $($c2:tt)* ) )+ => $x:expr) => {
@@ -96,6 +102,7 @@ pub(crate) mod private {
                 ($enum::DataDevice($($c1)*)$(, $enum_next::DataDevice($($c2)*))*) => $x,
                 ($enum::Primary($($c1)*)$(, $enum_next::Primary($($c2)*))*) => $x,
                 ($enum::DataControl($($c1)*)$(, $enum_next::DataControl($($c2)*))*) => $x,
+                ($enum::ExtDataControl($($c1)*)$(, $enum_next::ExtDataControl($($c2)*))*) => $x,
                 _ => unreachable!(),
             }
         };
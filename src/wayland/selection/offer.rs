@@ -2,7 +2,12 @@ use std::any::TypeId;
 use std::os::unix::io::OwnedFd;
 use std::sync::Arc;
 
+use ext_data_control_offer_v1::Request as ExtDataControlRequest;
 use tracing::debug;
+use wayland_protocols::ext::data_control::v1::server::ext_data_control_device_v1::ExtDataControlDeviceV1;
+use wayland_protocols::ext::data_control::v1::server::ext_data_control_offer_v1::{
+    self, ExtDataControlOfferV1 as ExtDataControlOffer,
+};
 use wayland_protocols::wp::primary_selection::zv1::server::zwp_primary_selection_device_v1::ZwpPrimarySelectionDeviceV1 as PrimaryDevice;
 use wayland_protocols::wp::primary_selection::zv1::server::zwp_primary_selection_offer_v1::{
     self, ZwpPrimarySelectionOfferV1 as PrimaryOffer,
@@ -61,6 +66,7 @@ pub enum SelectionOffer {
     DataDevice(WlDataOffer),
     Primary(PrimaryOffer),
     DataControl(DataControlOffer),
+    ExtDataControl(ExtDataControlOffer),
 }
 
 impl SelectionOffer {
@@ -88,6 +94,8 @@ impl SelectionOffer {
             WlDataOffer::interface()
         } else if type_id == TypeId::of::<PrimaryDevice>() {
             PrimaryOffer::interface()
+        } else if type_id == TypeId::of::<ExtDataControlDeviceV1>() {
+            ExtDataControlOffer::interface()
         } else {
             DataControlOffer::interface()
         };
@@ -100,6 +108,8 @@ impl SelectionOffer {
             Self::DataDevice(WlDataOffer::from_id(dh, offer).unwrap())
         } else if type_id == TypeId::of::<PrimaryDevice>() {
             Self::Primary(PrimaryOffer::from_id(dh, offer).unwrap())
+        } else if type_id == TypeId::of::<ExtDataControlDeviceV1>() {
+            Self::ExtDataControl(ExtDataControlOffer::from_id(dh, offer).unwrap())
         } else {
             Self::DataControl(DataControlOffer::from_id(dh, offer).unwrap())
         }
@@ -149,6 +159,14 @@ where
             } else {
                 return None;
             }
+        } else if type_id == TypeId::of::<ExtDataControlDeviceV1>() {
+            if let Ok((_resource, ExtDataControlRequest::Receive { mime_type, fd })) =
+                ExtDataControlOffer::parse_request(&dh, msg)
+            {
+                (mime_type, fd, "ext_data_control_offer")
+            } else {
+                return None;
+            }
         } else if let Ok((_resource, DataControlRequest::Receive { mime_type, fd })) =
             DataControlOffer::parse_request(&dh, msg)
         {
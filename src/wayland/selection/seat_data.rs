@@ -172,15 +172,18 @@ impl<U: Clone + Send + Sync + 'static> SeatData<U> {
                         && (data_control.version() >= EVT_PRIMARY_SELECTION_SINCE
                             || ty != SelectionTarget::Primary)
                 }
+                SelectionDevice::ExtDataControl(_) => update_data_control,
             })
         {
             // Data control doesn't require focus and should always get selection updates, unless
             // it was requested not to update them.
-            if !matches!(device, SelectionDevice::DataControl(_))
-                && dh
-                    .get_client(device.id())
-                    .map(|c| Some(&c) != client)
-                    .unwrap_or(true)
+            if !matches!(
+                device,
+                SelectionDevice::DataControl(_) | SelectionDevice::ExtDataControl(_)
+            ) && dh
+                .get_client(device.id())
+                .map(|c| Some(&c) != client)
+                .unwrap_or(true)
             {
                 continue;
             }
@@ -198,7 +201,7 @@ impl<U: Clone + Send + Sync + 'static> SeatData<U> {
                     // DataControl devices is the client itself, however other devices use
                     // the currently focused one as a client.
                     let client_id = match device {
-                        SelectionDevice::DataControl(device) => {
+                        SelectionDevice::DataControl(_) | SelectionDevice::ExtDataControl(_) => {
                             dh.get_client(device.id()).ok().map(|c| c.id())
                         }
                         _ => client.map(|c| c.id()),
@@ -1,5 +1,6 @@
 use std::os::unix::io::{AsFd, OwnedFd};
 
+use wayland_protocols::ext::data_control::v1::server::ext_data_control_source_v1::ExtDataControlSourceV1 as ExtDataControlSource;
 use wayland_protocols::wp::primary_selection::zv1::server::zwp_primary_selection_source_v1::ZwpPrimarySelectionSourceV1 as PrimarySource;
 use wayland_protocols_wlr::data_control::v1::server::zwlr_data_control_source_v1::ZwlrDataControlSourceV1 as DataControlSource;
 use wayland_server::{protocol::wl_data_source::WlDataSource, Resource};
@@ -35,6 +36,8 @@ pub enum SelectionSourceProvider {
     Primary(PrimarySource),
     /// The data control selection was used as source.
     DataControl(DataControlSource),
+    /// The ext data control selection was used as source.
+    ExtDataControl(ExtDataControlSource),
 }
 
 impl SelectionSourceProvider {
@@ -63,6 +66,10 @@ impl SelectionSourceProvider {
                 let data: &DataControlSourceUserData = source.data().unwrap();
                 data.inner.lock().unwrap().mime_types.contains(mime_type)
             }
+            Self::ExtDataControl(source) => {
+                let data: &DataControlSourceUserData = source.data().unwrap();
+                data.inner.lock().unwrap().mime_types.contains(mime_type)
+            }
         }
     }
 
@@ -81,6 +88,10 @@ impl SelectionSourceProvider {
                 let data: &DataControlSourceUserData = source.data().unwrap();
                 data.inner.lock().unwrap().mime_types.clone()
             }
+            Self::ExtDataControl(source) => {
+                let data: &DataControlSourceUserData = source.data().unwrap();
+                data.inner.lock().unwrap().mime_types.clone()
+            }
         }
     }
 }
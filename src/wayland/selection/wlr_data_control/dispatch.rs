@@ -0,0 +1,301 @@
+// The wlr and ext data control protocols only differ in their names, so the dispatch
+// implementations of both are generated from the same code.
+//
+// Expects the protocol modules of the manager, device and source interfaces, their interface types
+// and the variant of `SelectionDevice` and `SelectionSourceProvider` used for the protocol.
+macro_rules! data_control_dispatch {
+    (
+        $manager_mod:ident::$manager:ident,
+        $device_mod:ident::$device:ident,
+        $source_mod:ident::$source:ident,
+        $variant:ident
+    ) => {
+        impl<D>
+            wayland_server::GlobalDispatch<
+                $manager,
+                crate::wayland::selection::wlr_data_control::DataControlManagerGlobalData,
+                D,
+            > for crate::wayland::selection::wlr_data_control::DataControlState
+        where
+            D: wayland_server::GlobalDispatch<
+                $manager,
+                crate::wayland::selection::wlr_data_control::DataControlManagerGlobalData,
+            >,
+            D: wayland_server::Dispatch<
+                $manager,
+                crate::wayland::selection::wlr_data_control::DataControlManagerUserData,
+            >,
+            D: wayland_server::Dispatch<
+                $device,
+                crate::wayland::selection::wlr_data_control::DataControlDeviceUserData,
+            >,
+            D: wayland_server::Dispatch<
+                $source,
+                crate::wayland::selection::wlr_data_control::DataControlSourceUserData,
+            >,
+            D: crate::wayland::selection::wlr_data_control::DataControlHandler,
+            D: 'static,
+        {
+            fn bind(
+                _state: &mut D,
+                _handle: &wayland_server::DisplayHandle,
+                _client: &wayland_server::Client,
+                resource: wayland_server::New<$manager>,
+                global_data: &crate::wayland::selection::wlr_data_control::DataControlManagerGlobalData,
+                data_init: &mut wayland_server::DataInit<'_, D>,
+            ) {
+                data_init.init(
+                    resource,
+                    crate::wayland::selection::wlr_data_control::DataControlManagerUserData {
+                        primary: global_data.primary,
+                    },
+                );
+            }
+
+            fn can_view(
+                client: wayland_server::Client,
+                global_data: &crate::wayland::selection::wlr_data_control::DataControlManagerGlobalData,
+            ) -> bool {
+                (global_data.filter)(&client)
+            }
+        }
+
+        impl<D>
+            wayland_server::Dispatch<
+                $manager,
+                crate::wayland::selection::wlr_data_control::DataControlManagerUserData,
+                D,
+            > for crate::wayland::selection::wlr_data_control::DataControlState
+        where
+            D: wayland_server::Dispatch<
+                $manager,
+                crate::wayland::selection::wlr_data_control::DataControlManagerUserData,
+            >,
+            D: wayland_server::Dispatch<
+                $device,
+                crate::wayland::selection::wlr_data_control::DataControlDeviceUserData,
+            >,
+            D: wayland_server::Dispatch<
+                $source,
+                crate::wayland::selection::wlr_data_control::DataControlSourceUserData,
+            >,
+            D: crate::wayland::selection::wlr_data_control::DataControlHandler,
+            D: 'static,
+        {
+            fn request(
+                _handler: &mut D,
+                client: &wayland_server::Client,
+                _resource: &$manager,
+                request: $manager_mod::Request,
+                data: &crate::wayland::selection::wlr_data_control::DataControlManagerUserData,
+                dh: &wayland_server::DisplayHandle,
+                data_init: &mut wayland_server::DataInit<'_, D>,
+            ) {
+                use std::cell::RefCell;
+
+                use crate::input::Seat;
+                use crate::wayland::selection::device::SelectionDevice;
+                use crate::wayland::selection::seat_data::SeatData;
+                use crate::wayland::selection::wlr_data_control::{
+                    DataControlDeviceUserData, DataControlSourceUserData,
+                };
+                use crate::wayland::selection::SelectionTarget;
+
+                match request {
+                    $manager_mod::Request::CreateDataSource { id } => {
+                        data_init.init(id, DataControlSourceUserData::new());
+                    }
+                    $manager_mod::Request::GetDataDevice { id, seat: wl_seat } => {
+                        match Seat::<D>::from_resource(&wl_seat) {
+                            Some(seat) => {
+                                seat.user_data().insert_if_missing(|| {
+                                    RefCell::new(SeatData::<D::SelectionUserData>::new())
+                                });
+
+                                let device = SelectionDevice::$variant(data_init.init(
+                                    id,
+                                    DataControlDeviceUserData {
+                                        wl_seat,
+                                        primary: data.primary,
+                                    },
+                                ));
+
+                                let mut seat_data = seat
+                                    .user_data()
+                                    .get::<RefCell<SeatData<D::SelectionUserData>>>()
+                                    .unwrap()
+                                    .borrow_mut();
+
+                                seat_data.add_device(device.clone());
+
+                                // NOTE: broadcast selection only to the newly created device.
+                                let device = Some(&device);
+                                seat_data.send_selection::<D>(dh, SelectionTarget::Clipboard, device, true);
+                                if data.primary {
+                                    seat_data.send_selection::<D>(dh, SelectionTarget::Primary, device, true);
+                                }
+                            }
+                            None => {
+                                tracing::error!(
+                                    data_control_device = ?id,
+                                    client = ?client,
+                                    "Unmanaged seat given to a primary selection device."
+                                );
+                            }
+                        }
+                    }
+                    $manager_mod::Request::Destroy => (),
+                    _ => unreachable!(),
+                }
+            }
+        }
+
+        impl<D>
+            wayland_server::Dispatch<
+                $device,
+                crate::wayland::selection::wlr_data_control::DataControlDeviceUserData,
+                D,
+            > for crate::wayland::selection::wlr_data_control::DataControlState
+        where
+            D: wayland_server::Dispatch<
+                $device,
+                crate::wayland::selection::wlr_data_control::DataControlDeviceUserData,
+            >,
+            D: crate::wayland::selection::wlr_data_control::DataControlHandler,
+            D: 'static,
+        {
+            fn request(
+                handler: &mut D,
+                _client: &wayland_server::Client,
+                resource: &$device,
+                request: $device_mod::Request,
+                data: &crate::wayland::selection::wlr_data_control::DataControlDeviceUserData,
+                dh: &wayland_server::DisplayHandle,
+                _: &mut wayland_server::DataInit<'_, D>,
+            ) {
+                use std::cell::RefCell;
+
+                use crate::input::Seat;
+                use crate::wayland::selection::device::SelectionDevice;
+                use crate::wayland::selection::offer::OfferReplySource;
+                use crate::wayland::selection::seat_data::SeatData;
+                use crate::wayland::selection::source::SelectionSourceProvider;
+                use crate::wayland::selection::{SelectionSource, SelectionTarget};
+
+                let seat = match Seat::<D>::from_resource(&data.wl_seat) {
+                    Some(seat) => seat,
+                    None => return,
+                };
+
+                match request {
+                    $device_mod::Request::SetSelection { source, .. } => {
+                        seat.user_data()
+                            .insert_if_missing(|| RefCell::new(SeatData::<D::SelectionUserData>::new()));
+
+                        let source = source.map(SelectionSourceProvider::$variant);
+
+                        handler.new_selection(
+                            SelectionTarget::Clipboard,
+                            source.clone().map(|provider| SelectionSource { provider }),
+                            seat.clone(),
+                        );
+
+                        seat.user_data()
+                            .get::<RefCell<SeatData<D::SelectionUserData>>>()
+                            .unwrap()
+                            .borrow_mut()
+                            .set_clipboard_selection::<D>(dh, source.map(OfferReplySource::Client));
+                    }
+                    $device_mod::Request::SetPrimarySelection { source, .. } => {
+                        // When the primary selection is disabled, we should simply ignore the requests.
+                        if !data.primary {
+                            return;
+                        }
+
+                        seat.user_data()
+                            .insert_if_missing(|| RefCell::new(SeatData::<D::SelectionUserData>::new()));
+
+                        let source = source.map(SelectionSourceProvider::$variant);
+
+                        handler.new_selection(
+                            SelectionTarget::Primary,
+                            source.clone().map(|provider| SelectionSource { provider }),
+                            seat.clone(),
+                        );
+
+                        seat.user_data()
+                            .get::<RefCell<SeatData<D::SelectionUserData>>>()
+                            .unwrap()
+                            .borrow_mut()
+                            .set_primary_selection::<D>(dh, source.map(OfferReplySource::Client));
+                    }
+                    $device_mod::Request::Destroy => seat
+                        .user_data()
+                        .get::<RefCell<SeatData<D::SelectionUserData>>>()
+                        .unwrap()
+                        .borrow_mut()
+                        .retain_devices(|ndd| match ndd {
+                            SelectionDevice::$variant(ndd) => ndd != resource,
+                            _ => true,
+                        }),
+
+                    _ => unreachable!(),
+                }
+            }
+        }
+
+        impl<D>
+            wayland_server::Dispatch<
+                $source,
+                crate::wayland::selection::wlr_data_control::DataControlSourceUserData,
+                D,
+            > for crate::wayland::selection::wlr_data_control::DataControlState
+        where
+            D: wayland_server::Dispatch<
+                $source,
+                crate::wayland::selection::wlr_data_control::DataControlSourceUserData,
+            >,
+            D: crate::wayland::selection::wlr_data_control::DataControlHandler,
+            D: 'static,
+        {
+            fn request(
+                _state: &mut D,
+                _client: &wayland_server::Client,
+                _resource: &$source,
+                request: $source_mod::Request,
+                data: &crate::wayland::selection::wlr_data_control::DataControlSourceUserData,
+                _dhandle: &wayland_server::DisplayHandle,
+                _data_init: &mut wayland_server::DataInit<'_, D>,
+            ) {
+                match request {
+                    $source_mod::Request::Offer { mime_type } => {
+                        let mut data = data.inner.lock().unwrap();
+                        data.mime_types.push(mime_type);
+                    }
+                    $source_mod::Request::Destroy => (),
+                    _ => unreachable!(),
+                }
+            }
+
+            fn destroyed(
+                _state: &mut D,
+                _client: wayland_server::backend::ClientId,
+                _resource: &$source,
+                data: &crate::wayland::selection::wlr_data_control::DataControlSourceUserData,
+            ) {
+                data.alive_tracker.destroy_notify();
+            }
+        }
+
+        impl crate::utils::IsAlive for $source {
+            #[inline]
+            fn alive(&self) -> bool {
+                let data: &crate::wayland::selection::wlr_data_control::DataControlSourceUserData =
+                    wayland_server::Resource::data(self).unwrap();
+                data.alive_tracker.alive()
+            }
+        }
+    };
+}
+
+pub(crate) use data_control_dispatch;
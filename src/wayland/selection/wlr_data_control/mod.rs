@@ -44,17 +44,27 @@
 //! // You're now ready to go!
 //! ```
 //!
-//! Be aware that data control clients rely on other selection providers to be implemneted, like
+//! Be aware that data control clients rely on other selection providers to be implemented, like
 //! wl_data_device or zwp_primary_selection.
 
-use wayland_protocols_wlr::data_control::v1::server::zwlr_data_control_manager_v1::ZwlrDataControlManagerV1;
+use std::fmt;
+use std::sync::Arc;
+
+use wayland_protocols::ext::data_control::v1::server::ext_data_control_manager_v1::ExtDataControlManagerV1;
+use wayland_protocols_wlr::data_control::v1::server::{
+    zwlr_data_control_device_v1::{self, ZwlrDataControlDeviceV1},
+    zwlr_data_control_manager_v1::{self, ZwlrDataControlManagerV1},
+    zwlr_data_control_source_v1::{self, ZwlrDataControlSourceV1},
+};
 use wayland_server::backend::GlobalId;
 use wayland_server::{Client, DisplayHandle, GlobalDispatch};
 
 mod device;
+mod dispatch;
 mod source;
 
 pub use device::DataControlDeviceUserData;
+pub(crate) use dispatch::data_control_dispatch;
 pub use source::DataControlSourceUserData;
 
 use super::primary_selection::PrimarySelectionState;
@@ -66,16 +76,34 @@ pub trait DataControlHandler: Sized + SelectionHandler {
     fn data_control_state(&self) -> &DataControlState;
 }
 
+type ClientFilter = Arc<dyn for<'c> Fn(&'c Client) -> bool + Send + Sync>;
+
 /// State of the data control.
-#[derive(Debug)]
+///
+/// The state is shared by the [`wlr_data_control`](self) and [`ext_data_control`](super::ext_data_control)
+/// protocols, see [`DataControlState::enable_ext_data_control`].
 pub struct DataControlState {
     manager_global: GlobalId,
+    ext_manager_global: Option<GlobalId>,
+    primary: bool,
+    filter: ClientFilter,
+}
+
+impl fmt::Debug for DataControlState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DataControlState")
+            .field("manager_global", &self.manager_global)
+            .field("ext_manager_global", &self.ext_manager_global)
+            .field("primary", &self.primary)
+            .finish_non_exhaustive()
+    }
 }
 
 impl DataControlState {
     /// Register new [ZwlrDataControlManagerV1] global.
     ///
     /// Passing `primary_selection` will enable support for primary selection as well.
+    /// `filter` decides which clients can see the data control globals.
     pub fn new<D, F>(
         display: &DisplayHandle,
         primary_selection: Option<&PrimarySelectionState>,
@@ -85,154 +113,79 @@ impl DataControlState {
         D: GlobalDispatch<ZwlrDataControlManagerV1, DataControlManagerGlobalData> + 'static,
         F: for<'c> Fn(&'c Client) -> bool + Send + Sync + 'static,
     {
+        let primary = primary_selection.is_some();
+        let filter: ClientFilter = Arc::new(filter);
         let data = DataControlManagerGlobalData {
-            primary: primary_selection.is_some(),
-            filter: Box::new(filter),
+            primary,
+            filter: filter.clone(),
         };
         let manager_global = display.create_global::<D, ZwlrDataControlManagerV1, _>(2, data);
-        Self { manager_global }
+        Self {
+            manager_global,
+            ext_manager_global: None,
+            primary,
+            filter,
+        }
+    }
+
+    /// Additionally register the [ExtDataControlManagerV1] global.
+    ///
+    /// The standardized `ext_data_control` protocol is offered with the same primary selection
+    /// support and client filter as the `wlr_data_control` protocol. Both share the selection state
+    /// of a seat, so clipboard managers of both generations are supported side by side.
+    ///
+    /// This requires [`delegate_ext_data_control`](crate::delegate_ext_data_control) in addition to
+    /// [`delegate_data_control`](crate::delegate_data_control).
+    /// Calling this more than once has no effect.
+    pub fn enable_ext_data_control<D>(&mut self, display: &DisplayHandle)
+    where
+        D: GlobalDispatch<ExtDataControlManagerV1, DataControlManagerGlobalData> + 'static,
+    {
+        if self.ext_manager_global.is_some() {
+            return;
+        }
+
+        let data = DataControlManagerGlobalData {
+            primary: self.primary,
+            filter: self.filter.clone(),
+        };
+        self.ext_manager_global = Some(display.create_global::<D, ExtDataControlManagerV1, _>(1, data));
     }
 
     /// [ZwlrDataControlManagerV1]  GlobalId getter.
     pub fn global(&self) -> GlobalId {
         self.manager_global.clone()
     }
+
+    /// [ExtDataControlManagerV1] GlobalId getter, if enabled.
+    pub fn ext_global(&self) -> Option<GlobalId> {
+        self.ext_manager_global.clone()
+    }
 }
 
 #[allow(missing_debug_implementations)]
 #[doc(hidden)]
 pub struct DataControlManagerGlobalData {
     /// Whether to allow primary selection.
-    primary: bool,
+    pub(crate) primary: bool,
 
     /// Filter whether the clients can view global.
-    filter: Box<dyn for<'c> Fn(&'c Client) -> bool + Send + Sync>,
+    pub(crate) filter: ClientFilter,
 }
 
 #[doc(hidden)]
 #[derive(Debug, Clone, Copy)]
 pub struct DataControlManagerUserData {
     /// Whether to allow primary selection.
-    primary: bool,
+    pub(crate) primary: bool,
 }
 
-mod handlers {
-    use std::cell::RefCell;
-
-    use tracing::error;
-    use wayland_protocols_wlr::data_control::v1::server::zwlr_data_control_device_v1::ZwlrDataControlDeviceV1;
-    use wayland_protocols_wlr::data_control::v1::server::zwlr_data_control_manager_v1;
-    use wayland_protocols_wlr::data_control::v1::server::zwlr_data_control_manager_v1::ZwlrDataControlManagerV1;
-    use wayland_protocols_wlr::data_control::v1::server::zwlr_data_control_source_v1::ZwlrDataControlSourceV1;
-    use wayland_server::{Client, Dispatch, DisplayHandle, GlobalDispatch};
-
-    use crate::input::Seat;
-    use crate::wayland::selection::device::SelectionDevice;
-    use crate::wayland::selection::seat_data::SeatData;
-    use crate::wayland::selection::SelectionTarget;
-
-    use super::DataControlDeviceUserData;
-    use super::DataControlHandler;
-    use super::DataControlManagerGlobalData;
-    use super::DataControlManagerUserData;
-    use super::DataControlSourceUserData;
-    use super::DataControlState;
-
-    impl<D> GlobalDispatch<ZwlrDataControlManagerV1, DataControlManagerGlobalData, D> for DataControlState
-    where
-        D: GlobalDispatch<ZwlrDataControlManagerV1, DataControlManagerGlobalData>,
-        D: Dispatch<ZwlrDataControlManagerV1, DataControlManagerUserData>,
-        D: Dispatch<ZwlrDataControlDeviceV1, DataControlDeviceUserData>,
-        D: Dispatch<ZwlrDataControlSourceV1, DataControlSourceUserData>,
-        D: DataControlHandler,
-        D: 'static,
-    {
-        fn bind(
-            _state: &mut D,
-            _handle: &DisplayHandle,
-            _client: &wayland_server::Client,
-            resource: wayland_server::New<ZwlrDataControlManagerV1>,
-            global_data: &DataControlManagerGlobalData,
-            data_init: &mut wayland_server::DataInit<'_, D>,
-        ) {
-            data_init.init(
-                resource,
-                DataControlManagerUserData {
-                    primary: global_data.primary,
-                },
-            );
-        }
-
-        fn can_view(client: Client, global_data: &DataControlManagerGlobalData) -> bool {
-            (global_data.filter)(&client)
-        }
-    }
-
-    impl<D> Dispatch<ZwlrDataControlManagerV1, DataControlManagerUserData, D> for DataControlState
-    where
-        D: Dispatch<ZwlrDataControlManagerV1, DataControlManagerUserData>,
-        D: Dispatch<ZwlrDataControlDeviceV1, DataControlDeviceUserData>,
-        D: Dispatch<ZwlrDataControlSourceV1, DataControlSourceUserData>,
-        D: DataControlHandler,
-        D: 'static,
-    {
-        fn request(
-            _handler: &mut D,
-            client: &wayland_server::Client,
-            _resource: &ZwlrDataControlManagerV1,
-            request: <ZwlrDataControlManagerV1 as wayland_server::Resource>::Request,
-            data: &DataControlManagerUserData,
-            dh: &DisplayHandle,
-            data_init: &mut wayland_server::DataInit<'_, D>,
-        ) {
-            match request {
-                zwlr_data_control_manager_v1::Request::CreateDataSource { id } => {
-                    data_init.init(id, DataControlSourceUserData::new());
-                }
-                zwlr_data_control_manager_v1::Request::GetDataDevice { id, seat: wl_seat } => {
-                    match Seat::<D>::from_resource(&wl_seat) {
-                        Some(seat) => {
-                            seat.user_data()
-                                .insert_if_missing(|| RefCell::new(SeatData::<D::SelectionUserData>::new()));
-
-                            let device = SelectionDevice::DataControl(data_init.init(
-                                id,
-                                DataControlDeviceUserData {
-                                    wl_seat,
-                                    primary: data.primary,
-                                },
-                            ));
-
-                            let mut seat_data = seat
-                                .user_data()
-                                .get::<RefCell<SeatData<D::SelectionUserData>>>()
-                                .unwrap()
-                                .borrow_mut();
-
-                            seat_data.add_device(device.clone());
-
-                            // NOTE: broadcast selection only to the newly created device.
-                            let device = Some(&device);
-                            seat_data.send_selection::<D>(dh, SelectionTarget::Clipboard, device, true);
-                            if data.primary {
-                                seat_data.send_selection::<D>(dh, SelectionTarget::Primary, device, true);
-                            }
-                        }
-                        None => {
-                            error!(
-                                data_control_device = ?id,
-                                client = ?client,
-                                "Unmanaged seat given to a primary selection device."
-                            );
-                        }
-                    }
-                }
-                zwlr_data_control_manager_v1::Request::Destroy => (),
-                _ => unreachable!(),
-            }
-        }
-    }
-}
+data_control_dispatch!(
+    zwlr_data_control_manager_v1::ZwlrDataControlManagerV1,
+    zwlr_data_control_device_v1::ZwlrDataControlDeviceV1,
+    zwlr_data_control_source_v1::ZwlrDataControlSourceV1,
+    DataControl
+);
 
 #[allow(missing_docs)] // TODO
 #[macro_export]
@@ -44,13 +44,23 @@
 //! # }
 //! ```
 
-use std::{ffi::OsStr, io, os::unix::net::UnixStream};
+use std::{
+    ffi::OsStr,
+    io,
+    os::unix::{
+        io::{AsRawFd, BorrowedFd},
+        net::UnixStream,
+        process::CommandExt,
+    },
+    process::{Child, Command},
+    sync::Arc,
+};
 
 use calloop::{
     generic::Generic, EventSource, Interest, Mode, Poll, PostAction, Readiness, Token, TokenFactory,
 };
 use tracing::{debug, info};
-use wayland_server::{BindError, ListeningSocket};
+use wayland_server::{backend::ClientData, BindError, Client, DisplayHandle, ListeningSocket};
 
 /// A Wayland listening socket event source.
 ///
@@ -133,3 +143,46 @@ impl EventSource for ListeningSocketSource {
         self.socket.unregister(poll)
     }
 }
+
+/// Spawns a process, that is connected to the compositor via a pre-connected `WAYLAND_SOCKET`.
+///
+/// The client is inserted into the display with the given `data` before the process is started,
+/// so the compositor knows which client belongs to the spawned process without relying on
+/// the listening socket or on pids. This makes it possible to grant trusted helpers (bars, lockers,
+/// portals, ...) access to privileged globals, e.g. by checking the client data in the global filter.
+///
+/// The environment of `command` is used as is, apart from `WAYLAND_SOCKET`. Since clients
+/// prefer `WAYLAND_SOCKET` over `WAYLAND_DISPLAY`, children of the spawned process, which
+/// inherit `WAYLAND_DISPLAY`, connect through the listening socket as usual.
+pub fn spawn_client(
+    dh: &mut DisplayHandle,
+    command: &mut Command,
+    data: Arc<dyn ClientData>,
+) -> io::Result<(Client, Child)> {
+    let (server_stream, client_stream) = UnixStream::pair()?;
+    let client = dh.insert_client(server_stream, data)?;
+
+    let fd = client_stream.as_raw_fd();
+    command.env("WAYLAND_SOCKET", fd.to_string());
+    unsafe {
+        command.pre_exec(move || {
+            // unset the CLOEXEC flag, so the client side of the socket is passed to the process
+            rustix::io::fcntl_setfd(BorrowedFd::borrow_raw(fd), rustix::io::FdFlags::empty())?;
+            Ok(())
+        });
+    }
+
+    let child = match command.spawn() {
+        Ok(child) => child,
+        Err(err) => {
+            dh.backend_handle().kill_client(
+                client.id(),
+                wayland_server::backend::DisconnectReason::ConnectionClosed,
+            );
+            return Err(err);
+        }
+    };
+    debug!(pid = child.id(), client = ?client.id(), "Spawned client with WAYLAND_SOCKET");
+
+    Ok((client, child))
+}
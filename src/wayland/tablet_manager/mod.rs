@@ -1,6 +1,12 @@
 //! Utilities for graphics tablet support
 //!
-//! This module provides helpers to handle graphics tablets.
+//! This module provides helpers to handle graphics tablets, their tools and pads.
+//!
+//! Pads are added via [`TabletSeatHandle::add_pad`] from a [`TabletPadDescriptor`](crate::backend::input::TabletPadDescriptor),
+//! which the libinput backend can create for devices with the
+//! [`TabletPad`](crate::backend::input::DeviceCapability::TabletPad) capability.
+//! The returned [`TabletPadHandle`] forwards button, ring, strip and mode switch events
+//! to the focused client and provides the descriptions clients set for the controls of the pad.
 //!
 //! ```
 //! use smithay::{delegate_seat, delegate_tablet_manager};
@@ -99,6 +105,10 @@
 use crate::input::{Seat, SeatHandler};
 use wayland_protocols::wp::tablet::zv2::server::{
     zwp_tablet_manager_v2::{self, ZwpTabletManagerV2},
+    zwp_tablet_pad_group_v2::ZwpTabletPadGroupV2,
+    zwp_tablet_pad_ring_v2::ZwpTabletPadRingV2,
+    zwp_tablet_pad_strip_v2::ZwpTabletPadStripV2,
+    zwp_tablet_pad_v2::ZwpTabletPadV2,
     zwp_tablet_seat_v2::ZwpTabletSeatV2,
     zwp_tablet_tool_v2::ZwpTabletToolV2,
     zwp_tablet_v2::ZwpTabletV2,
@@ -108,10 +118,15 @@ use wayland_server::{backend::GlobalId, Client, DataInit, Dispatch, DisplayHandl
 const MANAGER_VERSION: u32 = 1;
 
 mod tablet;
+mod tablet_pad;
 mod tablet_seat;
 pub(crate) mod tablet_tool;
 
 pub use tablet::{TabletDescriptor, TabletHandle, TabletUserData};
+pub use tablet_pad::{
+    TabletPadFeedback, TabletPadGroupUserData, TabletPadHandle, TabletPadRingUserData,
+    TabletPadStripUserData, TabletPadUserData,
+};
 pub use tablet_seat::{TabletSeatHandle, TabletSeatHandler, TabletSeatUserData};
 pub use tablet_tool::{TabletToolHandle, TabletToolUserData};
 
@@ -145,6 +160,10 @@ impl TabletManagerState {
         D: Dispatch<ZwpTabletManagerV2, ()>,
         D: Dispatch<ZwpTabletSeatV2, TabletSeatUserData>,
         D: Dispatch<ZwpTabletToolV2, TabletToolUserData>,
+        D: Dispatch<ZwpTabletPadV2, TabletPadUserData>,
+        D: Dispatch<ZwpTabletPadGroupV2, TabletPadGroupUserData>,
+        D: Dispatch<ZwpTabletPadRingV2, TabletPadRingUserData>,
+        D: Dispatch<ZwpTabletPadStripV2, TabletPadStripUserData>,
         D: 'static,
     {
         let global = display.create_global::<D, ZwpTabletManagerV2, _>(MANAGER_VERSION, ());
@@ -183,6 +202,10 @@ where
     D: Dispatch<ZwpTabletSeatV2, TabletSeatUserData>,
     D: Dispatch<ZwpTabletV2, TabletUserData>,
     D: Dispatch<ZwpTabletToolV2, TabletToolUserData>,
+    D: Dispatch<ZwpTabletPadV2, TabletPadUserData>,
+    D: Dispatch<ZwpTabletPadGroupV2, TabletPadGroupUserData>,
+    D: Dispatch<ZwpTabletPadRingV2, TabletPadRingUserData>,
+    D: Dispatch<ZwpTabletPadStripV2, TabletPadStripUserData>,
     D: SeatHandler + TabletSeatHandler + 'static,
     D: CompositorHandler,
 {
@@ -240,5 +263,17 @@ macro_rules! delegate_tablet_manager {
         $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
             $crate::reexports::wayland_protocols::wp::tablet::zv2::server::zwp_tablet_v2::ZwpTabletV2: $crate::wayland::tablet_manager::TabletUserData
         ] => $crate::wayland::tablet_manager::TabletManagerState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::wp::tablet::zv2::server::zwp_tablet_pad_v2::ZwpTabletPadV2: $crate::wayland::tablet_manager::TabletPadUserData
+        ] => $crate::wayland::tablet_manager::TabletManagerState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::wp::tablet::zv2::server::zwp_tablet_pad_group_v2::ZwpTabletPadGroupV2: $crate::wayland::tablet_manager::TabletPadGroupUserData
+        ] => $crate::wayland::tablet_manager::TabletManagerState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::wp::tablet::zv2::server::zwp_tablet_pad_ring_v2::ZwpTabletPadRingV2: $crate::wayland::tablet_manager::TabletPadRingUserData
+        ] => $crate::wayland::tablet_manager::TabletManagerState);
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols::wp::tablet::zv2::server::zwp_tablet_pad_strip_v2::ZwpTabletPadStripV2: $crate::wayland::tablet_manager::TabletPadStripUserData
+        ] => $crate::wayland::tablet_manager::TabletManagerState);
     };
 }
@@ -0,0 +1,487 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::backend::input::{ButtonState, TabletPadAxisSource, TabletPadDescriptor};
+use wayland_protocols::wp::tablet::zv2::server::{
+    zwp_tablet_pad_group_v2::{self, ZwpTabletPadGroupV2},
+    zwp_tablet_pad_ring_v2::{self, ZwpTabletPadRingV2},
+    zwp_tablet_pad_strip_v2::{self, ZwpTabletPadStripV2},
+    zwp_tablet_pad_v2::{self, ZwpTabletPadV2},
+    zwp_tablet_seat_v2::ZwpTabletSeatV2,
+};
+use wayland_server::protocol::wl_surface::WlSurface;
+use wayland_server::{backend::ClientId, Client, DataInit, Dispatch, DisplayHandle, Resource, Weak};
+
+use crate::utils::Serial;
+
+use super::tablet::TabletHandle;
+use super::TabletManagerState;
+
+#[derive(Debug)]
+struct TabletPadGroupInstance {
+    rings: Vec<(u32, Weak<ZwpTabletPadRingV2>)>,
+    strips: Vec<(u32, Weak<ZwpTabletPadStripV2>)>,
+    group: Weak<ZwpTabletPadGroupV2>,
+}
+
+#[derive(Debug)]
+struct TabletPadInstance {
+    pad: Weak<ZwpTabletPadV2>,
+    groups: Vec<TabletPadGroupInstance>,
+}
+
+/// Control of a tablet pad a client provided a description for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TabletPadFeedback {
+    /// A button of the pad
+    Button(u32),
+    /// A ring of the pad
+    Ring(u32),
+    /// A strip of the pad
+    Strip(u32),
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct TabletPad {
+    instances: Vec<TabletPadInstance>,
+    focus: Option<WlSurface>,
+    modes: Vec<u32>,
+    feedback: HashMap<TabletPadFeedback, String>,
+}
+
+impl TabletPad {
+    fn focused_instance(&self) -> Option<&TabletPadInstance> {
+        let focus = self.focus.as_ref()?;
+        self.instances
+            .iter()
+            .find(|i| i.pad.id().same_client_as(&focus.id()))
+    }
+
+    fn enter(&mut self, focus: &WlSurface, tablet: &TabletHandle, serial: Serial, time: u32) {
+        if self.focus.as_ref() == Some(focus) {
+            return;
+        }
+        self.leave(serial);
+
+        self.focus = Some(focus.clone());
+        if let Some(instance) = self.focused_instance() {
+            if let Ok(wl_pad) = instance.pad.upgrade() {
+                tablet.with_focused_tablet(focus, |wl_tablet| {
+                    wl_pad.enter(serial.into(), wl_tablet, focus);
+                });
+            }
+            // clients have to assume mode 0 until told otherwise
+            for (group, mode) in instance.groups.iter().zip(self.modes.iter()) {
+                if let Ok(wl_group) = group.group.upgrade() {
+                    wl_group.mode_switch(time, serial.into(), *mode);
+                }
+            }
+        }
+    }
+
+    fn leave(&mut self, serial: Serial) {
+        if let Some(focus) = self.focus.as_ref() {
+            if let Some(wl_pad) = self.focused_instance().and_then(|i| i.pad.upgrade().ok()) {
+                wl_pad.leave(serial.into(), focus);
+            }
+        }
+
+        self.focus = None;
+        self.feedback.clear();
+    }
+
+    fn button(&self, button: u32, state: ButtonState, time: u32) {
+        if let Some(wl_pad) = self.focused_instance().and_then(|i| i.pad.upgrade().ok()) {
+            wl_pad.button(time, button, state.into());
+        }
+    }
+
+    fn ring(&self, ring: u32, position: f64, source: TabletPadAxisSource, time: u32) {
+        let Some(wl_ring) = self.focused_instance().and_then(|i| {
+            i.groups
+                .iter()
+                .flat_map(|g| g.rings.iter())
+                .find(|(number, _)| *number == ring)
+                .and_then(|(_, wl_ring)| wl_ring.upgrade().ok())
+        }) else {
+            return;
+        };
+
+        if source == TabletPadAxisSource::Finger {
+            wl_ring.source(zwp_tablet_pad_ring_v2::Source::Finger);
+        }
+        if position < 0.0 {
+            wl_ring.stop();
+        } else {
+            wl_ring.angle(position);
+        }
+        wl_ring.frame(time);
+    }
+
+    fn strip(&self, strip: u32, position: f64, source: TabletPadAxisSource, time: u32) {
+        let Some(wl_strip) = self.focused_instance().and_then(|i| {
+            i.groups
+                .iter()
+                .flat_map(|g| g.strips.iter())
+                .find(|(number, _)| *number == strip)
+                .and_then(|(_, wl_strip)| wl_strip.upgrade().ok())
+        }) else {
+            return;
+        };
+
+        if source == TabletPadAxisSource::Finger {
+            wl_strip.source(zwp_tablet_pad_strip_v2::Source::Finger);
+        }
+        if position < 0.0 {
+            wl_strip.stop();
+        } else {
+            wl_strip.position((position.clamp(0.0, 1.0) * 65535.0).round() as u32);
+        }
+        wl_strip.frame(time);
+    }
+
+    fn mode_switch(&mut self, group: u32, mode: u32, serial: Serial, time: u32) {
+        let Some(current) = self.modes.get_mut(group as usize) else {
+            return;
+        };
+        if *current == mode {
+            return;
+        }
+        *current = mode;
+
+        if let Some(wl_group) = self
+            .focused_instance()
+            .and_then(|i| i.groups.get(group as usize))
+            .and_then(|g| g.group.upgrade().ok())
+        {
+            wl_group.mode_switch(time, serial.into(), mode);
+        }
+    }
+}
+
+impl Drop for TabletPad {
+    fn drop(&mut self) {
+        for instance in self.instances.iter().filter_map(|i| i.pad.upgrade().ok()) {
+            // This event is sent when the pad is removed from the system and will send no further events.
+            instance.removed();
+        }
+    }
+}
+
+/// Handle to a tablet pad device
+///
+/// TabletPad represents the buttons, rings and strips of a graphics tablet, that are not
+/// related to a tool. Its events are sent to the surface the pad is focused on, which is
+/// usually the surface with keyboard focus.
+///
+/// The controls of a pad are organized in mode groups, allowing clients to bind different
+/// actions to the same control depending on the current mode of its group. Clients can
+/// describe their actions via feedback strings, which the compositor may display, e.g. in
+/// an on-screen display, see [`TabletPadHandle::feedback`].
+#[derive(Debug, Default, Clone)]
+pub struct TabletPadHandle {
+    pub(crate) inner: Arc<Mutex<TabletPad>>,
+}
+
+impl TabletPadHandle {
+    pub(super) fn new_instance<D>(
+        &mut self,
+        client: &Client,
+        dh: &DisplayHandle,
+        seat: &ZwpTabletSeatV2,
+        pad: &TabletPadDescriptor,
+    ) where
+        D: Dispatch<ZwpTabletPadV2, TabletPadUserData>,
+        D: Dispatch<ZwpTabletPadGroupV2, TabletPadGroupUserData>,
+        D: Dispatch<ZwpTabletPadRingV2, TabletPadRingUserData>,
+        D: Dispatch<ZwpTabletPadStripV2, TabletPadStripUserData>,
+        D: 'static,
+    {
+        let wl_pad = client
+            .create_resource::<ZwpTabletPadV2, _, D>(
+                dh,
+                seat.version(),
+                TabletPadUserData { handle: self.clone() },
+            )
+            .unwrap();
+
+        seat.pad_added(&wl_pad);
+
+        let mut groups = Vec::with_capacity(pad.groups.len());
+        for group in pad.groups.iter() {
+            let wl_group = client
+                .create_resource::<ZwpTabletPadGroupV2, _, D>(dh, seat.version(), TabletPadGroupUserData)
+                .unwrap();
+            wl_pad.group(&wl_group);
+
+            wl_group.buttons(group.buttons.iter().flat_map(|b| b.to_ne_bytes()).collect());
+
+            let rings = group
+                .rings
+                .iter()
+                .map(|number| {
+                    let wl_ring = client
+                        .create_resource::<ZwpTabletPadRingV2, _, D>(
+                            dh,
+                            seat.version(),
+                            TabletPadRingUserData {
+                                handle: self.clone(),
+                                number: *number,
+                            },
+                        )
+                        .unwrap();
+                    wl_group.ring(&wl_ring);
+                    (*number, wl_ring.downgrade())
+                })
+                .collect();
+
+            let strips = group
+                .strips
+                .iter()
+                .map(|number| {
+                    let wl_strip = client
+                        .create_resource::<ZwpTabletPadStripV2, _, D>(
+                            dh,
+                            seat.version(),
+                            TabletPadStripUserData {
+                                handle: self.clone(),
+                                number: *number,
+                            },
+                        )
+                        .unwrap();
+                    wl_group.strip(&wl_strip);
+                    (*number, wl_strip.downgrade())
+                })
+                .collect();
+
+            wl_group.modes(group.modes);
+            wl_group.done();
+
+            groups.push(TabletPadGroupInstance {
+                rings,
+                strips,
+                group: wl_group.downgrade(),
+            });
+        }
+
+        if let Some(syspath) = pad.syspath.as_ref().and_then(|p| p.to_str()) {
+            wl_pad.path(syspath.to_owned());
+        }
+        wl_pad.buttons(pad.buttons);
+        wl_pad.done();
+
+        let mut inner = self.inner.lock().unwrap();
+        inner.modes.resize(pad.groups.len(), 0);
+        inner.instances.push(TabletPadInstance {
+            pad: wl_pad.downgrade(),
+            groups,
+        });
+    }
+
+    /// Notify that this pad is focused on a certain surface.
+    ///
+    /// The pad has to be associated with the given tablet, which needs to be known to the client.
+    /// This also sends the current mode of every mode group to the newly focused client.
+    pub fn enter(&self, focus: &WlSurface, tablet: &TabletHandle, serial: Serial, time: u32) {
+        self.inner.lock().unwrap().enter(focus, tablet, serial, time);
+    }
+
+    /// Notify that this pad is no longer focused on any surface.
+    pub fn leave(&self, serial: Serial) {
+        self.inner.lock().unwrap().leave(serial);
+    }
+
+    /// Button on the pad was pressed or released
+    pub fn button(&self, button: u32, state: ButtonState, time: u32) {
+        self.inner.lock().unwrap().button(button, state, time);
+    }
+
+    /// Ring on the pad changed its position
+    ///
+    /// The position is given in degrees, a negative position signals the end of the interaction.
+    pub fn ring(&self, ring: u32, position: f64, source: TabletPadAxisSource, time: u32) {
+        self.inner.lock().unwrap().ring(ring, position, source, time);
+    }
+
+    /// Strip on the pad changed its position
+    ///
+    /// The position is normalized to `[0, 1]`, a negative position signals the end of the interaction.
+    pub fn strip(&self, strip: u32, position: f64, source: TabletPadAxisSource, time: u32) {
+        self.inner.lock().unwrap().strip(strip, position, source, time);
+    }
+
+    /// Update the mode of a mode group
+    ///
+    /// Nothing is sent to the client, if the mode did not change. It is thus safe to call this
+    /// with the mode of every received pad event before forwarding it.
+    pub fn mode_switch(&self, group: u32, mode: u32, serial: Serial, time: u32) {
+        self.inner.lock().unwrap().mode_switch(group, mode, serial, time);
+    }
+
+    /// Returns the current mode of a mode group
+    pub fn mode(&self, group: u32) -> Option<u32> {
+        self.inner.lock().unwrap().modes.get(group as usize).copied()
+    }
+
+    /// Returns the description the focused client provided for a control of this pad
+    ///
+    /// Descriptions are reset whenever the focus of the pad changes.
+    pub fn feedback(&self, control: TabletPadFeedback) -> Option<String> {
+        self.inner.lock().unwrap().feedback.get(&control).cloned()
+    }
+
+    fn set_feedback(&self, client: &impl Resource, control: TabletPadFeedback, description: String) {
+        let mut inner = self.inner.lock().unwrap();
+        // only the focused client may describe the controls of the pad
+        if inner
+            .focus
+            .as_ref()
+            .is_some_and(|focus| focus.id().same_client_as(&client.id()))
+        {
+            inner.feedback.insert(control, description);
+        }
+    }
+}
+
+impl From<ButtonState> for zwp_tablet_pad_v2::ButtonState {
+    #[inline]
+    fn from(from: ButtonState) -> zwp_tablet_pad_v2::ButtonState {
+        match from {
+            ButtonState::Pressed => zwp_tablet_pad_v2::ButtonState::Pressed,
+            ButtonState::Released => zwp_tablet_pad_v2::ButtonState::Released,
+        }
+    }
+}
+
+/// User data of ZwpTabletPadV2 object
+#[derive(Debug)]
+pub struct TabletPadUserData {
+    handle: TabletPadHandle,
+}
+
+/// User data of ZwpTabletPadGroupV2 object
+#[derive(Debug)]
+pub struct TabletPadGroupUserData;
+
+/// User data of ZwpTabletPadRingV2 object
+#[derive(Debug)]
+pub struct TabletPadRingUserData {
+    handle: TabletPadHandle,
+    number: u32,
+}
+
+/// User data of ZwpTabletPadStripV2 object
+#[derive(Debug)]
+pub struct TabletPadStripUserData {
+    handle: TabletPadHandle,
+    number: u32,
+}
+
+impl<D> Dispatch<ZwpTabletPadV2, TabletPadUserData, D> for TabletManagerState
+where
+    D: Dispatch<ZwpTabletPadV2, TabletPadUserData>,
+    D: 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        pad: &ZwpTabletPadV2,
+        request: zwp_tablet_pad_v2::Request,
+        data: &TabletPadUserData,
+        _dh: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            zwp_tablet_pad_v2::Request::SetFeedback {
+                button, description, ..
+            } => {
+                data.handle
+                    .set_feedback(pad, TabletPadFeedback::Button(button), description);
+            }
+            zwp_tablet_pad_v2::Request::Destroy => {
+                // Nothing to do
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(_state: &mut D, _client: ClientId, pad: &ZwpTabletPadV2, data: &TabletPadUserData) {
+        data.handle
+            .inner
+            .lock()
+            .unwrap()
+            .instances
+            .retain(|i| i.pad.id() != pad.id());
+    }
+}
+
+impl<D> Dispatch<ZwpTabletPadGroupV2, TabletPadGroupUserData, D> for TabletManagerState
+where
+    D: Dispatch<ZwpTabletPadGroupV2, TabletPadGroupUserData>,
+    D: 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _group: &ZwpTabletPadGroupV2,
+        _request: zwp_tablet_pad_group_v2::Request,
+        _data: &TabletPadGroupUserData,
+        _dh: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+    }
+}
+
+impl<D> Dispatch<ZwpTabletPadRingV2, TabletPadRingUserData, D> for TabletManagerState
+where
+    D: Dispatch<ZwpTabletPadRingV2, TabletPadRingUserData>,
+    D: 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        ring: &ZwpTabletPadRingV2,
+        request: zwp_tablet_pad_ring_v2::Request,
+        data: &TabletPadRingUserData,
+        _dh: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            zwp_tablet_pad_ring_v2::Request::SetFeedback { description, .. } => {
+                data.handle
+                    .set_feedback(ring, TabletPadFeedback::Ring(data.number), description);
+            }
+            zwp_tablet_pad_ring_v2::Request::Destroy => {
+                // Nothing to do
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<ZwpTabletPadStripV2, TabletPadStripUserData, D> for TabletManagerState
+where
+    D: Dispatch<ZwpTabletPadStripV2, TabletPadStripUserData>,
+    D: 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        strip: &ZwpTabletPadStripV2,
+        request: zwp_tablet_pad_strip_v2::Request,
+        data: &TabletPadStripUserData,
+        _dh: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            zwp_tablet_pad_strip_v2::Request::SetFeedback { description, .. } => {
+                data.handle
+                    .set_feedback(strip, TabletPadFeedback::Strip(data.number), description);
+            }
+            zwp_tablet_pad_strip_v2::Request::Destroy => {
+                // Nothing to do
+            }
+            _ => unreachable!(),
+        }
+    }
+}
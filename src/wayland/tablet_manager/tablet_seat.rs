@@ -1,4 +1,8 @@
 use wayland_protocols::wp::tablet::zv2::server::{
+    zwp_tablet_pad_group_v2::ZwpTabletPadGroupV2,
+    zwp_tablet_pad_ring_v2::ZwpTabletPadRingV2,
+    zwp_tablet_pad_strip_v2::ZwpTabletPadStripV2,
+    zwp_tablet_pad_v2::ZwpTabletPadV2,
     zwp_tablet_seat_v2::{self, ZwpTabletSeatV2},
     zwp_tablet_tool_v2::ZwpTabletToolV2,
     zwp_tablet_v2::ZwpTabletV2,
@@ -6,10 +10,17 @@ use wayland_protocols::wp::tablet::zv2::server::{
 use wayland_server::{backend::ClientId, Client, DataInit, Dispatch, DisplayHandle, Resource, Weak};
 
 use crate::input::pointer::CursorImageStatus;
-use crate::{backend::input::TabletToolDescriptor, wayland::compositor::CompositorHandler};
+use crate::{
+    backend::input::{TabletPadDescriptor, TabletToolDescriptor},
+    wayland::compositor::CompositorHandler,
+};
 
 use super::{
     tablet::TabletUserData,
+    tablet_pad::{
+        TabletPadGroupUserData, TabletPadHandle, TabletPadRingUserData, TabletPadStripUserData,
+        TabletPadUserData,
+    },
     tablet_tool::{TabletToolHandle, TabletToolUserData},
 };
 use super::{
@@ -26,6 +37,7 @@ pub(crate) struct TabletSeat {
     instances: Vec<Weak<ZwpTabletSeatV2>>,
     tablets: HashMap<TabletDescriptor, TabletHandle>,
     tools: HashMap<TabletToolDescriptor, TabletToolHandle>,
+    pads: HashMap<TabletPadDescriptor, TabletPadHandle>,
 }
 
 impl fmt::Debug for TabletSeat {
@@ -34,6 +46,7 @@ impl fmt::Debug for TabletSeat {
             .field("instances", &self.instances)
             .field("tablets", &self.tablets)
             .field("tools", &self.tools)
+            .field("pads", &self.pads)
             .finish()
     }
 }
@@ -51,7 +64,7 @@ pub trait TabletSeatHandler {
 ///
 /// TabletSeat extends `Seat` with graphic tablet specific functionality
 ///
-/// TabletSeatHandle can be used to advertise available graphics tablets and tools to wayland clients
+/// TabletSeatHandle can be used to advertise available graphics tablets, tools and pads to wayland clients
 #[derive(Default, Debug, Clone)]
 pub struct TabletSeatHandle {
     pub(crate) inner: Arc<Mutex<TabletSeat>>,
@@ -67,6 +80,10 @@ impl TabletSeatHandle {
     ) where
         D: Dispatch<ZwpTabletV2, TabletUserData>,
         D: Dispatch<ZwpTabletToolV2, TabletToolUserData>,
+        D: Dispatch<ZwpTabletPadV2, TabletPadUserData>,
+        D: Dispatch<ZwpTabletPadGroupV2, TabletPadGroupUserData>,
+        D: Dispatch<ZwpTabletPadRingV2, TabletPadRingUserData>,
+        D: Dispatch<ZwpTabletPadStripV2, TabletPadStripUserData>,
         D: TabletSeatHandler + 'static,
         D: CompositorHandler,
     {
@@ -82,6 +99,11 @@ impl TabletSeatHandle {
             tool.new_instance(state, client, dh, seat, desc);
         }
 
+        // Notify new instance about available pads
+        for (desc, pad) in inner.pads.iter_mut() {
+            pad.new_instance::<D>(client, dh, seat, desc);
+        }
+
         inner.instances.push(seat.downgrade());
     }
 
@@ -205,6 +227,67 @@ impl TabletSeatHandle {
     pub fn clear_tools(&self) {
         self.inner.lock().unwrap().tools.clear();
     }
+
+    /// Add a new pad to a seat.
+    ///
+    /// Pads are usually added on [input::Event::DeviceAdded](crate::backend::input::InputEvent::DeviceAdded) event
+    /// of devices with the [TabletPad](crate::backend::input::DeviceCapability::TabletPad) capability.
+    ///
+    /// Returns new [TabletPadHandle] if pad was not know by this seat, if pad was already know it returns existing handle,
+    /// it allows you to send pad input events to clients.
+    pub fn add_pad<D>(&self, dh: &DisplayHandle, pad_desc: &TabletPadDescriptor) -> TabletPadHandle
+    where
+        D: Dispatch<ZwpTabletPadV2, TabletPadUserData>,
+        D: Dispatch<ZwpTabletPadGroupV2, TabletPadGroupUserData>,
+        D: Dispatch<ZwpTabletPadRingV2, TabletPadRingUserData>,
+        D: Dispatch<ZwpTabletPadStripV2, TabletPadStripUserData>,
+        D: 'static,
+    {
+        let inner = &mut *self.inner.lock().unwrap();
+
+        let pads = &mut inner.pads;
+        let instances = &inner.instances;
+
+        let pad = pads.entry(pad_desc.clone()).or_insert_with(|| {
+            let mut pad = TabletPadHandle::default();
+            // Create new pad instance for every seat instance
+            for seat in instances.iter() {
+                let Ok(seat) = seat.upgrade() else {
+                    continue;
+                };
+
+                if let Ok(client) = dh.get_client(seat.id()) {
+                    pad.new_instance::<D>(&client, dh, &seat, pad_desc);
+                }
+            }
+            pad
+        });
+
+        pad.clone()
+    }
+
+    /// Get a handle to a tablet pad
+    pub fn get_pad(&self, pad_desc: &TabletPadDescriptor) -> Option<TabletPadHandle> {
+        self.inner.lock().unwrap().pads.get(pad_desc).cloned()
+    }
+
+    /// Count all tablet pad devices
+    pub fn count_pads(&self) -> usize {
+        self.inner.lock().unwrap().pads.len()
+    }
+
+    /// Remove tablet pad device
+    ///
+    /// Called when pad is no longer available
+    /// For example on [input::Event::DeviceRemoved](crate::backend::input::InputEvent::DeviceRemoved) event.
+    pub fn remove_pad(&self, pad_desc: &TabletPadDescriptor) {
+        self.inner.lock().unwrap().pads.remove(pad_desc);
+    }
+
+    /// Remove all tablet pad devices
+    pub fn clear_pads(&self) {
+        self.inner.lock().unwrap().pads.clear();
+    }
 }
 
 /// User data of ZwpTabletSeatV2 object
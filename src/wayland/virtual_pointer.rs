@@ -0,0 +1,607 @@
+//! Utilities for virtual pointer support
+//!
+//! This module implements the `zwlr_virtual_pointer_manager_v1` protocol, which allows
+//! clients like remote-desktop servers or automation tools to emulate a pointer device.
+//!
+//! Instead of sending events to clients directly, virtual pointers act like an additional
+//! [`InputBackend`]: requests of the virtual pointers are converted into [`InputEvent`]s of the
+//! [`VirtualPointerInputBackend`] and passed to [`VirtualPointerHandler::virtual_pointer_event`].
+//! This way they can be handled by the same code processing the events of any other input backend,
+//! which routes them through the usual focus and grab handling of the [`PointerHandle`](crate::input::pointer::PointerHandle).
+//!
+//! The seat and output requested by the client are available through the [`VirtualPointer`]
+//! device of every event. If no seat was requested, the compositor should pick a default seat.
+//!
+//! ```
+//! use smithay::wayland::virtual_pointer::{
+//!     VirtualPointerHandler, VirtualPointerInputBackend, VirtualPointerManagerState,
+//! };
+//! use smithay::backend::input::InputEvent;
+//! use smithay::delegate_virtual_pointer;
+//!
+//! # struct State;
+//! # let mut display = wayland_server::Display::<State>::new().unwrap();
+//!
+//! // Create the manager global, with a filter to avoid untrusted clients emulating a pointer
+//! VirtualPointerManagerState::new::<State, _>(&display.handle(), |_client| true);
+//!
+//! // provide the necessary trait implementations
+//! impl VirtualPointerHandler for State {
+//!     fn virtual_pointer_event(&mut self, event: InputEvent<VirtualPointerInputBackend>) {
+//!         // process the event like the events of any other input backend
+//!     }
+//! }
+//!
+//! delegate_virtual_pointer!(State);
+//! ```
+
+use std::{path::PathBuf, sync::Mutex};
+
+use wayland_protocols_wlr::virtual_pointer::v1::server::{
+    zwlr_virtual_pointer_manager_v1::{self, ZwlrVirtualPointerManagerV1},
+    zwlr_virtual_pointer_v1::{self, ZwlrVirtualPointerV1},
+};
+use wayland_server::{
+    backend::{ClientId, GlobalId},
+    protocol::{wl_output::WlOutput, wl_pointer, wl_seat::WlSeat},
+    Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource, WEnum,
+};
+
+use crate::{
+    backend::input::{
+        AbsolutePositionEvent, Axis, AxisRelativeDirection, AxisSource, ButtonState, Device,
+        DeviceCapability, Event, InputBackend, InputEvent, PointerAxisEvent, PointerButtonEvent,
+        PointerMotionAbsoluteEvent, PointerMotionEvent, UnusedEvent,
+    },
+    input::{Seat, SeatHandler},
+    output::Output,
+};
+
+const MANAGER_VERSION: u32 = 2;
+
+/// Handler for virtual pointer events
+pub trait VirtualPointerHandler {
+    /// A virtual pointer was created, destroyed or emitted an event
+    ///
+    /// Pointer events should be processed like the events of any other input backend.
+    fn virtual_pointer_event(&mut self, event: InputEvent<VirtualPointerInputBackend>);
+}
+
+/// State of the wlr virtual pointer protocol
+#[derive(Debug)]
+pub struct VirtualPointerManagerState {
+    global: GlobalId,
+}
+
+/// Data associated with a virtual pointer manager global.
+#[allow(missing_debug_implementations)]
+pub struct VirtualPointerManagerGlobalData {
+    filter: Box<dyn for<'c> Fn(&'c Client) -> bool + Send + Sync>,
+}
+
+impl VirtualPointerManagerState {
+    /// Initialize a virtual pointer manager global.
+    ///
+    /// Only clients passing the `filter` can see and use the global.
+    pub fn new<D, F>(display: &DisplayHandle, filter: F) -> Self
+    where
+        D: GlobalDispatch<ZwlrVirtualPointerManagerV1, VirtualPointerManagerGlobalData>,
+        D: Dispatch<ZwlrVirtualPointerManagerV1, ()>,
+        D: Dispatch<ZwlrVirtualPointerV1, VirtualPointerUserData>,
+        D: VirtualPointerHandler,
+        D: 'static,
+        F: for<'c> Fn(&'c Client) -> bool + Send + Sync + 'static,
+    {
+        let data = VirtualPointerManagerGlobalData {
+            filter: Box::new(filter),
+        };
+        let global = display.create_global::<D, ZwlrVirtualPointerManagerV1, _>(MANAGER_VERSION, data);
+
+        Self { global }
+    }
+
+    /// Get the id of ZwlrVirtualPointerManagerV1 global
+    pub fn global(&self) -> GlobalId {
+        self.global.clone()
+    }
+}
+
+/// A virtual pointer device created by a client
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VirtualPointer {
+    pointer: ZwlrVirtualPointerV1,
+}
+
+impl VirtualPointer {
+    /// Returns the seat requested by the client, if any
+    pub fn seat<D: SeatHandler + 'static>(&self) -> Option<Seat<D>> {
+        self.data().seat.as_ref().and_then(Seat::from_resource)
+    }
+
+    /// Returns the output absolute motion events are mapped to, if requested by the client
+    pub fn output(&self) -> Option<Output> {
+        self.data().output.as_ref().and_then(Output::from_resource)
+    }
+
+    /// Returns the underlying `zwlr_virtual_pointer_v1` resource
+    pub fn resource(&self) -> &ZwlrVirtualPointerV1 {
+        &self.pointer
+    }
+
+    fn data(&self) -> &VirtualPointerUserData {
+        self.pointer.data::<VirtualPointerUserData>().unwrap()
+    }
+}
+
+impl Device for VirtualPointer {
+    fn id(&self) -> String {
+        self.pointer.id().to_string()
+    }
+
+    fn name(&self) -> String {
+        String::from("virtual pointer")
+    }
+
+    fn has_capability(&self, capability: DeviceCapability) -> bool {
+        capability == DeviceCapability::Pointer
+    }
+
+    fn usb_id(&self) -> Option<(u32, u32)> {
+        None
+    }
+
+    fn syspath(&self) -> Option<PathBuf> {
+        None
+    }
+}
+
+/// Marker type for the events emitted by virtual pointers
+#[derive(Debug)]
+pub struct VirtualPointerInputBackend;
+
+impl InputBackend for VirtualPointerInputBackend {
+    type Device = VirtualPointer;
+    type KeyboardKeyEvent = UnusedEvent;
+    type PointerAxisEvent = VirtualPointerAxisEvent;
+    type PointerButtonEvent = VirtualPointerButtonEvent;
+    type PointerMotionEvent = VirtualPointerMotionEvent;
+    type PointerMotionAbsoluteEvent = VirtualPointerMotionAbsoluteEvent;
+
+    type GestureSwipeBeginEvent = UnusedEvent;
+    type GestureSwipeUpdateEvent = UnusedEvent;
+    type GestureSwipeEndEvent = UnusedEvent;
+    type GesturePinchBeginEvent = UnusedEvent;
+    type GesturePinchUpdateEvent = UnusedEvent;
+    type GesturePinchEndEvent = UnusedEvent;
+    type GestureHoldBeginEvent = UnusedEvent;
+    type GestureHoldEndEvent = UnusedEvent;
+
+    type TouchDownEvent = UnusedEvent;
+    type TouchUpEvent = UnusedEvent;
+    type TouchMotionEvent = UnusedEvent;
+    type TouchCancelEvent = UnusedEvent;
+    type TouchFrameEvent = UnusedEvent;
+    type TabletToolAxisEvent = UnusedEvent;
+    type TabletToolProximityEvent = UnusedEvent;
+    type TabletToolTipEvent = UnusedEvent;
+    type TabletToolButtonEvent = UnusedEvent;
+
+    type TabletPadButtonEvent = UnusedEvent;
+    type TabletPadRingEvent = UnusedEvent;
+    type TabletPadStripEvent = UnusedEvent;
+    type SwitchToggleEvent = UnusedEvent;
+
+    type SpecialEvent = UnusedEvent;
+}
+
+/// Relative motion of a virtual pointer
+#[derive(Debug, Clone)]
+pub struct VirtualPointerMotionEvent {
+    pointer: VirtualPointer,
+    time: u32,
+    dx: f64,
+    dy: f64,
+}
+
+impl Event<VirtualPointerInputBackend> for VirtualPointerMotionEvent {
+    fn time(&self) -> u64 {
+        self.time as u64 * 1000
+    }
+
+    fn device(&self) -> VirtualPointer {
+        self.pointer.clone()
+    }
+}
+
+impl PointerMotionEvent<VirtualPointerInputBackend> for VirtualPointerMotionEvent {
+    fn delta_x(&self) -> f64 {
+        self.dx
+    }
+
+    fn delta_y(&self) -> f64 {
+        self.dy
+    }
+
+    fn delta_x_unaccel(&self) -> f64 {
+        self.dx
+    }
+
+    fn delta_y_unaccel(&self) -> f64 {
+        self.dy
+    }
+}
+
+/// Absolute motion of a virtual pointer
+///
+/// The raw position is given relative to the extent chosen by the client.
+/// If the client requested an output, the position should be mapped to
+/// that output, see [`VirtualPointer::output`].
+#[derive(Debug, Clone)]
+pub struct VirtualPointerMotionAbsoluteEvent {
+    pointer: VirtualPointer,
+    time: u32,
+    x: u32,
+    y: u32,
+    x_extent: u32,
+    y_extent: u32,
+}
+
+impl Event<VirtualPointerInputBackend> for VirtualPointerMotionAbsoluteEvent {
+    fn time(&self) -> u64 {
+        self.time as u64 * 1000
+    }
+
+    fn device(&self) -> VirtualPointer {
+        self.pointer.clone()
+    }
+}
+
+impl AbsolutePositionEvent<VirtualPointerInputBackend> for VirtualPointerMotionAbsoluteEvent {
+    fn x(&self) -> f64 {
+        self.x as f64
+    }
+
+    fn y(&self) -> f64 {
+        self.y as f64
+    }
+
+    fn x_transformed(&self, width: i32) -> f64 {
+        self.x as f64 * width as f64 / self.x_extent as f64
+    }
+
+    fn y_transformed(&self, height: i32) -> f64 {
+        self.y as f64 * height as f64 / self.y_extent as f64
+    }
+}
+
+impl PointerMotionAbsoluteEvent<VirtualPointerInputBackend> for VirtualPointerMotionAbsoluteEvent {}
+
+/// Button press or release of a virtual pointer
+#[derive(Debug, Clone)]
+pub struct VirtualPointerButtonEvent {
+    pointer: VirtualPointer,
+    time: u32,
+    button: u32,
+    state: ButtonState,
+}
+
+impl Event<VirtualPointerInputBackend> for VirtualPointerButtonEvent {
+    fn time(&self) -> u64 {
+        self.time as u64 * 1000
+    }
+
+    fn device(&self) -> VirtualPointer {
+        self.pointer.clone()
+    }
+}
+
+impl PointerButtonEvent<VirtualPointerInputBackend> for VirtualPointerButtonEvent {
+    fn button_code(&self) -> u32 {
+        self.button
+    }
+
+    fn state(&self) -> ButtonState {
+        self.state
+    }
+}
+
+/// Scroll event of a virtual pointer
+///
+/// Contains all axis requests of the client sent between two frames.
+/// A stopped axis is reported with an amount of `0.0`.
+#[derive(Debug, Clone)]
+pub struct VirtualPointerAxisEvent {
+    pointer: VirtualPointer,
+    axis: PendingAxis,
+}
+
+impl Event<VirtualPointerInputBackend> for VirtualPointerAxisEvent {
+    fn time(&self) -> u64 {
+        self.axis.time as u64 * 1000
+    }
+
+    fn device(&self) -> VirtualPointer {
+        self.pointer.clone()
+    }
+}
+
+impl PointerAxisEvent<VirtualPointerInputBackend> for VirtualPointerAxisEvent {
+    fn amount(&self, axis: Axis) -> Option<f64> {
+        self.axis.amount[axis_index(axis)]
+    }
+
+    fn amount_v120(&self, axis: Axis) -> Option<f64> {
+        self.axis.v120[axis_index(axis)]
+    }
+
+    fn source(&self) -> AxisSource {
+        self.axis.source.unwrap_or(AxisSource::Wheel)
+    }
+
+    fn relative_direction(&self, _axis: Axis) -> AxisRelativeDirection {
+        AxisRelativeDirection::Identical
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+struct PendingAxis {
+    time: u32,
+    source: Option<AxisSource>,
+    amount: [Option<f64>; 2],
+    v120: [Option<f64>; 2],
+}
+
+impl PendingAxis {
+    fn is_empty(&self) -> bool {
+        self.amount.iter().chain(self.v120.iter()).all(Option::is_none)
+    }
+}
+
+fn axis_index(axis: Axis) -> usize {
+    match axis {
+        Axis::Vertical => 0,
+        Axis::Horizontal => 1,
+    }
+}
+
+/// User data of ZwlrVirtualPointerV1 object
+#[derive(Debug)]
+pub struct VirtualPointerUserData {
+    seat: Option<WlSeat>,
+    output: Option<WlOutput>,
+    pending_axis: Mutex<PendingAxis>,
+}
+
+impl<D> GlobalDispatch<ZwlrVirtualPointerManagerV1, VirtualPointerManagerGlobalData, D>
+    for VirtualPointerManagerState
+where
+    D: GlobalDispatch<ZwlrVirtualPointerManagerV1, VirtualPointerManagerGlobalData>,
+    D: Dispatch<ZwlrVirtualPointerManagerV1, ()>,
+    D: Dispatch<ZwlrVirtualPointerV1, VirtualPointerUserData>,
+    D: VirtualPointerHandler,
+    D: 'static,
+{
+    fn bind(
+        _: &mut D,
+        _: &DisplayHandle,
+        _: &Client,
+        resource: New<ZwlrVirtualPointerManagerV1>,
+        _: &VirtualPointerManagerGlobalData,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        data_init.init(resource, ());
+    }
+
+    fn can_view(client: Client, global_data: &VirtualPointerManagerGlobalData) -> bool {
+        (global_data.filter)(&client)
+    }
+}
+
+impl<D> Dispatch<ZwlrVirtualPointerManagerV1, (), D> for VirtualPointerManagerState
+where
+    D: Dispatch<ZwlrVirtualPointerManagerV1, ()>,
+    D: Dispatch<ZwlrVirtualPointerV1, VirtualPointerUserData>,
+    D: VirtualPointerHandler,
+    D: 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        _resource: &ZwlrVirtualPointerManagerV1,
+        request: zwlr_virtual_pointer_manager_v1::Request,
+        _data: &(),
+        _handle: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        let (seat, output, id) = match request {
+            zwlr_virtual_pointer_manager_v1::Request::CreateVirtualPointer { seat, id } => (seat, None, id),
+            zwlr_virtual_pointer_manager_v1::Request::CreateVirtualPointerWithOutput { seat, output, id } => {
+                (seat, output, id)
+            }
+            zwlr_virtual_pointer_manager_v1::Request::Destroy => return,
+            _ => unreachable!(),
+        };
+
+        let pointer = data_init.init(
+            id,
+            VirtualPointerUserData {
+                seat,
+                output,
+                pending_axis: Mutex::new(PendingAxis::default()),
+            },
+        );
+        state.virtual_pointer_event(InputEvent::DeviceAdded {
+            device: VirtualPointer { pointer },
+        });
+    }
+}
+
+impl<D> Dispatch<ZwlrVirtualPointerV1, VirtualPointerUserData, D> for VirtualPointerManagerState
+where
+    D: Dispatch<ZwlrVirtualPointerV1, VirtualPointerUserData>,
+    D: VirtualPointerHandler,
+    D: 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        resource: &ZwlrVirtualPointerV1,
+        request: zwlr_virtual_pointer_v1::Request,
+        data: &VirtualPointerUserData,
+        _dh: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        let pointer = VirtualPointer {
+            pointer: resource.clone(),
+        };
+
+        match request {
+            zwlr_virtual_pointer_v1::Request::Motion { time, dx, dy } => {
+                let event = VirtualPointerMotionEvent {
+                    pointer,
+                    time,
+                    dx,
+                    dy,
+                };
+                state.virtual_pointer_event(InputEvent::PointerMotion { event });
+            }
+            zwlr_virtual_pointer_v1::Request::MotionAbsolute {
+                time,
+                x,
+                y,
+                x_extent,
+                y_extent,
+            } => {
+                // the position is undefined without an extent
+                if x_extent == 0 || y_extent == 0 {
+                    return;
+                }
+                let event = VirtualPointerMotionAbsoluteEvent {
+                    pointer,
+                    time,
+                    x,
+                    y,
+                    x_extent,
+                    y_extent,
+                };
+                state.virtual_pointer_event(InputEvent::PointerMotionAbsolute { event });
+            }
+            zwlr_virtual_pointer_v1::Request::Button {
+                time,
+                button,
+                state: button_state,
+            } => {
+                let button_state = match button_state {
+                    WEnum::Value(wl_pointer::ButtonState::Pressed) => ButtonState::Pressed,
+                    _ => ButtonState::Released,
+                };
+                let event = VirtualPointerButtonEvent {
+                    pointer,
+                    time,
+                    button,
+                    state: button_state,
+                };
+                state.virtual_pointer_event(InputEvent::PointerButton { event });
+            }
+            zwlr_virtual_pointer_v1::Request::Axis { time, axis, value } => {
+                let Some(axis) = convert_axis(resource, axis) else {
+                    return;
+                };
+                let mut pending = data.pending_axis.lock().unwrap();
+                pending.time = time;
+                *pending.amount[axis_index(axis)].get_or_insert(0.0) += value;
+            }
+            zwlr_virtual_pointer_v1::Request::AxisDiscrete {
+                time,
+                axis,
+                value,
+                discrete,
+            } => {
+                let Some(axis) = convert_axis(resource, axis) else {
+                    return;
+                };
+                let mut pending = data.pending_axis.lock().unwrap();
+                pending.time = time;
+                *pending.amount[axis_index(axis)].get_or_insert(0.0) += value;
+                *pending.v120[axis_index(axis)].get_or_insert(0.0) += discrete as f64 * 120.0;
+            }
+            zwlr_virtual_pointer_v1::Request::AxisStop { time, axis } => {
+                let Some(axis) = convert_axis(resource, axis) else {
+                    return;
+                };
+                let mut pending = data.pending_axis.lock().unwrap();
+                pending.time = time;
+                pending.amount[axis_index(axis)] = Some(0.0);
+            }
+            zwlr_virtual_pointer_v1::Request::AxisSource { axis_source } => {
+                let source = match axis_source {
+                    WEnum::Value(wl_pointer::AxisSource::Wheel) => AxisSource::Wheel,
+                    WEnum::Value(wl_pointer::AxisSource::Finger) => AxisSource::Finger,
+                    WEnum::Value(wl_pointer::AxisSource::Continuous) => AxisSource::Continuous,
+                    WEnum::Value(wl_pointer::AxisSource::WheelTilt) => AxisSource::WheelTilt,
+                    _ => {
+                        resource.post_error(
+                            zwlr_virtual_pointer_v1::Error::InvalidAxisSource,
+                            "Invalid axis source",
+                        );
+                        return;
+                    }
+                };
+                data.pending_axis.lock().unwrap().source = Some(source);
+            }
+            zwlr_virtual_pointer_v1::Request::Frame => {
+                let axis = std::mem::take(&mut *data.pending_axis.lock().unwrap());
+                if !axis.is_empty() {
+                    let event = VirtualPointerAxisEvent { pointer, axis };
+                    state.virtual_pointer_event(InputEvent::PointerAxis { event });
+                }
+            }
+            zwlr_virtual_pointer_v1::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(
+        state: &mut D,
+        _client: ClientId,
+        resource: &ZwlrVirtualPointerV1,
+        _data: &VirtualPointerUserData,
+    ) {
+        state.virtual_pointer_event(InputEvent::DeviceRemoved {
+            device: VirtualPointer {
+                pointer: resource.clone(),
+            },
+        });
+    }
+}
+
+fn convert_axis(resource: &ZwlrVirtualPointerV1, axis: WEnum<wl_pointer::Axis>) -> Option<Axis> {
+    match axis {
+        WEnum::Value(wl_pointer::Axis::VerticalScroll) => Some(Axis::Vertical),
+        WEnum::Value(wl_pointer::Axis::HorizontalScroll) => Some(Axis::Horizontal),
+        _ => {
+            resource.post_error(zwlr_virtual_pointer_v1::Error::InvalidAxis, "Invalid axis");
+            None
+        }
+    }
+}
+
+/// Macro to delegate implementation of the wlr virtual pointer protocol to [`VirtualPointerManagerState`].
+///
+/// You must also implement [`VirtualPointerHandler`] to use this.
+#[macro_export]
+macro_rules! delegate_virtual_pointer {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        $crate::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols_wlr::virtual_pointer::v1::server::zwlr_virtual_pointer_manager_v1::ZwlrVirtualPointerManagerV1: $crate::wayland::virtual_pointer::VirtualPointerManagerGlobalData
+        ] => $crate::wayland::virtual_pointer::VirtualPointerManagerState);
+
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols_wlr::virtual_pointer::v1::server::zwlr_virtual_pointer_manager_v1::ZwlrVirtualPointerManagerV1: ()
+        ] => $crate::wayland::virtual_pointer::VirtualPointerManagerState);
+
+        $crate::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            $crate::reexports::wayland_protocols_wlr::virtual_pointer::v1::server::zwlr_virtual_pointer_v1::ZwlrVirtualPointerV1: $crate::wayland::virtual_pointer::VirtualPointerUserData
+        ] => $crate::wayland::virtual_pointer::VirtualPointerManagerState);
+    };
+}
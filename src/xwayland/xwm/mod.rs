@@ -92,6 +92,7 @@
 //! ```
 
 use crate::{
+    output::Output,
     utils::{x11rb::X11Source, Client, Coordinate, Logical, Point, Rectangle, Size},
     wayland::{
         selection::SelectionTarget,
@@ -119,9 +120,10 @@ use wayland_server::Resource;
 pub use x11rb::protocol::xproto::Window as X11Window;
 use x11rb::{
     connection::Connection as _,
-    errors::ReplyOrIdError,
+    errors::{ReplyError, ReplyOrIdError},
     protocol::{
         composite::{ConnectionExt as _, Redirect},
+        randr::ConnectionExt as _,
         render::{ConnectionExt as _, CreatePictureAux, PictureWrapper},
         xfixes::{ConnectionExt as _, SelectionEventMask},
         xproto::{
@@ -185,6 +187,7 @@ mod atoms {
             _NET_WM_WINDOW_TYPE_TOOLTIP,
             _NET_WM_WINDOW_TYPE_UTILITY,
             _NET_WM_STATE_MODAL,
+            _NET_REQUEST_FRAME_EXTENTS,
             _MOTIF_WM_HINTS,
             _NET_STARTUP_ID,
 
@@ -203,8 +206,11 @@ mod atoms {
             _NET_WM_STATE_HIDDEN,
             _NET_WM_STATE_FULLSCREEN,
             _NET_WM_STATE_FOCUSED,
+            _NET_WM_STATE_DEMANDS_ATTENTION,
+            _NET_FRAME_EXTENTS,
             _NET_SUPPORTING_WM_CHECK,
             _XSETTINGS_SETTINGS,
+            RESOURCE_MANAGER,
 
             // selection
             _WL_SELECTION,
@@ -346,6 +352,18 @@ pub trait XwmHandler {
     fn unfullscreen_request(&mut self, xwm: XwmId, window: X11Surface) {
         let _ = (xwm, window);
     }
+    /// Window requests to be activated through `_NET_ACTIVE_WINDOW`.
+    ///
+    /// Like activation requests of wayland clients through xdg-activation, the compositor
+    /// may either focus the window or mark it as demanding attention instead,
+    /// e.g. using [`X11Surface::set_demands_attention`].
+    fn activate_request(&mut self, xwm: XwmId, window: X11Surface) {
+        let _ = (xwm, window);
+    }
+    /// The urgency of a window changed, see [`X11Surface::is_urgent`].
+    fn urgency_changed(&mut self, xwm: XwmId, window: X11Surface) {
+        let _ = (xwm, window);
+    }
     /// Window requests to be minimized.
     fn minimize_request(&mut self, xwm: XwmId, window: X11Surface) {
         let _ = (xwm, window);
@@ -401,6 +419,8 @@ pub struct X11Wm {
     wm_window: X11Window,
     atoms: Atoms,
     xsettings: XSettings,
+    // `None` until the existing resource database was read
+    xresources: Option<HashMap<String, String>>,
 
     pub(crate) unpaired_surfaces: HashMap<u64, X11Window>,
     sequences_to_ignore: BinaryHeap<Reverse<u16>>,
@@ -787,7 +807,10 @@ impl X11Wm {
                 atoms._NET_WM_STATE_FULLSCREEN,
                 atoms._NET_WM_STATE_MODAL,
                 atoms._NET_WM_STATE_FOCUSED,
+                atoms._NET_WM_STATE_DEMANDS_ATTENTION,
                 atoms._NET_ACTIVE_WINDOW,
+                atoms._NET_FRAME_EXTENTS,
+                atoms._NET_REQUEST_FRAME_EXTENTS,
                 atoms._NET_WM_MOVERESIZE,
                 atoms._NET_CLIENT_LIST,
                 atoms._NET_CLIENT_LIST_STACKING,
@@ -868,6 +891,7 @@ impl X11Wm {
             screen,
             atoms,
             xsettings,
+            xresources: None,
             wm_window: win,
             _xfixes_data,
             clipboard,
@@ -899,6 +923,12 @@ impl X11Wm {
         self.id
     }
 
+    /// Client scale of the Xwayland client, as set on its
+    /// [`CompositorClientState`](crate::wayland::compositor::CompositorClientState::set_client_scale)
+    pub fn client_scale(&self) -> u32 {
+        self.client_scale.load(Ordering::Acquire)
+    }
+
     /// Raises a window in the internal X11 state
     ///
     /// Needs to be called to match raising of windows inside the compositor to keep the stacking order
@@ -1286,6 +1316,142 @@ impl X11Wm {
 
         Ok(())
     }
+
+    /// Updates the X resource database (`RESOURCE_MANAGER` property of the root window)
+    /// with the newly provided name/value-pairs, like `Xft.dpi`.
+    ///
+    /// The first call reads the existing resource database, e.g. loaded with `xrdb` by the session,
+    /// and merges the given resources into it. Later calls only update the resources known from then on,
+    /// so changes to the property made by other clients in the meantime are overwritten.
+    ///
+    /// Applications usually only read the resource database on startup.
+    pub fn set_xresources(
+        &mut self,
+        resources: impl Iterator<Item = (String, String)>,
+    ) -> Result<(), ConnectionError> {
+        if self.xresources.is_none() {
+            let existing = self
+                .conn
+                .get_property(
+                    false,
+                    self.screen.root,
+                    self.atoms.RESOURCE_MANAGER,
+                    AtomEnum::STRING,
+                    0,
+                    0x1fffffff,
+                )?
+                .reply_unchecked()?;
+            self.xresources = Some(
+                existing
+                    .map(|reply| parse_xresources(&reply.value))
+                    .unwrap_or_default(),
+            );
+        }
+        let xresources = self.xresources.as_mut().unwrap();
+        xresources.extend(resources);
+
+        let mut resources = xresources.iter().collect::<Vec<_>>();
+        resources.sort();
+        let data = resources
+            .into_iter()
+            .map(|(name, value)| format!("{}:\t{}\n", name, value))
+            .collect::<String>();
+        self.conn.change_property8(
+            PropMode::REPLACE,
+            self.screen.root,
+            self.atoms.RESOURCE_MANAGER,
+            AtomEnum::STRING,
+            data.as_bytes(),
+        )?;
+        self.conn.flush()?;
+
+        Ok(())
+    }
+
+    /// Updates XSETTINGS and the X resource database to match the given scale and cursor theme.
+    ///
+    /// This sets the dpi used by Xft (`Xft/DPI`, `Xft.dpi`) and the cursor theme and size
+    /// (`Gtk/CursorThemeName`, `Gtk/CursorThemeSize`, `Xcursor.theme`, `Xcursor.size`),
+    /// so X11 applications follow HiDPI and theme settings of the compositor.
+    /// `scale` should match the scale of Xwayland clients, usually the client scale set on
+    /// [`CompositorClientState`](crate::wayland::compositor::CompositorClientState::set_client_scale).
+    pub fn set_display_settings(
+        &mut self,
+        scale: f64,
+        cursor_theme: &str,
+        cursor_size: u32,
+    ) -> Result<(), SettingsError> {
+        let dpi = 96.0 * scale;
+        let cursor_size = (cursor_size as f64 * scale).round() as i32;
+        self.set_xsettings(
+            [
+                (
+                    "Xft/DPI".to_string(),
+                    Value::Integer((dpi * 1024.0).round() as i32),
+                ),
+                (
+                    "Gtk/CursorThemeName".to_string(),
+                    Value::String(cursor_theme.to_string()),
+                ),
+                ("Gtk/CursorThemeSize".to_string(), Value::Integer(cursor_size)),
+            ]
+            .into_iter(),
+        )?;
+        self.set_xresources(
+            [
+                ("Xft.dpi".to_string(), (dpi.round() as i32).to_string()),
+                ("Xcursor.theme".to_string(), cursor_theme.to_string()),
+                ("Xcursor.size".to_string(), cursor_size.to_string()),
+            ]
+            .into_iter(),
+        )?;
+        Ok(())
+    }
+
+    /// Marks the RandR output of Xwayland matching the given output as primary.
+    ///
+    /// Xwayland reflects the compositor's outputs through RandR, but has no notion of a primary output.
+    /// X11 applications, in particular games, use the primary output for fullscreen windows and
+    /// to pick the resolution to switch to. Passing `None` clears the primary output.
+    /// If Xwayland has no RandR output matching the given output, e.g. because it did not pick up
+    /// a newly added output yet, the primary output is left untouched and a warning is logged.
+    ///
+    /// Resolution changes requested by fullscreen X11 clients are emulated by Xwayland through
+    /// `wp_viewporter`, as long as the [viewporter](crate::wayland::viewporter) global is available,
+    /// so they result in scaling instead of real modesets.
+    pub fn set_primary_output(&mut self, output: Option<&Output>) -> Result<(), ReplyError> {
+        let mut primary = x11rb::NONE;
+        if let Some(output) = output {
+            let resources = self
+                .conn
+                .randr_get_screen_resources_current(self.screen.root)?
+                .reply()?;
+            for randr_output in resources.outputs {
+                let info = self
+                    .conn
+                    .randr_get_output_info(randr_output, resources.config_timestamp)?
+                    .reply()?;
+                // Xwayland names its outputs like the wayland outputs
+                if info.name == output.name().as_bytes() {
+                    primary = randr_output;
+                    break;
+                }
+            }
+
+            if primary == x11rb::NONE {
+                warn!(
+                    output = output.name(),
+                    "No RandR output matches the output, keeping the current primary output"
+                );
+                return Ok(());
+            }
+        }
+
+        self.conn.randr_set_output_primary(self.screen.root, primary)?;
+        self.conn.flush()?;
+
+        Ok(())
+    }
 }
 
 fn handle_event<D>(
@@ -2119,9 +2285,13 @@ where
             }
 
             if let Some(surface) = xwm.windows.iter().find(|x| x.window_id() == n.window).cloned() {
+                let was_urgent = surface.is_urgent();
                 if let Some(property) = surface.update_property(n.atom)? {
                     drop(_guard);
-                    state.property_notify(xwm_id, surface, property);
+                    state.property_notify(xwm_id, surface.clone(), property);
+                    if surface.is_urgent() != was_urgent {
+                        state.urgency_changed(xwm_id, surface);
+                    }
                 }
             }
         }
@@ -2248,53 +2418,93 @@ where
                             .map(|reply| String::from_utf8(reply.name)),
                     );
                     if let Some(surface) = xwm.windows.iter().find(|x| x.window_id() == msg.window).cloned() {
+                        // both atoms are handled independently, e.g. fullscreen and demands attention
+                        // may be changed by the same message
+                        let actions = &data[1..=2];
+                        let changes_maximized = (actions[0] == xwm.atoms._NET_WM_STATE_MAXIMIZED_HORZ
+                            && actions[1] == xwm.atoms._NET_WM_STATE_MAXIMIZED_VERT)
+                            || (actions[0] == xwm.atoms._NET_WM_STATE_MAXIMIZED_VERT
+                                && actions[1] == xwm.atoms._NET_WM_STATE_MAXIMIZED_HORZ);
+                        let changes_demands_attention =
+                            actions.contains(&xwm.atoms._NET_WM_STATE_DEMANDS_ATTENTION);
+                        let changes_fullscreen = actions.contains(&xwm.atoms._NET_WM_STATE_FULLSCREEN);
                         drop(_guard);
-                        match &data[1..=2] {
-                            &[x, y]
-                                if (x == xwm.atoms._NET_WM_STATE_MAXIMIZED_HORZ
-                                    && y == xwm.atoms._NET_WM_STATE_MAXIMIZED_VERT)
-                                    || (x == xwm.atoms._NET_WM_STATE_MAXIMIZED_VERT
-                                        && y == xwm.atoms._NET_WM_STATE_MAXIMIZED_HORZ) =>
-                            {
-                                match data[0] {
-                                    0 => {
-                                        if surface.is_maximized() {
-                                            state.unmaximize_request(xwm_id, surface)
-                                        }
+
+                        if changes_maximized {
+                            match data[0] {
+                                0 => {
+                                    if surface.is_maximized() {
+                                        state.unmaximize_request(xwm_id, surface.clone())
                                     }
-                                    1 => {
-                                        if !surface.is_maximized() {
-                                            state.maximize_request(xwm_id, surface)
-                                        }
+                                }
+                                1 => {
+                                    if !surface.is_maximized() {
+                                        state.maximize_request(xwm_id, surface.clone())
                                     }
-                                    2 => {
-                                        if surface.is_maximized() {
-                                            state.unmaximize_request(xwm_id, surface)
-                                        } else {
-                                            state.maximize_request(xwm_id, surface)
-                                        }
+                                }
+                                2 => {
+                                    if surface.is_maximized() {
+                                        state.unmaximize_request(xwm_id, surface.clone())
+                                    } else {
+                                        state.maximize_request(xwm_id, surface.clone())
                                     }
-                                    _ => {}
                                 }
+                                _ => {}
                             }
-                            actions if actions.contains(&xwm.atoms._NET_WM_STATE_FULLSCREEN) => {
-                                match data[0] {
-                                    0 => state.unfullscreen_request(xwm_id, surface),
-                                    1 => state.fullscreen_request(xwm_id, surface),
-                                    2 => {
-                                        if surface.is_fullscreen() {
-                                            state.unfullscreen_request(xwm_id, surface)
-                                        } else {
-                                            state.fullscreen_request(xwm_id, surface)
-                                        }
+                        }
+                        if changes_demands_attention {
+                            let was_urgent = surface.is_urgent();
+                            let demands_attention = match data[0] {
+                                0 => Some(false),
+                                1 => Some(true),
+                                2 => Some(!surface.demands_attention()),
+                                _ => None,
+                            };
+                            if let Some(demands_attention) = demands_attention {
+                                surface.set_demands_attention(demands_attention)?;
+                                if surface.is_urgent() != was_urgent {
+                                    state.urgency_changed(xwm_id, surface.clone());
+                                }
+                            }
+                        }
+                        if changes_fullscreen {
+                            match data[0] {
+                                0 => state.unfullscreen_request(xwm_id, surface),
+                                1 => state.fullscreen_request(xwm_id, surface),
+                                2 => {
+                                    if surface.is_fullscreen() {
+                                        state.unfullscreen_request(xwm_id, surface)
+                                    } else {
+                                        state.fullscreen_request(xwm_id, surface)
                                     }
-                                    _ => {}
                                 }
+                                _ => {}
                             }
-                            _ => {}
                         }
                     }
                 }
+                x if x == xwm.atoms._NET_ACTIVE_WINDOW => {
+                    if let Some(surface) = xwm.windows.iter().find(|x| x.window_id() == msg.window).cloned() {
+                        drop(_guard);
+                        state.activate_request(xwm_id, surface);
+                    }
+                }
+                x if x == xwm.atoms._NET_REQUEST_FRAME_EXTENTS => {
+                    // windows may ask before being mapped, so answer with the current extents or none
+                    let extents = xwm
+                        .windows
+                        .iter()
+                        .find(|x| x.window_id() == msg.window)
+                        .map(|surface| surface.frame_extents())
+                        .unwrap_or_default();
+                    conn.change_property32(
+                        PropMode::REPLACE,
+                        msg.window,
+                        xwm.atoms._NET_FRAME_EXTENTS,
+                        AtomEnum::CARDINAL,
+                        &extents,
+                    )?;
+                }
                 x if x == xwm.atoms._NET_WM_MOVERESIZE => {
                     if let Some(surface) = xwm.windows.iter().find(|x| x.window_id() == msg.window).cloned() {
                         drop(_guard);
@@ -2482,6 +2692,16 @@ fn send_selection_notify_resp(
     Ok(())
 }
 
+// Entries of a resource database as stored in the `RESOURCE_MANAGER` property, ignoring comments
+fn parse_xresources(data: &[u8]) -> HashMap<String, String> {
+    String::from_utf8_lossy(data)
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('!'))
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect()
+}
+
 fn send_configure_notify(
     conn: &RustConnection,
     win: &X11Window,
@@ -77,6 +77,7 @@ pub(crate) struct SharedSurfaceState {
     net_state: HashSet<Atom>,
     motif_hints: Vec<u32>,
     window_type: Vec<Atom>,
+    frame_extents: [u32; 4],
 }
 
 pub(super) type Protocols = Vec<WMProtocol>;
@@ -132,6 +133,57 @@ pub enum WmWindowType {
     Utility,
 }
 
+fn constrain_size(
+    size: Size<i32, Logical>,
+    min: Option<Size<i32, Logical>>,
+    max: Option<Size<i32, Logical>>,
+    base: Option<Size<i32, Logical>>,
+    increment: Option<Size<i32, Logical>>,
+    aspect: Option<(f64, f64)>,
+) -> Size<i32, Logical> {
+    let min = min.unwrap_or_default();
+    let max = max
+        .filter(|max| max.w > 0 && max.h > 0)
+        .unwrap_or_else(|| (i32::MAX, i32::MAX).into());
+    let clamp = |size: Size<i32, Logical>| -> Size<i32, Logical> {
+        (
+            size.w.min(max.w).max(min.w).max(1),
+            size.h.min(max.h).max(min.h).max(1),
+        )
+            .into()
+    };
+    let mut size = clamp(size);
+
+    if let Some((min_aspect, max_aspect)) = aspect {
+        let ratio = size.w as f64 / size.h as f64;
+        if ratio < min_aspect {
+            size.h = (size.w as f64 / min_aspect) as i32;
+        } else if ratio > max_aspect {
+            size.w = (size.h as f64 * max_aspect) as i32;
+        }
+        size = clamp(size);
+    }
+
+    if let Some(increment) = increment {
+        let base = base.unwrap_or_default();
+        if size.w > base.w {
+            size.w -= (size.w - base.w) % increment.w;
+        }
+        if size.h > base.h {
+            size.h -= (size.h - base.h) % increment.h;
+        }
+        // rounding down might have violated the minimum size, round up instead
+        while size.w < min.w {
+            size.w += increment.w;
+        }
+        while size.h < min.h {
+            size.h += increment.h;
+        }
+    }
+
+    size
+}
+
 /// Window properties of [`X11Surface`]s
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
@@ -192,6 +244,7 @@ impl X11Surface {
                 net_state: HashSet::new(),
                 motif_hints: vec![0; 5],
                 window_type: Vec::new(),
+                frame_extents: [0; 4],
             })),
             user_data: Arc::new(UserDataMap::new()),
         }
@@ -442,6 +495,55 @@ impl X11Surface {
         res.or_else(|| self.min_size())
     }
 
+    /// Returns the suggested size increments of the underlying X11 window
+    ///
+    /// Windows like terminals prefer to be sized in multiples of their cell size,
+    /// added on top of their [`X11Surface::base_size`].
+    pub fn size_increment(&self) -> Option<Size<i32, Logical>> {
+        let client_scale = self
+            .client_scale
+            .as_ref()
+            .map(|s| s.load(Ordering::Acquire))
+            .unwrap_or(1);
+        let state = self.state.lock().unwrap();
+        state
+            .normal_hints
+            .as_ref()
+            .and_then(|hints| hints.size_increment)
+            .filter(|(w, h)| *w > 0 && *h > 0)
+            .map(Size::<i32, Client>::from)
+            .map(|s| s.to_logical(client_scale as i32))
+    }
+
+    /// Returns the minimum and maximum aspect ratio (width / height) of the underlying X11 window
+    pub fn aspect_ratio(&self) -> Option<(f64, f64)> {
+        let state = self.state.lock().unwrap();
+        let (min, max) = state.normal_hints.as_ref()?.aspect?;
+        if min.numerator <= 0 || min.denominator <= 0 || max.numerator <= 0 || max.denominator <= 0 {
+            return None;
+        }
+        Some((
+            min.numerator as f64 / min.denominator as f64,
+            max.numerator as f64 / max.denominator as f64,
+        ))
+    }
+
+    /// Constrain a size to the size hints of the underlying X11 window
+    ///
+    /// This applies the minimum and maximum size, aspect ratio and size increments
+    /// as described by ICCCM and should be used when picking a size for the window,
+    /// e.g. during interactive resizes or in tiling layouts.
+    pub fn constrain_size(&self, size: Size<i32, Logical>) -> Size<i32, Logical> {
+        constrain_size(
+            size,
+            self.min_size(),
+            self.max_size(),
+            self.base_size(),
+            self.size_increment(),
+            self.aspect_ratio(),
+        )
+    }
+
     /// Returns if the window is in the maximized state
     pub fn is_maximized(&self) -> bool {
         let state = self.state.lock().unwrap();
@@ -476,6 +578,19 @@ impl X11Surface {
             .contains(&self.atoms._NET_WM_STATE_FOCUSED)
     }
 
+    /// Returns if the window demands attention
+    ///
+    /// This is the case if either the urgency hint of `WM_HINTS` or the
+    /// `_NET_WM_STATE_DEMANDS_ATTENTION` state is set. Changes are signaled through
+    /// [`XwmHandler::urgency_changed`](super::XwmHandler::urgency_changed).
+    pub fn is_urgent(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        state.hints.as_ref().is_some_and(|hints| hints.urgent)
+            || state
+                .net_state
+                .contains(&self.atoms._NET_WM_STATE_DEMANDS_ATTENTION)
+    }
+
     /// Returns true if the window is client-side decorated
     pub fn is_decorated(&self) -> bool {
         let state = self.state.lock().unwrap();
@@ -545,6 +660,44 @@ impl X11Surface {
         Ok(())
     }
 
+    /// Sets or clears the `_NET_WM_STATE_DEMANDS_ATTENTION` state of the window.
+    ///
+    /// Compositors should clear the state once the window was activated.
+    /// The urgency hint of `WM_HINTS` is controlled by the client and not affected by this.
+    pub fn set_demands_attention(&self, demands_attention: bool) -> Result<(), ConnectionError> {
+        if demands_attention {
+            self.change_net_state(&[self.atoms._NET_WM_STATE_DEMANDS_ATTENTION], &[])?;
+        } else {
+            self.change_net_state(&[], &[self.atoms._NET_WM_STATE_DEMANDS_ATTENTION])?;
+        }
+        Ok(())
+    }
+
+    /// Returns the frame extents last set with [`X11Surface::set_frame_extents`]
+    ///
+    /// The extents are given as `[left, right, top, bottom]`.
+    pub fn frame_extents(&self) -> [u32; 4] {
+        self.state.lock().unwrap().frame_extents
+    }
+
+    /// Sets the `_NET_FRAME_EXTENTS` of the window.
+    ///
+    /// Compositors drawing server-side decorations around the window should announce their size
+    /// as `[left, right, top, bottom]`, so clients can take them into account when positioning themselves.
+    pub fn set_frame_extents(&self, extents: [u32; 4]) -> Result<(), ConnectionError> {
+        self.state.lock().unwrap().frame_extents = extents;
+        let conn = self.conn.upgrade().ok_or(ConnectionError::UnknownError)?;
+        conn.change_property32(
+            PropMode::REPLACE,
+            self.window,
+            self.atoms._NET_FRAME_EXTENTS,
+            AtomEnum::CARDINAL,
+            &extents,
+        )?;
+        conn.flush()?;
+        Ok(())
+    }
+
     /// Returns the reported window type of the underlying X11 window if set.
     ///
     /// Windows without a window type set should be considered to be of type `Normal` for
@@ -603,6 +756,14 @@ impl X11Surface {
         Ok(())
     }
 
+    pub(super) fn demands_attention(&self) -> bool {
+        self.state
+            .lock()
+            .unwrap()
+            .net_state
+            .contains(&self.atoms._NET_WM_STATE_DEMANDS_ATTENTION)
+    }
+
     fn input_mode(&self) -> InputMode {
         let state = self.state.lock().unwrap();
         match (
@@ -1185,3 +1346,44 @@ impl<D: SeatHandler + 'static> TouchTarget<D> for X11Surface {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::constrain_size;
+
+    #[test]
+    fn constrain_to_size_hints() {
+        // terminal with 8x16 cells and 4px padding
+        let size = constrain_size(
+            (805, 603).into(),
+            Some((20, 36).into()),
+            None,
+            Some((4, 4).into()),
+            Some((8, 16).into()),
+            None,
+        );
+        assert_eq!(size, (804, 596).into());
+
+        // fixed size dialog
+        let size = constrain_size(
+            (1000, 50).into(),
+            Some((400, 300).into()),
+            Some((400, 300).into()),
+            None,
+            None,
+            None,
+        );
+        assert_eq!(size, (400, 300).into());
+
+        // 16:9 video player
+        let size = constrain_size(
+            (1600, 1600).into(),
+            None,
+            None,
+            None,
+            None,
+            Some((16. / 9., 16. / 9.)),
+        );
+        assert_eq!(size, (1600, 900).into());
+    }
+}
@@ -41,6 +41,7 @@ impl Backend for TestState {
 
     fn reset_buffers(&mut self, _output: &Output) {}
     fn early_import(&mut self, _surface: &wl_surface::WlSurface) {}
+    fn window_mapped(&mut self, _output: &Output) {}
     fn update_led_state(&mut self, _led_state: smithay::input::keyboard::LedState) {}
 }
 